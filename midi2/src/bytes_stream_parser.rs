@@ -0,0 +1,651 @@
+use crate::{
+    buffer::{BufferMut, Bytes},
+    error::BufferOverflow,
+    BytesMessage,
+};
+
+/// Parses a MIDI 1.0 byte stream, one byte at a time.
+///
+/// The parser understands running status and system real time messages
+/// interleaved with the bytes of other messages (including sysex).
+/// It does not allocate: short messages are assembled in a small internal
+/// buffer, and sysex messages are assembled in the user supplied sysex buffer.
+/// Emitted messages borrow from the parser and are valid until the next
+/// call to [push](BytesStreamParser::push).
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let mut parser = BytesStreamParser::new([0x0_u8; 32], SysexMode::Whole);
+/// let mut messages = Vec::new();
+///
+/// // a note on, followed by a running status note on with
+/// // a timing clock message in the middle of it
+/// for byte in [0x91, 0x3C, 0x40, 0x3E, 0xF8, 0x40] {
+///     if let Some(Ok(BytesStreamEvent::Message(message))) = parser.push(byte) {
+///         messages.push(message.data().to_vec());
+///     }
+/// }
+///
+/// assert_eq!(
+///     messages,
+///     [vec![0x91, 0x3C, 0x40], vec![0xF8], vec![0x91, 0x3E, 0x40]],
+/// );
+/// ```
+///
+/// Sysex messages which do not fit into the sysex buffer are handled according
+/// to the parser's [SysexMode].
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let mut parser = BytesStreamParser::new([0x0_u8; 6], SysexMode::Chunked);
+/// let mut chunks = Vec::new();
+///
+/// for byte in [0xF0, 0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0xF7] {
+///     if let Some(Ok(BytesStreamEvent::SysexChunk(chunk, message))) = parser.push(byte) {
+///         chunks.push((chunk, message.payload().map(u8::from).collect::<Vec<u8>>()));
+///     }
+/// }
+///
+/// assert_eq!(
+///     chunks,
+///     [
+///         (SysexChunk::Start, vec![0x0, 0x1, 0x2, 0x3]),
+///         (SysexChunk::End, vec![0x4, 0x5, 0x6]),
+///     ],
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct BytesStreamParser<B: Bytes + BufferMut> {
+    sysex_buffer: B,
+    sysex_mode: SysexMode,
+    sysex: Option<SysexState>,
+    running_status: Option<u8>,
+    message: [u8; 3],
+    message_size: usize,
+    received: usize,
+    real_time: [u8; 1],
+}
+
+/// Determines how a [BytesStreamParser] handles sysex messages which
+/// overflow its sysex buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SysexMode {
+    /// Sysex messages are only emitted whole.
+    /// Messages which overflow the sysex buffer are discarded
+    /// and reported with a [BufferOverflow] error.
+    #[default]
+    Whole,
+    /// Sysex messages which overflow the sysex buffer are emitted
+    /// in chunks as the buffer fills up.
+    /// Each chunk is represented by a well formed sysex message.
+    Chunked,
+}
+
+/// The position of a chunk within a sysex message which was split up by a
+/// [BytesStreamParser].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SysexChunk {
+    Start,
+    Continue,
+    End,
+}
+
+/// Items produced by a [BytesStreamParser].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesStreamEvent<'a> {
+    /// A complete message.
+    Message(BytesMessage<&'a [u8]>),
+    /// Part of a sysex message which was too large for the sysex buffer.
+    /// Only emitted in [SysexMode::Chunked].
+    #[cfg(feature = "sysex7")]
+    SysexChunk(SysexChunk, crate::sysex7::Sysex7<&'a [u8]>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SysexState {
+    // number of bytes written into the sysex buffer,
+    // including the start byte
+    size: usize,
+    overflowed: bool,
+    chunked: bool,
+    // a data byte which arrived when the buffer was full.
+    // it will begin the next chunk.
+    carry: Option<u8>,
+}
+
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_END: u8 = 0xF7;
+
+impl<B: Bytes + BufferMut> BytesStreamParser<B> {
+    /// Create a parser which assembles sysex messages into the provided buffer.
+    /// The entire length of the buffer is used, including the sysex start and
+    /// end bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sysex buffer is shorter than three bytes.
+    pub fn new(sysex_buffer: B, sysex_mode: SysexMode) -> Self {
+        assert!(
+            sysex_buffer.buffer().len() >= 3,
+            "Sysex buffer should fit at least one payload byte"
+        );
+        BytesStreamParser {
+            sysex_buffer,
+            sysex_mode,
+            sysex: None,
+            running_status: None,
+            message: [0x0; 3],
+            message_size: 0,
+            received: 0,
+            real_time: [0x0; 1],
+        }
+    }
+
+    /// Discard any partially received message and the running status.
+    pub fn reset(&mut self) {
+        self.sysex = None;
+        self.running_status = None;
+        self.message_size = 0;
+        self.received = 0;
+    }
+
+    /// Feed the next byte of the stream into the parser.
+    ///
+    /// Returns the message completed by this byte, if there is one.
+    /// Data bytes received without a preceding status, and undefined status
+    /// bytes are ignored.
+    /// Sysex messages interrupted by a status byte other than a real time
+    /// message are discarded.
+    pub fn push(&mut self, byte: u8) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+        if let Some(carry) = self.sysex.as_mut().and_then(|sysex| sysex.carry.take()) {
+            self.sysex_buffer.buffer_mut()[1] = carry;
+            if let Some(sysex) = self.sysex.as_mut() {
+                sysex.size = 2;
+            }
+        }
+
+        match byte {
+            0xF8..=0xFF => self.real_time(byte),
+            SYSEX_END if self.sysex.is_some() => self.end_sysex(),
+            0x80..=0xF7 => {
+                self.sysex = None;
+                self.status(byte)
+            }
+            _ if self.sysex.is_some() => self.sysex_data(byte),
+            _ => self.data(byte),
+        }
+    }
+
+    fn real_time(&mut self, byte: u8) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+        match byte {
+            // undefined
+            0xF9 | 0xFD => None,
+            _ => {
+                self.real_time[0] = byte;
+                message_event(&self.real_time[..])
+            }
+        }
+    }
+
+    fn status(&mut self, byte: u8) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+        match byte {
+            0x80..=0xEF => {
+                self.running_status = Some(byte);
+                self.begin_message(byte, channel_voice_message_size(byte));
+                None
+            }
+            SYSEX_START => {
+                self.running_status = None;
+                self.message_size = 0;
+                self.sysex_buffer.buffer_mut()[0] = SYSEX_START;
+                self.sysex = Some(SysexState {
+                    size: 1,
+                    overflowed: false,
+                    chunked: false,
+                    carry: None,
+                });
+                None
+            }
+            0xF1 | 0xF3 => {
+                self.running_status = None;
+                self.begin_message(byte, 2);
+                None
+            }
+            0xF2 => {
+                self.running_status = None;
+                self.begin_message(byte, 3);
+                None
+            }
+            0xF6 => {
+                self.running_status = None;
+                self.message_size = 0;
+                self.message[0] = byte;
+                message_event(&self.message[..1])
+            }
+            // undefined status bytes and stray sysex end bytes
+            _ => {
+                self.running_status = None;
+                self.message_size = 0;
+                None
+            }
+        }
+    }
+
+    fn begin_message(&mut self, status: u8, size: usize) {
+        self.message[0] = status;
+        self.message_size = size;
+        self.received = 1;
+    }
+
+    fn data(&mut self, byte: u8) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+        if self.message_size == 0 {
+            return None;
+        }
+
+        self.message[self.received] = byte;
+        self.received += 1;
+
+        if self.received < self.message_size {
+            return None;
+        }
+
+        let size = self.message_size;
+        if self.running_status.is_some() {
+            // subsequent data bytes reuse the status byte
+            self.received = 1;
+        } else {
+            self.message_size = 0;
+        }
+
+        message_event(&self.message[..size])
+    }
+
+    fn sysex_data(&mut self, byte: u8) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+        let capacity = self.sysex_buffer.buffer().len();
+        let sysex = self.sysex.as_mut()?;
+
+        if sysex.overflowed {
+            return None;
+        }
+
+        // leave room for the end byte
+        if sysex.size < capacity - 1 {
+            self.sysex_buffer.buffer_mut()[sysex.size] = byte;
+            sysex.size += 1;
+            return None;
+        }
+
+        match self.sysex_mode {
+            SysexMode::Whole => {
+                sysex.overflowed = true;
+                Some(Err(BufferOverflow))
+            }
+            SysexMode::Chunked => {
+                let chunk = if sysex.chunked {
+                    SysexChunk::Continue
+                } else {
+                    SysexChunk::Start
+                };
+                let size = sysex.size;
+                sysex.chunked = true;
+                sysex.carry = Some(byte);
+                self.sysex_buffer.buffer_mut()[size] = SYSEX_END;
+                sysex_chunk_event(chunk, &self.sysex_buffer.buffer()[..size + 1])
+            }
+        }
+    }
+
+    fn end_sysex(&mut self) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+        let sysex = self.sysex.take()?;
+
+        if sysex.overflowed {
+            return None;
+        }
+
+        self.sysex_buffer.buffer_mut()[sysex.size] = SYSEX_END;
+        let data = &self.sysex_buffer.buffer()[..sysex.size + 1];
+        if sysex.chunked {
+            sysex_chunk_event(SysexChunk::End, data)
+        } else {
+            message_event(data)
+        }
+    }
+}
+
+fn channel_voice_message_size(status: u8) -> usize {
+    match status & 0xF0 {
+        // program change and channel pressure
+        0xC0 | 0xD0 => 2,
+        _ => 3,
+    }
+}
+
+// messages of types which are disabled by the crate features are dropped
+fn message_event(data: &[u8]) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+    BytesMessage::try_from(data)
+        .ok()
+        .map(|message| Ok(BytesStreamEvent::Message(message)))
+}
+
+#[allow(unused_variables)]
+fn sysex_chunk_event(
+    chunk: SysexChunk,
+    data: &[u8],
+) -> Option<Result<BytesStreamEvent<'_>, BufferOverflow>> {
+    #[cfg(feature = "sysex7")]
+    {
+        crate::sysex7::Sysex7::try_from(data)
+            .ok()
+            .map(|message| Ok(BytesStreamEvent::SysexChunk(chunk, message)))
+    }
+    #[cfg(not(feature = "sysex7"))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn parse<B: Bytes + BufferMut>(
+        parser: &mut BytesStreamParser<B>,
+        bytes: &[u8],
+    ) -> std::vec::Vec<Result<std::vec::Vec<u8>, BufferOverflow>> {
+        let mut ret = std::vec::Vec::new();
+        for byte in bytes {
+            match parser.push(*byte) {
+                Some(Ok(BytesStreamEvent::Message(message))) => {
+                    ret.push(Ok(message.data().to_vec()))
+                }
+                #[cfg(feature = "sysex7")]
+                Some(Ok(BytesStreamEvent::SysexChunk(_, message))) => {
+                    ret.push(Ok(message.data().to_vec()))
+                }
+                Some(Err(e)) => ret.push(Err(e)),
+                None => {}
+            }
+        }
+        ret
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn channel_voice_messages() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(
+                &mut parser,
+                &[0x90, 0x3C, 0x40, 0xC2, 0x05, 0xE0, 0x00, 0x40]
+            ),
+            std::vec![
+                Ok(std::vec![0x90, 0x3C, 0x40]),
+                Ok(std::vec![0xC2, 0x05]),
+                Ok(std::vec![0xE0, 0x00, 0x40]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn running_status() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0x90, 0x3C, 0x40, 0x3E, 0x40, 0x40, 0x00]),
+            std::vec![
+                Ok(std::vec![0x90, 0x3C, 0x40]),
+                Ok(std::vec![0x90, 0x3E, 0x40]),
+                Ok(std::vec![0x90, 0x40, 0x00]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn running_status_two_byte_messages() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xD3, 0x10, 0x20, 0x30]),
+            std::vec![
+                Ok(std::vec![0xD3, 0x10]),
+                Ok(std::vec![0xD3, 0x20]),
+                Ok(std::vec![0xD3, 0x30]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn real_time_within_message() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(
+                &mut parser,
+                &[0xB0, 0xF8, 0x07, 0xFE, 0x64, 0x08, 0xFA, 0x10]
+            ),
+            std::vec![
+                Ok(std::vec![0xF8]),
+                Ok(std::vec![0xFE]),
+                Ok(std::vec![0xB0, 0x07, 0x64]),
+                Ok(std::vec![0xFA]),
+                Ok(std::vec![0xB0, 0x08, 0x10]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn system_common_cancels_running_status() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0x90, 0x3C, 0x40, 0xF3, 0x01, 0x3E, 0x40]),
+            std::vec![Ok(std::vec![0x90, 0x3C, 0x40]), Ok(std::vec![0xF3, 0x01]),],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn system_common_messages() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(
+                &mut parser,
+                &[0xF1, 0x5F, 0xF2, 0x7D, 0x6C, 0xF3, 0x4D, 0xF6]
+            ),
+            std::vec![
+                Ok(std::vec![0xF1, 0x5F]),
+                Ok(std::vec![0xF2, 0x7D, 0x6C]),
+                Ok(std::vec![0xF3, 0x4D]),
+                Ok(std::vec![0xF6]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn data_without_status_is_ignored() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0x3C, 0x40, 0x80, 0x3C, 0x40]),
+            std::vec![Ok(std::vec![0x80, 0x3C, 0x40])],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn undefined_status_bytes_are_ignored() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(
+                &mut parser,
+                &[0xF4, 0x01, 0xF5, 0xF9, 0xFD, 0xF7, 0x01, 0xFC]
+            ),
+            std::vec![Ok(std::vec![0xFC])],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 0x01, 0x02, 0x03, 0xF7]),
+            std::vec![Ok(std::vec![0xF0, 0x01, 0x02, 0x03, 0xF7])],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex_filling_buffer() {
+        let mut parser = BytesStreamParser::new([0x0; 4], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 0x01, 0x02, 0xF7]),
+            std::vec![Ok(std::vec![0xF0, 0x01, 0x02, 0xF7])],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "system-common"))]
+    fn real_time_within_sysex() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 0x01, 0xF8, 0x02, 0xF7]),
+            std::vec![Ok(std::vec![0xF8]), Ok(std::vec![0xF0, 0x01, 0x02, 0xF7])],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn interrupted_sysex_is_discarded() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 0x01, 0x02, 0x90, 0x3C, 0x40, 0xF7]),
+            std::vec![Ok(std::vec![0x90, 0x3C, 0x40])],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn sysex_cancels_running_status() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(
+                &mut parser,
+                &[0x90, 0x3C, 0x40, 0xF0, 0x01, 0xF7, 0x3E, 0x40]
+            ),
+            std::vec![
+                Ok(std::vec![0x90, 0x3C, 0x40]),
+                Ok(std::vec![0xF0, 0x01, 0xF7]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex_overflow() {
+        let mut parser = BytesStreamParser::new([0x0; 4], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 0x01, 0x02, 0x03, 0x04, 0xF7]),
+            std::vec![Err(BufferOverflow)],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex_after_overflow() {
+        let mut parser = BytesStreamParser::new([0x0; 4], SysexMode::Whole);
+        assert_eq!(
+            parse(
+                &mut parser,
+                &[0xF0, 0x01, 0x02, 0x03, 0xF7, 0xF0, 0x01, 0xF7]
+            ),
+            std::vec![Err(BufferOverflow), Ok(std::vec![0xF0, 0x01, 0xF7])],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex_chunks() {
+        let mut parser = BytesStreamParser::new([0x0; 4], SysexMode::Chunked);
+        let mut chunks = std::vec::Vec::new();
+        for byte in [0xF0, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0xF7] {
+            if let Some(Ok(BytesStreamEvent::SysexChunk(chunk, message))) = parser.push(byte) {
+                chunks.push((chunk, message.data().to_vec()));
+            }
+        }
+        assert_eq!(
+            chunks,
+            std::vec![
+                (SysexChunk::Start, std::vec![0xF0, 0x01, 0x02, 0xF7]),
+                (SysexChunk::Continue, std::vec![0xF0, 0x03, 0x04, 0xF7]),
+                (SysexChunk::Continue, std::vec![0xF0, 0x05, 0x06, 0xF7]),
+                (SysexChunk::End, std::vec![0xF0, 0x07, 0xF7]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex_chunks_exactly_filling_buffer() {
+        let mut parser = BytesStreamParser::new([0x0; 4], SysexMode::Chunked);
+        let mut chunks = std::vec::Vec::new();
+        for byte in [0xF0, 0x01, 0x02, 0x03, 0x04, 0xF7] {
+            if let Some(Ok(BytesStreamEvent::SysexChunk(chunk, message))) = parser.push(byte) {
+                chunks.push((chunk, message.data().to_vec()));
+            }
+        }
+        assert_eq!(
+            chunks,
+            std::vec![
+                (SysexChunk::Start, std::vec![0xF0, 0x01, 0x02, 0xF7]),
+                (SysexChunk::End, std::vec![0xF0, 0x03, 0x04, 0xF7]),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex_fitting_buffer_in_chunked_mode_is_a_message() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Chunked);
+        let mut last = None;
+        for byte in [0xF0, 0x01, 0x02, 0xF7] {
+            if let Some(Ok(event)) = parser.push(byte) {
+                last = Some(matches!(event, BytesStreamEvent::Message(_)));
+            }
+        }
+        assert_eq!(last, Some(true));
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn borrowed_sysex_buffer() {
+        let mut buffer = [0x0_u8; 8];
+        let mut parser = BytesStreamParser::new(&mut buffer[..], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0xF0, 0x01, 0x02, 0xF7]),
+            std::vec![Ok(std::vec![0xF0, 0x01, 0x02, 0xF7])],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn reset() {
+        let mut parser = BytesStreamParser::new([0x0; 8], SysexMode::Whole);
+        assert_eq!(
+            parse(&mut parser, &[0x90, 0x3C, 0x40]),
+            std::vec![Ok(std::vec![0x90, 0x3C, 0x40])],
+        );
+        parser.reset();
+        assert_eq!(parse(&mut parser, &[0x3C, 0x40]), std::vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sysex_buffer_too_small() {
+        BytesStreamParser::new([0x0; 2], SysexMode::Whole);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{buffer::Bytes, error::BufferOverflow, Data};
+
+/// Writes MIDI 1.0 messages into a compact byte stream.
+///
+/// When running status is enabled (the default) the status byte of a
+/// channel voice message is omitted whenever it matches the status byte of the
+/// previous channel voice message.
+/// As described by the MIDI 1.0 specification, system exclusive and system
+/// common messages cancel the running status, while system real time
+/// messages leave it untouched.
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let mut serializer = BytesStreamSerializer::new();
+/// let mut buffer = [0x0_u8; 16];
+/// let mut size = 0;
+///
+/// let mut note_on = channel_voice1::NoteOn::<[u8; 3]>::new();
+/// note_on.set_channel(u4::new(0x1));
+/// note_on.set_velocity(u7::new(0x40));
+///
+/// for note_number in [0x3C, 0x3E, 0x40] {
+///     note_on.set_note_number(u7::new(note_number));
+///     size += serializer
+///         .write(&note_on, &mut buffer[size..])
+///         .expect("Buffer is large enough");
+/// }
+///
+/// assert_eq!(&buffer[..size], &[0x91, 0x3C, 0x40, 0x3E, 0x40, 0x40, 0x40]);
+/// ```
+///
+/// Longer runs can be achieved by writing note off messages as note on messages
+/// with zero velocity.
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let mut serializer = BytesStreamSerializer::new();
+/// serializer.set_note_off_as_note_on(true);
+///
+/// let mut buffer = [0x0_u8; 16];
+/// let mut size = 0;
+///
+/// let mut note_on = channel_voice1::NoteOn::<[u8; 3]>::new();
+/// note_on.set_note_number(u7::new(0x3C));
+/// note_on.set_velocity(u7::new(0x40));
+///
+/// let mut note_off = channel_voice1::NoteOff::<[u8; 3]>::new();
+/// note_off.set_note_number(u7::new(0x3C));
+/// note_off.set_velocity(u7::new(0x40));
+///
+/// size += serializer.write(&note_on, &mut buffer[size..]).unwrap();
+/// size += serializer.write(&note_off, &mut buffer[size..]).unwrap();
+///
+/// assert_eq!(&buffer[..size], &[0x90, 0x3C, 0x40, 0x3C, 0x00]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytesStreamSerializer {
+    running_status_enabled: bool,
+    note_off_as_note_on: bool,
+    running_status: Option<u8>,
+}
+
+impl core::default::Default for BytesStreamSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+
+impl BytesStreamSerializer {
+    /// Create a serializer with running status enabled
+    /// and note off conversion disabled.
+    pub fn new() -> Self {
+        BytesStreamSerializer {
+            running_status_enabled: true,
+            note_off_as_note_on: false,
+            running_status: None,
+        }
+    }
+
+    pub fn running_status(&self) -> bool {
+        self.running_status_enabled
+    }
+
+    pub fn set_running_status(&mut self, enabled: bool) {
+        self.running_status_enabled = enabled;
+        self.running_status = None;
+    }
+
+    pub fn note_off_as_note_on(&self) -> bool {
+        self.note_off_as_note_on
+    }
+
+    /// When enabled, note off messages are written as note on messages
+    /// with zero velocity.
+    /// Note that the release velocity of the note off message is lost.
+    pub fn set_note_off_as_note_on(&mut self, enabled: bool) {
+        self.note_off_as_note_on = enabled;
+    }
+
+    /// Forget the current running status so that the status byte
+    /// is written with the next channel voice message.
+    ///
+    /// Useful when the receiver may have missed earlier parts of the stream.
+    pub fn reset(&mut self) {
+        self.running_status = None;
+    }
+
+    /// Write the next message of the stream into the provided buffer.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// # Fails
+    ///
+    /// When the buffer is too small to fit the serialized message.
+    /// The running status is left unchanged in this case.
+    pub fn write<B: Bytes, M: Data<B>>(
+        &mut self,
+        message: &M,
+        buffer: &mut [u8],
+    ) -> Result<usize, BufferOverflow> {
+        let data = message.data();
+        let Some(&status) = data.first() else {
+            return Ok(0);
+        };
+
+        match status {
+            0x80..=0xEF => {
+                let mut status = status;
+                let mut velocity = data.get(2).copied();
+                if self.note_off_as_note_on && status & 0xF0 == NOTE_OFF {
+                    status = NOTE_ON | (status & 0x0F);
+                    velocity = Some(0x0);
+                }
+
+                let omit_status =
+                    self.running_status_enabled && self.running_status == Some(status);
+                let size = data.len() - usize::from(omit_status);
+                if buffer.len() < size {
+                    return Err(BufferOverflow);
+                }
+
+                let mut index = 0;
+                if !omit_status {
+                    buffer[index] = status;
+                    index += 1;
+                }
+                buffer[index] = data[1];
+                index += 1;
+                if let Some(velocity) = velocity {
+                    buffer[index] = velocity;
+                }
+
+                if self.running_status_enabled {
+                    self.running_status = Some(status);
+                }
+                Ok(size)
+            }
+            _ => {
+                if buffer.len() < data.len() {
+                    return Err(BufferOverflow);
+                }
+                buffer[..data.len()].copy_from_slice(data);
+                if status < 0xF8 {
+                    // system exclusive and system common messages
+                    self.running_status = None;
+                }
+                Ok(data.len())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "channel-voice1")]
+    use super::*;
+    #[cfg(feature = "channel-voice1")]
+    use crate::BytesMessage;
+    #[cfg(feature = "channel-voice1")]
+    use pretty_assertions::assert_eq;
+
+    #[cfg(feature = "channel-voice1")]
+    fn write_all(serializer: &mut BytesStreamSerializer, messages: &[&[u8]]) -> std::vec::Vec<u8> {
+        let mut ret = std::vec::Vec::new();
+        let mut buffer = [0x0; 32];
+        for message in messages {
+            let message = BytesMessage::try_from(*message).unwrap();
+            let size = serializer.write(&message, &mut buffer[..]).unwrap();
+            ret.extend_from_slice(&buffer[..size]);
+        }
+        ret
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn running_status() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(
+                &mut serializer,
+                &[
+                    &[0x90, 0x3C, 0x40],
+                    &[0x90, 0x3E, 0x40],
+                    &[0x90, 0x40, 0x40]
+                ]
+            ),
+            &[0x90, 0x3C, 0x40, 0x3E, 0x40, 0x40, 0x40],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn status_change() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(
+                &mut serializer,
+                &[
+                    &[0x90, 0x3C, 0x40],
+                    &[0x91, 0x3E, 0x40],
+                    &[0xC1, 0x05],
+                    &[0xC1, 0x06]
+                ]
+            ),
+            &[0x90, 0x3C, 0x40, 0x91, 0x3E, 0x40, 0xC1, 0x05, 0x06],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn running_status_disabled() {
+        let mut serializer = BytesStreamSerializer::new();
+        serializer.set_running_status(false);
+        assert_eq!(
+            write_all(&mut serializer, &[&[0x90, 0x3C, 0x40], &[0x90, 0x3E, 0x40]]),
+            &[0x90, 0x3C, 0x40, 0x90, 0x3E, 0x40],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn note_off_unchanged_by_default() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(&mut serializer, &[&[0x90, 0x3C, 0x40], &[0x80, 0x3C, 0x20]]),
+            &[0x90, 0x3C, 0x40, 0x80, 0x3C, 0x20],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn note_off_as_note_on() {
+        let mut serializer = BytesStreamSerializer::new();
+        serializer.set_note_off_as_note_on(true);
+        assert_eq!(
+            write_all(
+                &mut serializer,
+                &[
+                    &[0x92, 0x3C, 0x40],
+                    &[0x82, 0x3C, 0x20],
+                    &[0x83, 0x3C, 0x20]
+                ]
+            ),
+            &[0x92, 0x3C, 0x40, 0x3C, 0x00, 0x93, 0x3C, 0x00],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn real_time_keeps_running_status() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(
+                &mut serializer,
+                &[&[0x90, 0x3C, 0x40], &[0xF8], &[0x90, 0x3E, 0x40]]
+            ),
+            &[0x90, 0x3C, 0x40, 0xF8, 0x3E, 0x40],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn system_common_cancels_running_status() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(
+                &mut serializer,
+                &[&[0x90, 0x3C, 0x40], &[0xF3, 0x01], &[0x90, 0x3E, 0x40]]
+            ),
+            &[0x90, 0x3C, 0x40, 0xF3, 0x01, 0x90, 0x3E, 0x40],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "sysex7"))]
+    fn sysex_cancels_running_status() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(
+                &mut serializer,
+                &[
+                    &[0x90, 0x3C, 0x40],
+                    &[0xF0, 0x01, 0x02, 0xF7],
+                    &[0x90, 0x3E, 0x40]
+                ]
+            ),
+            &[0x90, 0x3C, 0x40, 0xF0, 0x01, 0x02, 0xF7, 0x90, 0x3E, 0x40],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn reset() {
+        let mut serializer = BytesStreamSerializer::new();
+        assert_eq!(
+            write_all(&mut serializer, &[&[0x90, 0x3C, 0x40]]),
+            &[0x90, 0x3C, 0x40]
+        );
+        serializer.reset();
+        assert_eq!(
+            write_all(&mut serializer, &[&[0x90, 0x3E, 0x40]]),
+            &[0x90, 0x3E, 0x40]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn buffer_overflow() {
+        let mut serializer = BytesStreamSerializer::new();
+        let message = BytesMessage::try_from(&[0x90_u8, 0x3C, 0x40][..]).unwrap();
+        let mut buffer = [0x0; 2];
+        assert_eq!(
+            serializer.write(&message, &mut buffer[..]),
+            Err(BufferOverflow)
+        );
+        let mut buffer = [0x0; 3];
+        assert_eq!(serializer.write(&message, &mut buffer[..]), Ok(3));
+        let mut buffer = [0x0; 2];
+        assert_eq!(serializer.write(&message, &mut buffer[..]), Ok(2));
+        assert_eq!(buffer, [0x3C, 0x40]);
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn channel_voice1_messages() {
+        use crate::channel_voice1::{ChannelVoice1, ControlChange};
+
+        let mut serializer = BytesStreamSerializer::new();
+        let mut buffer = [0x0; 3];
+        let message: ChannelVoice1<[u8; 3]> = ControlChange::<[u8; 3]>::new().into();
+        assert_eq!(serializer.write(&message, &mut buffer[..]), Ok(3));
+        assert_eq!(buffer, [0xB0, 0x00, 0x00]);
+        assert_eq!(serializer.write(&message, &mut buffer[..]), Ok(2));
+    }
+}
@@ -1,12 +1,30 @@
 #![doc = include_str!("ci/README.md")]
 
+mod ack;
 mod common_properties;
 mod device_id;
 mod discovery;
+mod discovery_reply;
+mod endpoint_info_inquiry;
+mod endpoint_info_reply;
+mod invalidate_muid;
+mod nak;
+mod profile_configuration;
+pub mod property_exchange;
 mod version;
 
+pub mod mcoded7;
+
+pub use ack::*;
 pub use device_id::*;
 pub use discovery::*;
+pub use discovery_reply::*;
+pub use endpoint_info_inquiry::*;
+pub use endpoint_info_reply::*;
+pub use invalidate_muid::*;
+pub use nak::*;
+pub use profile_configuration::*;
+pub use property_exchange::*;
 pub use version::*;
 
 pub trait Ci<B: crate::buffer::Bytes> {
@@ -0,0 +1,187 @@
+use crate::{ci, detail::property};
+
+pub(crate) const STATUS: u8 = 0x7D;
+
+/// Positive acknowledgement of a MIDI-CI transaction.
+#[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 25))]
+struct Ack {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x2)]
+    original_transaction: ux::u7,
+    #[property(ci::common_properties::U7Property<15>)]
+    #[version(0x2)]
+    status_code: ux::u7,
+    #[property(ci::common_properties::U7Property<16>)]
+    #[version(0x2)]
+    status_data: ux::u7,
+    #[property(DetailsProperty)]
+    #[version(0x2)]
+    details: [ux::u7; 5],
+    #[property(ci::common_properties::DataWriteProperty<22, 2>)]
+    #[version(0x2)]
+    #[writeonly]
+    #[resize]
+    message_text: &[ux::u7],
+    #[property(ci::common_properties::DataReadProperty<22, 2>)]
+    #[version(0x2)]
+    #[readonly]
+    message_text: &[u8],
+}
+
+pub(crate) struct DetailsProperty;
+
+impl<B: crate::buffer::Bytes> property::Property<B> for DetailsProperty {
+    type Type = [ux::u7; 5];
+}
+
+impl<'a, B: crate::buffer::Bytes> property::ReadProperty<'a, B> for DetailsProperty {
+    fn read(buffer: &'a B) -> Self::Type {
+        let mut details = [ux::u7::default(); 5];
+        for (dst, src) in details.iter_mut().zip(buffer.buffer()[17..22].iter()) {
+            *dst = ux::u7::new(src & 0x7F);
+        }
+        details
+    }
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+}
+
+impl<B: crate::buffer::Bytes + crate::buffer::BufferMut> property::WriteProperty<B>
+    for DetailsProperty
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        for (dst, src) in buffer.buffer_mut()[17..22].iter_mut().zip(v.iter()) {
+            *dst = (*src).into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x7D, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::FunctionBlock);
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        message.set_original_transaction(ux::u7::new(0x34));
+        message.set_status_code(ux::u7::new(0x10));
+        message.set_status_data(ux::u7::new(0x05));
+        message.set_details([
+            ux::u7::new(0x1),
+            ux::u7::new(0x2),
+            ux::u7::new(0x3),
+            ux::u7::new(0x4),
+            ux::u7::new(0x5),
+        ]);
+        message.set_message_text(&[ux::u7::new(b'O'), ux::u7::new(b'K')]);
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7D, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x34, 0x10, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x02, 0x00, b'O', b'K', 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn device_id() {
+        use crate::ci::Ci;
+
+        let mut message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Group);
+        assert_eq!(message.device_id(), ci::DeviceId::Group);
+    }
+
+    #[test]
+    fn details() {
+        let mut message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        let details = [
+            ux::u7::new(0x7F),
+            ux::u7::new(0x0),
+            ux::u7::new(0x3A),
+            ux::u7::new(0x0),
+            ux::u7::new(0x11),
+        ];
+        message.set_details(details);
+        assert_eq!(message.details(), details);
+    }
+
+    #[test]
+    fn message_text() {
+        let mut message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        message.set_message_text(&[ux::u7::new(b'O'), ux::u7::new(b'K')]);
+        assert_eq!(message.message_text(), b"OK");
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = Ack::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7D, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x34, 0x10, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x02, 0x00, b'O', b'K', 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.original_transaction(), ux::u7::new(0x34));
+        assert_eq!(message.status_code(), ux::u7::new(0x10));
+        assert_eq!(message.status_data(), ux::u7::new(0x05));
+        assert_eq!(message.message_text(), b"OK");
+    }
+
+    #[test]
+    fn try_from_slice_invalid_device_id() {
+        assert!(Ack::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x50, 0x0D, 0x7D, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
@@ -244,3 +244,204 @@ impl<B: Bytes + BufferMut> property::WriteProperty<B> for BroadcastDestinationPr
         Default::default()
     }
 }
+
+pub struct U7Property<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for U7Property<INDEX> {
+    type Type = ux::u7;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for U7Property<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        ux::u7::new(buffer.buffer()[INDEX] & 0x7F)
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B> for U7Property<INDEX> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        buffer.buffer_mut()[INDEX] = v.into();
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+pub struct U14Property<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for U14Property<INDEX> {
+    type Type = ux::u14;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for U14Property<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        use crate::detail::Encode7Bit;
+        ux::u14::from_u7s(&buffer.buffer()[INDEX..INDEX + 2])
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B> for U14Property<INDEX> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        use crate::detail::Encode7Bit;
+        v.to_u7s(&mut buffer.buffer_mut()[INDEX..INDEX + 2]);
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+pub struct MuidProperty<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for MuidProperty<INDEX> {
+    type Type = ux::u28;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for MuidProperty<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        use crate::detail::Encode7Bit;
+        ux::u28::from_u7s(&buffer.buffer()[INDEX..INDEX + 4])
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B> for MuidProperty<INDEX> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        use crate::detail::Encode7Bit;
+        v.to_u7s(&mut buffer.buffer_mut()[INDEX..INDEX + 4]);
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+// variable sized data fields are preceded by their length,
+// encoded as LENGTH_SIZE 7 bit bytes, least significant first
+pub(crate) fn read_length(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |length, b| (length << 7) | usize::from(b & 0x7F))
+}
+
+pub(crate) fn write_length(bytes: &mut [u8], length: usize) {
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = ((length >> (7 * i)) & 0x7F) as u8;
+    }
+}
+
+pub(crate) fn max_length(length_size: usize) -> usize {
+    (1 << (7 * length_size)) - 1
+}
+
+/// A variable length block of 7 bit data, preceded by its length
+/// at `OFFSET` and running up to the end of the message.
+pub struct DataWriteProperty<'a, const OFFSET: usize, const LENGTH_SIZE: usize>(
+    core::marker::PhantomData<&'a u8>,
+);
+
+impl<'a, const OFFSET: usize, const LENGTH_SIZE: usize> DataWriteProperty<'a, OFFSET, LENGTH_SIZE> {
+    fn length(data: &[ux::u7]) -> usize {
+        data.len().min(max_length(LENGTH_SIZE))
+    }
+    fn message_size(data: &[ux::u7]) -> usize {
+        OFFSET + LENGTH_SIZE + Self::length(data) + 1
+    }
+}
+
+impl<'a, const OFFSET: usize, const LENGTH_SIZE: usize, B: Bytes> property::Property<B>
+    for DataWriteProperty<'a, OFFSET, LENGTH_SIZE>
+{
+    type Type = &'a [ux::u7];
+}
+
+impl<const OFFSET: usize, const LENGTH_SIZE: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for DataWriteProperty<'_, OFFSET, LENGTH_SIZE>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let length = Self::length(v);
+        let data_offset = OFFSET + LENGTH_SIZE;
+        write_length(&mut buffer.buffer_mut()[OFFSET..data_offset], length);
+        for (dst, src) in buffer.buffer_mut()[data_offset..data_offset + length]
+            .iter_mut()
+            .zip(v.iter())
+        {
+            *dst = (*src).into();
+        }
+    }
+}
+
+impl<const OFFSET: usize, const LENGTH_SIZE: usize, B: Bytes + BufferMut>
+    property::ResizeProperty<B> for DataWriteProperty<'_, OFFSET, LENGTH_SIZE>
+{
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        let size = Self::message_size(value);
+        buffer.resize(size);
+        buffer.buffer_mut()[size - 1] = 0xF7;
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        let size = Self::message_size(value);
+        buffer.try_resize(size)?;
+        buffer.buffer_mut()[size - 1] = 0xF7;
+        Ok(())
+    }
+}
+
+pub struct DataReadProperty<'a, const OFFSET: usize, const LENGTH_SIZE: usize>(
+    core::marker::PhantomData<&'a u8>,
+);
+
+impl<'a, const OFFSET: usize, const LENGTH_SIZE: usize, B: Bytes> property::Property<B>
+    for DataReadProperty<'a, OFFSET, LENGTH_SIZE>
+{
+    type Type = &'a [u8];
+}
+
+impl<'a, const OFFSET: usize, const LENGTH_SIZE: usize, B: 'a + Bytes> property::ReadProperty<'a, B>
+    for DataReadProperty<'a, OFFSET, LENGTH_SIZE>
+{
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        let data_offset = OFFSET + LENGTH_SIZE;
+        let end = data_offset + read_length(&buffer.buffer()[OFFSET..data_offset]);
+        if buffer.buffer().get(end) == Some(&0xF7) {
+            Ok(())
+        } else {
+            Err(crate::error::InvalidData(
+                "Data length field does not match the message size",
+            ))
+        }
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let data_offset = OFFSET + LENGTH_SIZE;
+        let length = read_length(&buffer.buffer()[OFFSET..data_offset]);
+        let end = (data_offset + length).min(buffer.buffer().len());
+        &buffer.buffer()[data_offset..end]
+    }
+}
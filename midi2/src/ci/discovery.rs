@@ -57,7 +57,7 @@ struct DiscoveryQuery {
     output_path_id: ux::u7,
 }
 
-struct DeviceManufacturerProperty;
+pub(crate) struct DeviceManufacturerProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for DeviceManufacturerProperty {
     type Type = [ux::u7; 3];
@@ -92,7 +92,7 @@ impl<B: crate::buffer::Bytes + crate::buffer::BufferMut> property::WriteProperty
     }
 }
 
-struct DeviceFamilyProperty;
+pub(crate) struct DeviceFamilyProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for DeviceFamilyProperty {
     type Type = ux::u14;
@@ -123,7 +123,7 @@ impl<B: crate::buffer::Bytes + crate::buffer::BufferMut> property::WriteProperty
     }
 }
 
-struct DeviceModelNumberProperty;
+pub(crate) struct DeviceModelNumberProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for DeviceModelNumberProperty {
     type Type = ux::u14;
@@ -154,7 +154,7 @@ impl<B: crate::buffer::Bytes + crate::buffer::BufferMut> property::WriteProperty
     }
 }
 
-struct SoftwareVersionProperty;
+pub(crate) struct SoftwareVersionProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for SoftwareVersionProperty {
     type Type = [ux::u7; 4];
@@ -191,7 +191,7 @@ impl<B: crate::buffer::Bytes + crate::buffer::BufferMut> property::WriteProperty
     }
 }
 
-struct CiSupportProperty<const BIT: usize>;
+pub(crate) struct CiSupportProperty<const BIT: usize>;
 
 impl<const BIT: usize, B: crate::buffer::Bytes> property::Property<B> for CiSupportProperty<BIT> {
     type Type = bool;
@@ -224,7 +224,7 @@ impl<const BIT: usize, B: crate::buffer::Bytes + crate::buffer::BufferMut>
     }
 }
 
-struct MaxSysexSizeProperty;
+pub(crate) struct MaxSysexSizeProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for MaxSysexSizeProperty {
     type Type = ux::u28;
@@ -255,7 +255,7 @@ impl<B: crate::buffer::Bytes + crate::buffer::BufferMut> property::WriteProperty
     }
 }
 
-struct OutputPathIdProperty;
+pub(crate) struct OutputPathIdProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for OutputPathIdProperty {
     type Type = ux::u7;
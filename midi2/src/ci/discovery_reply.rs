@@ -0,0 +1,222 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x71;
+
+/// Sent in response to a [DiscoveryQuery](crate::ci::DiscoveryQuery).
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 31),
+    SupportedVersion(version = 0x2, min_size = 33)
+)]
+struct DiscoveryReply {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::discovery::DeviceManufacturerProperty)]
+    #[version(0x1)]
+    device_manufacturer: [ux::u7; 3],
+    #[property(ci::discovery::DeviceFamilyProperty)]
+    #[version(0x1)]
+    device_family: ux::u14,
+    #[property(ci::discovery::DeviceModelNumberProperty)]
+    #[version(0x1)]
+    model_number: ux::u14,
+    #[property(ci::discovery::SoftwareVersionProperty)]
+    #[version(0x1)]
+    software_version: [ux::u7; 4],
+    #[property(ci::discovery::CiSupportProperty<3>)]
+    #[version(0x1)]
+    process_inquiry_supported: bool,
+    #[property(ci::discovery::CiSupportProperty<4>)]
+    #[version(0x1)]
+    property_exchange_supported: bool,
+    #[property(ci::discovery::CiSupportProperty<5>)]
+    #[version(0x1)]
+    profile_configuration_supported: bool,
+    #[property(ci::discovery::CiSupportProperty<6>)]
+    #[version(0x1)]
+    protocol_negotiation_supported: bool,
+    #[property(ci::discovery::MaxSysexSizeProperty)]
+    #[version(0x1)]
+    max_sysex_size: ux::u28,
+    #[property(ci::discovery::OutputPathIdProperty)]
+    #[version(0x2)]
+    output_path_id: ux::u7,
+    #[property(ci::common_properties::U7Property<31>)]
+    #[version(0x2)]
+    function_block: ux::u7,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = DiscoveryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        message.set_device_manufacturer([ux::u7::new(0x21), ux::u7::new(0x66), ux::u7::new(0x61)]);
+        message.set_device_family(ux::u14::new(0x278A));
+        message.set_model_number(ux::u14::new(0x2269));
+        message.set_software_version([
+            ux::u7::new(0x30),
+            ux::u7::new(0x49),
+            ux::u7::new(0xB),
+            ux::u7::new(0x63),
+        ]);
+        message.set_property_exchange_supported(true);
+        message.set_profile_configuration_supported(true);
+        message.set_max_sysex_size(ux::u28::new(0xEF6EFE2));
+        message.set_output_path_id(ux::u7::new(0x25));
+        message.set_function_block(ux::u7::new(0x7F));
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0,
+                0x7E,
+                0x7F,
+                0x0D,
+                0x71,
+                0x02,
+                0x59,
+                0x33,
+                0x23,
+                0x5A,
+                0x33,
+                0x2B,
+                0x07,
+                0x15,
+                0x21,
+                0x66,
+                0x61,
+                0x0A,
+                0x4F,
+                0x69,
+                0x44,
+                0x30,
+                0x49,
+                0x0B,
+                0x63,
+                0b0000_1100,
+                0x62,
+                0x5F,
+                0x5B,
+                0x77,
+                0x25,
+                0x7F,
+                0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = DiscoveryReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x71, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn new_v1() {
+        use crate::Data;
+
+        let message = DiscoveryReply::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x71, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        use crate::Data;
+
+        let message = DiscoveryReply::<0x2, [u8; 33]>::try_new().expect("Buffer is large enough");
+        assert_eq!(message.data().len(), 33);
+    }
+
+    #[test]
+    fn try_new_buffer_too_small() {
+        assert!(DiscoveryReply::<0x2, [u8; 32]>::try_new().is_err());
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = DiscoveryReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x71, 0x02, 0x0, 0x0, 0x0, 0x0, 0x33, 0x2B, 0x07, 0x15,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x05, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.function_block(), ux::u7::new(0x05));
+    }
+
+    #[test]
+    fn try_from_slice_wrong_status() {
+        assert!(DiscoveryReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x70, 0x02, 0x0, 0x0, 0x0, 0x0, 0x33, 0x2B, 0x07, 0x15,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x05, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn destination() {
+        use crate::ci::Ci;
+        let mut message = DiscoveryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        assert_eq!(message.destination(), ux::u28::new(0x2A1D5B3));
+    }
+
+    #[test]
+    fn device_id() {
+        use crate::ci::{Ci, DeviceId};
+        let message = DiscoveryReply::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(message.device_id(), DeviceId::FunctionBlock);
+    }
+
+    #[test]
+    fn function_block() {
+        use crate::Data;
+
+        let mut message = DiscoveryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_function_block(ux::u7::new(0x3));
+        assert_eq!(message.function_block(), ux::u7::new(0x3));
+        assert_eq!(message.data()[31], 0x3);
+    }
+}
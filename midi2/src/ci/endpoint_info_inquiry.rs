@@ -0,0 +1,76 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x72;
+
+/// Requests a piece of information about the remote MIDI-CI device,
+/// such as its product instance id (status `0x00`).
+#[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 16))]
+struct EndpointInfoInquiry {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x2)]
+    status: ux::u7,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = EndpointInfoInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        message.set_status(ux::u7::new(0x0));
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x72, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x00, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = EndpointInfoInquiry::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x72, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x00, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.status(), ux::u7::new(0x0));
+    }
+
+    #[test]
+    fn header() {
+        use crate::ci::{Ci, DeviceId};
+
+        let mut message = EndpointInfoInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xB48D9D9));
+        assert_eq!(message.source(), ux::u28::new(0xB48D9D9));
+        assert_eq!(message.device_id(), DeviceId::FunctionBlock);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x73;
+
+/// Sent in response to an [EndpointInfoInquiry](crate::ci::EndpointInfoInquiry).
+#[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 18))]
+struct EndpointInfoReply {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x2)]
+    status: ux::u7,
+    #[property(ci::common_properties::DataWriteProperty<15, 2>)]
+    #[version(0x2)]
+    #[writeonly]
+    #[resize]
+    information: &[ux::u7],
+    #[property(ci::common_properties::DataReadProperty<15, 2>)]
+    #[version(0x2)]
+    #[readonly]
+    information: &[u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = EndpointInfoReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0xF7,
+            ]
+        );
+        assert!(message.information().is_empty());
+    }
+
+    #[test]
+    fn information() {
+        use crate::Data;
+
+        let mut message = EndpointInfoReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_information(&[ux::u7::new(0x41), ux::u7::new(0x42), ux::u7::new(0x43)]);
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x03, 0x0, 0x41, 0x42, 0x43, 0xF7,
+            ]
+        );
+        assert_eq!(message.information(), &[0x41, 0x42, 0x43]);
+    }
+
+    #[test]
+    fn information_shrink() {
+        use crate::Data;
+
+        let mut message = EndpointInfoReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_information(&[ux::u7::new(0x41), ux::u7::new(0x42), ux::u7::new(0x43)]);
+        message.set_information(&[ux::u7::new(0x44)]);
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x44, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_set_information() {
+        use crate::Data;
+
+        let mut buffer = [0x0; 20];
+        let mut message = EndpointInfoReply::<0x2, _>::try_new_with_buffer(&mut buffer[..])
+            .expect("Buffer is large enough");
+        message
+            .try_set_information(&[ux::u7::new(0x41), ux::u7::new(0x42)])
+            .expect("Buffer is large enough");
+        assert_eq!(message.data().len(), 20);
+        assert!(message
+            .try_set_information(&[ux::u7::new(0x41), ux::u7::new(0x42), ux::u7::new(0x43)])
+            .is_err());
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = EndpointInfoReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x02, 0x0, 0x41, 0x42, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.information(), &[0x41, 0x42]);
+    }
+
+    #[test]
+    fn try_from_slice_inconsistent_length() {
+        assert!(EndpointInfoReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x03, 0x0, 0x41, 0x42, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn try_from_slice_v1() {
+        assert!(EndpointInfoReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x7E;
+
+/// Broadcast to notify all MIDI-CI devices that the target MUID is
+/// no longer in use.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 19),
+    SupportedVersion(version = 0x2, min_size = 19)
+)]
+struct InvalidateMuid {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::BroadcastDestinationProperty)]
+    #[version(0x1)]
+    broadcast_destination: (),
+    #[property(ci::common_properties::MuidProperty<14>)]
+    #[version(0x1)]
+    target_muid: ux::u28,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_target_muid(ux::u28::new(0x2A1D5B3));
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x33, 0x2B, 0x07, 0x15, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn new_v1() {
+        use crate::Data;
+
+        let message = InvalidateMuid::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x01, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn target_muid() {
+        let mut message = InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        message.set_target_muid(ux::u28::new(0x2A1D5B3));
+        assert_eq!(message.target_muid(), ux::u28::new(0x2A1D5B3));
+    }
+
+    #[test]
+    fn destination() {
+        use crate::ci::Ci;
+        let message = InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(message.destination(), ux::u28::MAX);
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = InvalidateMuid::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x01, 0x59, 0x33, 0x23, 0x5A, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x33, 0x2B, 0x07, 0x15, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.target_muid(), ux::u28::new(0x2A1D5B3));
+    }
+
+    #[test]
+    fn try_from_slice_not_broadcast() {
+        assert!(InvalidateMuid::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x01, 0x59, 0x33, 0x23, 0x5A, 0x7F, 0x7F, 0x7F, 0x0,
+                0x33, 0x2B, 0x07, 0x15, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Streaming Mcoded7 encoding and decoding.
+//!
+//! Mcoded7 is the 8-bit to 7-bit scheme used for property exchange bodies
+//! sent with `"mutualEncoding": "Mcoded7"`. Each group of up to seven bytes
+//! is preceded by a byte carrying their most significant bits, the first
+//! byte of the group in bit 6.
+//!
+//! The [Encoder] and [Decoder] are iterator adaptors, so they compose directly
+//! with [Sysex::payload](crate::Sysex::payload) and
+//! [Sysex::set_payload](crate::Sysex::set_payload) without any intermediate buffer.
+//!
+//! ```rust
+//! use midi2::{ci::mcoded7, prelude::*, sysex7::Sysex7};
+//!
+//! let data = [0x81_u8, 0x02, 0xFF];
+//!
+//! let mut message = Sysex7::<Vec<u8>>::new();
+//! message.set_payload(mcoded7::encode(data.iter().copied()));
+//! assert_eq!(message.data(), &[0xF0, 0x50, 0x01, 0x02, 0x7F, 0xF7]);
+//!
+//! let decoded: Vec<u8> = mcoded7::decode(message.payload()).collect();
+//! assert_eq!(decoded, data);
+//! ```
+
+#[cfg(feature = "zlib")]
+pub mod zlib;
+
+const GROUP_SIZE: usize = 7;
+
+/// The number of 7-bit bytes required to Mcoded7 encode `size` bytes.
+pub fn encoded_size(size: usize) -> usize {
+    size + (size + GROUP_SIZE - 1) / GROUP_SIZE
+}
+
+/// The number of bytes produced by decoding `size` Mcoded7 encoded 7-bit bytes.
+pub fn decoded_size(size: usize) -> usize {
+    size - (size + GROUP_SIZE) / (GROUP_SIZE + 1)
+}
+
+/// Mcoded7 encode the provided bytes.
+pub fn encode<I: core::iter::IntoIterator<Item = u8>>(data: I) -> Encoder<I::IntoIter> {
+    Encoder::new(data.into_iter())
+}
+
+/// Decode the provided Mcoded7 encoded 7-bit bytes.
+pub fn decode<I: core::iter::IntoIterator<Item = ux::u7>>(data: I) -> Decoder<I::IntoIter> {
+    Decoder::new(data.into_iter())
+}
+
+/// Iterator adaptor yielding the Mcoded7 encoding of the wrapped bytes.
+#[derive(Debug, Clone)]
+pub struct Encoder<I> {
+    iter: I,
+    group: [ux::u7; GROUP_SIZE],
+    group_size: usize,
+    index: usize,
+}
+
+impl<I: core::iter::Iterator<Item = u8>> Encoder<I> {
+    pub fn new(iter: I) -> Self {
+        Encoder {
+            iter,
+            group: Default::default(),
+            group_size: 0,
+            index: 0,
+        }
+    }
+}
+
+impl<I: core::iter::Iterator<Item = u8>> core::iter::Iterator for Encoder<I> {
+    type Item = ux::u7;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.group_size {
+            let byte = self.group[self.index];
+            self.index += 1;
+            return Some(byte);
+        }
+
+        let mut msbs = 0x0_u8;
+        self.group_size = 0;
+        self.index = 0;
+        while self.group_size < GROUP_SIZE {
+            let Some(byte) = self.iter.next() else {
+                break;
+            };
+            msbs |= (byte >> 7) << (GROUP_SIZE - 1 - self.group_size);
+            self.group[self.group_size] = ux::u7::new(byte & 0x7F);
+            self.group_size += 1;
+        }
+
+        if self.group_size == 0 {
+            None
+        } else {
+            Some(ux::u7::new(msbs))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.group_size - self.index;
+        let (lower, upper) = self.iter.size_hint();
+        (
+            remaining + encoded_size(lower),
+            upper.and_then(|upper| encoded_size(upper).checked_add(remaining)),
+        )
+    }
+}
+
+impl<I: core::iter::ExactSizeIterator<Item = u8>> core::iter::ExactSizeIterator for Encoder<I> {}
+
+impl<I: core::iter::FusedIterator<Item = u8>> core::iter::FusedIterator for Encoder<I> {}
+
+/// Iterator adaptor yielding the bytes decoded from the wrapped Mcoded7 encoded data.
+///
+/// A trailing group may be shorter than seven bytes,
+/// in which case the unused bits of its header byte are ignored.
+#[derive(Debug, Clone)]
+pub struct Decoder<I> {
+    iter: I,
+    msbs: u8,
+    index: usize,
+}
+
+impl<I: core::iter::Iterator<Item = ux::u7>> Decoder<I> {
+    pub fn new(iter: I) -> Self {
+        Decoder {
+            iter,
+            msbs: 0x0,
+            index: GROUP_SIZE,
+        }
+    }
+}
+
+impl<I: core::iter::Iterator<Item = ux::u7>> core::iter::Iterator for Decoder<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == GROUP_SIZE {
+            self.msbs = self.iter.next()?.into();
+            self.index = 0;
+        }
+        let byte = u8::from(self.iter.next()?);
+        let msb = (self.msbs >> (GROUP_SIZE - 1 - self.index)) & 0b1;
+        self.index += 1;
+        Some(byte | (msb << 7))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let decoded = |size: usize| {
+            let in_group = GROUP_SIZE - self.index;
+            if size <= in_group {
+                size
+            } else {
+                in_group + decoded_size(size - in_group)
+            }
+        };
+        (decoded(lower), upper.map(decoded))
+    }
+}
+
+impl<I: core::iter::ExactSizeIterator<Item = ux::u7>> core::iter::ExactSizeIterator for Decoder<I> {}
+
+impl<I: core::iter::FusedIterator<Item = ux::u7>> core::iter::FusedIterator for Decoder<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn u7s(data: &[u8]) -> std::vec::Vec<ux::u7> {
+        data.iter().copied().map(ux::u7::new).collect()
+    }
+
+    #[test]
+    fn encode_empty() {
+        assert_eq!(encode([]).next(), None);
+    }
+
+    #[test]
+    fn encode_full_group() {
+        assert_eq!(
+            encode([0x80_u8, 0x01, 0xFF, 0x03, 0x04, 0x85, 0x06]).collect::<std::vec::Vec<_>>(),
+            u7s(&[0b0101_0010, 0x00, 0x01, 0x7F, 0x03, 0x04, 0x05, 0x06]),
+        );
+    }
+
+    #[test]
+    fn encode_partial_group() {
+        assert_eq!(
+            encode([0x00_u8, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x87, 0x88])
+                .collect::<std::vec::Vec<_>>(),
+            u7s(&[
+                0x00,
+                0x00,
+                0x01,
+                0x02,
+                0x03,
+                0x04,
+                0x05,
+                0x06,
+                0b0110_0000,
+                0x07,
+                0x08
+            ]),
+        );
+    }
+
+    #[test]
+    fn encode_len() {
+        let data = [0x0_u8; 15];
+        let mut encoder = encode(data.iter().copied());
+        assert_eq!(encoder.len(), 18);
+        encoder.next();
+        encoder.next();
+        assert_eq!(encoder.len(), 16);
+    }
+
+    #[test]
+    fn decode_full_group() {
+        assert_eq!(
+            decode(u7s(&[
+                0b0101_0010,
+                0x00,
+                0x01,
+                0x7F,
+                0x03,
+                0x04,
+                0x05,
+                0x06
+            ]))
+            .collect::<std::vec::Vec<_>>(),
+            std::vec![0x80_u8, 0x01, 0xFF, 0x03, 0x04, 0x85, 0x06],
+        );
+    }
+
+    #[test]
+    fn decode_partial_group() {
+        assert_eq!(
+            decode(u7s(&[
+                0x00,
+                0x00,
+                0x01,
+                0x02,
+                0x03,
+                0x04,
+                0x05,
+                0x06,
+                0b0110_0000,
+                0x07,
+                0x08
+            ]))
+            .collect::<std::vec::Vec<_>>(),
+            std::vec![0x00_u8, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x87, 0x88],
+        );
+    }
+
+    #[test]
+    fn decode_lone_header() {
+        assert_eq!(decode(u7s(&[0x7F])).next(), None);
+    }
+
+    #[test]
+    fn decode_len() {
+        let data = u7s(&[0x0; 11]);
+        let mut decoder = decode(data.iter().copied());
+        assert_eq!(decoder.len(), 9);
+        decoder.next();
+        assert_eq!(decoder.len(), 8);
+        for _ in 0..6 {
+            decoder.next();
+        }
+        assert_eq!(decoder.len(), 2);
+    }
+
+    #[test]
+    fn sizes() {
+        for size in 0..64 {
+            assert_eq!(encode(std::vec![0x0_u8; size]).count(), encoded_size(size));
+            assert_eq!(decoded_size(encoded_size(size)), size);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: std::vec::Vec<u8> = (0..=255).collect();
+        assert_eq!(
+            decode(encode(data.iter().copied())).collect::<std::vec::Vec<_>>(),
+            data,
+        );
+    }
+
+    #[test]
+    fn round_trip_through_sysex7() {
+        use crate::{sysex7::Sysex7, Sysex};
+
+        let data: std::vec::Vec<u8> = (0x70..0x90).collect();
+        let mut message = Sysex7::<std::vec::Vec<u32>>::new();
+        message.set_payload(encode(data.iter().copied()));
+        assert_eq!(message.payload_size(), encoded_size(data.len()));
+        assert_eq!(
+            decode(message.payload()).collect::<std::vec::Vec<_>>(),
+            data
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! zlib compression combined with Mcoded7 encoding.
+//!
+//! Used for property exchange bodies sent with `"mutualEncoding": "zlib+Mcoded7"`.
+//!
+//! ```rust
+//! use midi2::{ci::mcoded7, prelude::*, sysex7::Sysex7};
+//!
+//! let body = br#"{"status":200}"#;
+//!
+//! let mut message = Sysex7::<Vec<u8>>::new();
+//! message.set_payload(mcoded7::zlib::encode(body));
+//!
+//! assert_eq!(mcoded7::zlib::decode(message.payload()), Ok(body.to_vec()));
+//! ```
+
+use crate::error::InvalidData;
+
+const ERR_INVALID_ZLIB_DATA: &str = "Invalid zlib compressed data";
+
+/// zlib compress the provided data with the default compression level
+/// and Mcoded7 encode the result.
+pub fn encode(data: &[u8]) -> super::Encoder<std::vec::IntoIter<u8>> {
+    encode_with_level(
+        data,
+        miniz_oxide::deflate::CompressionLevel::DefaultLevel as u8,
+    )
+}
+
+/// zlib compress the provided data with the given compression level (0 - 10)
+/// and Mcoded7 encode the result.
+pub fn encode_with_level(data: &[u8], level: u8) -> super::Encoder<std::vec::IntoIter<u8>> {
+    super::encode(miniz_oxide::deflate::compress_to_vec_zlib(data, level))
+}
+
+/// Mcoded7 decode the provided 7-bit bytes and decompress the result.
+pub fn decode<I: core::iter::IntoIterator<Item = ux::u7>>(
+    data: I,
+) -> Result<std::vec::Vec<u8>, InvalidData> {
+    let compressed: std::vec::Vec<u8> = super::decode(data).collect();
+    miniz_oxide::inflate::decompress_to_vec_zlib(&compressed)
+        .map_err(|_| InvalidData(ERR_INVALID_ZLIB_DATA))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() {
+        let data: std::vec::Vec<u8> = (0..1024).map(|i| (i % 251) as u8).collect();
+        assert_eq!(decode(encode(&data)), Ok(data));
+    }
+
+    #[test]
+    fn compresses() {
+        let data = [0x42_u8; 1024];
+        assert!(encode(&data).len() < data.len());
+    }
+
+    #[test]
+    fn decode_invalid_data() {
+        assert_eq!(
+            decode(super::super::encode([0x00_u8, 0x01, 0x02, 0x03])),
+            Err(InvalidData(ERR_INVALID_ZLIB_DATA)),
+        );
+    }
+}
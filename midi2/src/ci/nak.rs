@@ -0,0 +1,146 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x7F;
+
+/// Negative acknowledgement of a MIDI-CI transaction.
+///
+/// Version 1 messages consist of the common header only.
+/// Version 2 adds the status and message text fields shared with [Ack](crate::ci::Ack).
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 15),
+    SupportedVersion(version = 0x2, min_size = 25)
+)]
+struct Nak {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x2)]
+    original_transaction: ux::u7,
+    #[property(ci::common_properties::U7Property<15>)]
+    #[version(0x2)]
+    status_code: ux::u7,
+    #[property(ci::common_properties::U7Property<16>)]
+    #[version(0x2)]
+    status_data: ux::u7,
+    #[property(ci::ack::DetailsProperty)]
+    #[version(0x2)]
+    details: [ux::u7; 5],
+    #[property(ci::common_properties::DataWriteProperty<22, 2>)]
+    #[version(0x2)]
+    #[writeonly]
+    #[resize]
+    message_text: &[ux::u7],
+    #[property(ci::common_properties::DataReadProperty<22, 2>)]
+    #[version(0x2)]
+    #[readonly]
+    message_text: &[u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new_v1() {
+        use crate::Data;
+
+        let message = Nak::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[0xF0, 0x7E, 0x0, 0x0D, 0x7F, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,]
+        );
+    }
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = Nak::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x7F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = Nak::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::FunctionBlock);
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        message.set_original_transaction(ux::u7::new(0x34));
+        message.set_status_code(ux::u7::new(0x20));
+        message.set_status_data(ux::u7::new(0x01));
+        message.set_message_text(&[ux::u7::new(b'N'), ux::u7::new(b'o')]);
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7F, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x34, 0x20, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, b'N', b'o', 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters_v1() {
+        use crate::ci::Ci;
+
+        let mut message = Nak::<0x1, std::vec::Vec<u8>>::new();
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        assert_eq!(message.destination(), ux::u28::new(0x2A1D5B3));
+    }
+
+    #[test]
+    fn try_from_slice_v1() {
+        Nak::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7F, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = Nak::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7F, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x34, 0x20, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, b'N', b'o', 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.status_code(), ux::u7::new(0x20));
+        assert_eq!(message.message_text(), b"No");
+    }
+
+    #[test]
+    fn try_from_slice_too_short() {
+        assert!(Nak::<0x2, _>::try_from(
+            &[0xF0, 0x7E, 0x7F, 0x0D, 0x7F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7][..],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,27 @@
+mod profile_added_report;
+mod profile_details_inquiry;
+mod profile_details_reply;
+mod profile_disabled_report;
+mod profile_enabled_report;
+mod profile_id;
+mod profile_inquiry;
+mod profile_inquiry_reply;
+mod profile_removed_report;
+mod profile_specific_data;
+mod set_profile_off;
+mod set_profile_on;
+
+pub use profile_added_report::*;
+pub use profile_details_inquiry::*;
+pub use profile_details_reply::*;
+pub use profile_disabled_report::*;
+pub use profile_enabled_report::*;
+pub use profile_id::{ProfileId, ProfileIds};
+pub use profile_inquiry::*;
+pub use profile_inquiry_reply::*;
+pub use profile_removed_report::*;
+pub use profile_specific_data::*;
+pub use set_profile_off::*;
+pub use set_profile_on::*;
+
+pub(crate) use profile_id::ProfileIdProperty;
@@ -0,0 +1,95 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x29;
+
+/// Sent in response to a [ProfileDetailsInquiry](crate::ci::ProfileDetailsInquiry).
+#[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 23))]
+struct ProfileDetailsReply {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::profile_configuration::ProfileIdProperty<14>)]
+    #[version(0x2)]
+    profile_id: ci::ProfileId,
+    #[property(ci::common_properties::U7Property<19>)]
+    #[version(0x2)]
+    inquiry_target: ux::u7,
+    #[property(ci::common_properties::DataWriteProperty<20, 2>)]
+    #[version(0x2)]
+    #[writeonly]
+    #[resize]
+    target_data: &[ux::u7],
+    #[property(ci::common_properties::DataReadProperty<20, 2>)]
+    #[version(0x2)]
+    #[readonly]
+    target_data: &[u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = ProfileDetailsReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x29, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = ProfileDetailsReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(ci::ProfileId::Standard {
+            bank: ux::u7::new(0x1),
+            number: ux::u7::new(0x2),
+            version: ux::u7::new(0x3),
+            level: ux::u7::new(0x4),
+        });
+        message.set_inquiry_target(ux::u7::new(0x5));
+        message.set_target_data(&[ux::u7::new(0x10), ux::u7::new(0x11)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x29, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x02, 0x00, 0x10, 0x11, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = ProfileDetailsReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x29, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x02, 0x00, 0x10, 0x11, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.target_data(), &[0x10, 0x11]);
+    }
+}
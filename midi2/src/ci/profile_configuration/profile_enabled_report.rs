@@ -0,0 +1,95 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x24;
+
+/// Broadcast when a profile has been enabled, along with the number of
+/// channels it has been enabled on.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 20),
+    SupportedVersion(version = 0x2, min_size = 22)
+)]
+struct ProfileEnabledReport {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::BroadcastDestinationProperty)]
+    #[version(0x1)]
+    broadcast_destination: (),
+    #[property(ci::profile_configuration::ProfileIdProperty<14>)]
+    #[version(0x1)]
+    profile_id: ci::ProfileId,
+    #[property(ci::common_properties::U14Property<19>)]
+    #[version(0x2)]
+    number_of_channels: ux::u14,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = ProfileEnabledReport::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::FunctionBlock);
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_profile_id(ci::ProfileId::Standard {
+            bank: ux::u7::new(0x1),
+            number: ux::u7::new(0x2),
+            version: ux::u7::new(0x3),
+            level: ux::u7::new(0x4),
+        });
+        message.set_number_of_channels(ux::u14::new(0x2));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x24, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x7E, 0x01, 0x02, 0x03, 0x04, 0x02, 0x00, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn destination() {
+        use crate::ci::Ci;
+
+        let message = ProfileEnabledReport::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(message.destination(), ux::u28::MAX);
+    }
+
+    #[test]
+    fn try_from_slice_not_broadcast() {
+        assert!(ProfileEnabledReport::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x24, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x7E, 0x01, 0x02, 0x03, 0x04, 0x02, 0x00, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn try_from_slice_v1() {
+        assert!(ProfileEnabledReport::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x24, 0x01, 0x59, 0x33, 0x23, 0x5A, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x7E, 0x01, 0x02, 0x03, 0x04, 0xF7,
+            ][..],
+        )
+        .is_ok());
+    }
+}
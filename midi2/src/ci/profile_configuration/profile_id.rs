@@ -0,0 +1,219 @@
+use crate::{
+    buffer::{BufferMut, Bytes},
+    detail::property,
+};
+use ux::u7;
+
+const STANDARD_PROFILE: u8 = 0x7E;
+
+/// Identifies a MIDI-CI profile.
+///
+/// Profile ids are five bytes long. Standard profiles, defined by the
+/// MMA / AMEI, begin with `0x7E`. Any other leading byte is the start of
+/// the Sysex ID of the manufacturer defining the profile.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProfileId {
+    Standard {
+        bank: u7,
+        number: u7,
+        version: u7,
+        level: u7,
+    },
+    ManufacturerSpecific {
+        manufacturer: [u7; 3],
+        data: [u7; 2],
+    },
+}
+
+impl core::default::Default for ProfileId {
+    fn default() -> Self {
+        ProfileId::Standard {
+            bank: Default::default(),
+            number: Default::default(),
+            version: Default::default(),
+            level: Default::default(),
+        }
+    }
+}
+
+impl core::convert::From<[u7; 5]> for ProfileId {
+    fn from(bytes: [u7; 5]) -> Self {
+        if u8::from(bytes[0]) == STANDARD_PROFILE {
+            ProfileId::Standard {
+                bank: bytes[1],
+                number: bytes[2],
+                version: bytes[3],
+                level: bytes[4],
+            }
+        } else {
+            ProfileId::ManufacturerSpecific {
+                manufacturer: [bytes[0], bytes[1], bytes[2]],
+                data: [bytes[3], bytes[4]],
+            }
+        }
+    }
+}
+
+impl core::convert::From<ProfileId> for [u7; 5] {
+    fn from(id: ProfileId) -> Self {
+        match id {
+            ProfileId::Standard {
+                bank,
+                number,
+                version,
+                level,
+            } => [u7::new(STANDARD_PROFILE), bank, number, version, level],
+            ProfileId::ManufacturerSpecific { manufacturer, data } => [
+                manufacturer[0],
+                manufacturer[1],
+                manufacturer[2],
+                data[0],
+                data[1],
+            ],
+        }
+    }
+}
+
+impl ProfileId {
+    pub(crate) fn read(bytes: &[u8]) -> Self {
+        let mut u7s = [u7::default(); 5];
+        for (dst, src) in u7s.iter_mut().zip(bytes.iter()) {
+            *dst = u7::new(src & 0x7F);
+        }
+        u7s.into()
+    }
+    pub(crate) fn write(self, bytes: &mut [u8]) {
+        let u7s: [u7; 5] = self.into();
+        for (dst, src) in bytes.iter_mut().zip(u7s.iter()) {
+            *dst = (*src).into();
+        }
+    }
+}
+
+/// Iterates over a list of profile ids within a MIDI-CI message.
+#[derive(Debug, Clone)]
+pub struct ProfileIds<'a>(core::slice::ChunksExact<'a, u8>);
+
+impl<'a> ProfileIds<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        ProfileIds(data.chunks_exact(5))
+    }
+}
+
+impl core::iter::Iterator for ProfileIds<'_> {
+    type Item = ProfileId;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(ProfileId::read)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl core::iter::ExactSizeIterator for ProfileIds<'_> {}
+
+impl core::iter::FusedIterator for ProfileIds<'_> {}
+
+pub(crate) struct ProfileIdProperty<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for ProfileIdProperty<INDEX> {
+    type Type = ProfileId;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for ProfileIdProperty<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        ProfileId::read(&buffer.buffer()[INDEX..INDEX + 5])
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for ProfileIdProperty<INDEX>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        v.write(&mut buffer.buffer_mut()[INDEX..INDEX + 5]);
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn standard_from_u7s() {
+        assert_eq!(
+            ProfileId::from([
+                u7::new(0x7E),
+                u7::new(0x01),
+                u7::new(0x02),
+                u7::new(0x03),
+                u7::new(0x04)
+            ]),
+            ProfileId::Standard {
+                bank: u7::new(0x01),
+                number: u7::new(0x02),
+                version: u7::new(0x03),
+                level: u7::new(0x04),
+            }
+        );
+    }
+
+    #[test]
+    fn manufacturer_specific_from_u7s() {
+        assert_eq!(
+            ProfileId::from([
+                u7::new(0x00),
+                u7::new(0x21),
+                u7::new(0x66),
+                u7::new(0x10),
+                u7::new(0x11)
+            ]),
+            ProfileId::ManufacturerSpecific {
+                manufacturer: [u7::new(0x00), u7::new(0x21), u7::new(0x66)],
+                data: [u7::new(0x10), u7::new(0x11)],
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let id = ProfileId::ManufacturerSpecific {
+            manufacturer: [u7::new(0x41), u7::new(0x0), u7::new(0x0)],
+            data: [u7::new(0x2), u7::new(0x3)],
+        };
+        let mut bytes = [0x0; 5];
+        id.write(&mut bytes);
+        assert_eq!(bytes, [0x41, 0x0, 0x0, 0x2, 0x3]);
+        assert_eq!(ProfileId::read(&bytes), id);
+    }
+
+    #[test]
+    fn profile_ids() {
+        let ids = ProfileIds::new(&[0x7E, 0x1, 0x2, 0x3, 0x4, 0x41, 0x0, 0x0, 0x2, 0x3]);
+        assert_eq!(ids.len(), 2);
+        assert_eq!(
+            ids.collect::<std::vec::Vec<_>>(),
+            std::vec![
+                ProfileId::Standard {
+                    bank: u7::new(0x1),
+                    number: u7::new(0x2),
+                    version: u7::new(0x3),
+                    level: u7::new(0x4),
+                },
+                ProfileId::ManufacturerSpecific {
+                    manufacturer: [u7::new(0x41), u7::new(0x0), u7::new(0x0)],
+                    data: [u7::new(0x2), u7::new(0x3)],
+                },
+            ]
+        );
+    }
+}
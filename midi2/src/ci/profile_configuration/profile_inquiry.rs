@@ -0,0 +1,112 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x20;
+
+/// Requests the list of profiles supported on the addressed channel,
+/// group or function block.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 15),
+    SupportedVersion(version = 0x2, min_size = 15)
+)]
+struct ProfileInquiry {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = ProfileInquiry::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[0xF0, 0x7E, 0x0, 0x0D, 0x20, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7]
+        );
+    }
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = ProfileInquiry::<0x1, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Channel(ux::u4::new(0x3)));
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x03, 0x0D, 0x20, 0x01, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0xF7
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        use crate::ci::{Ci, DeviceId};
+
+        let message = ProfileInquiry::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7E, 0x0D, 0x20, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.device_id(), DeviceId::Group);
+        assert_eq!(message.destination(), ux::u28::new(0x2A1D5B3));
+    }
+
+    #[test]
+    fn try_from_sysex7() {
+        let sysex7 = crate::sysex7::Sysex7::try_from(
+            &[
+                0xF0, 0x7E, 0x7E, 0x0D, 0x20, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0xF7,
+            ][..],
+        )
+        .expect("Valid sysex7");
+        assert!(ProfileInquiry::<0x2, _>::try_from(sysex7).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn try_from_ump_sysex7() {
+        use crate::ci::Ci;
+
+        let sysex7 = crate::sysex7::Sysex7::try_from(
+            &[
+                0x3016_7E7E_u32,
+                0x0D20_0259,
+                0x3026_3323,
+                0x5A33_2B07,
+                0x3031_1500,
+                0x0000_0000,
+            ][..],
+        )
+        .expect("Valid sysex7");
+        let message =
+            ProfileInquiry::<0x2, std::vec::Vec<u8>>::try_from(&sysex7).expect("Valid data");
+        assert_eq!(message.source(), ux::u28::new(0xB48D9D9));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{ci, detail::property};
+
+pub(crate) const STATUS: u8 = 0x21;
+
+/// Sent in response to a [ProfileInquiry](crate::ci::ProfileInquiry), listing the
+/// enabled and disabled profiles of the addressed channel, group or function block.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 19),
+    SupportedVersion(version = 0x2, min_size = 19)
+)]
+struct ProfileInquiryReply {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ProfilesWriteProperty<ENABLED>)]
+    #[version(0x1)]
+    #[writeonly]
+    #[resize]
+    enabled_profiles: &[ci::ProfileId],
+    #[property(ProfilesReadProperty<ENABLED>)]
+    #[version(0x1)]
+    #[readonly]
+    enabled_profiles: ci::ProfileIds,
+    #[property(ProfilesWriteProperty<DISABLED>)]
+    #[version(0x1)]
+    #[writeonly]
+    #[resize]
+    disabled_profiles: &[ci::ProfileId],
+    #[property(ProfilesReadProperty<DISABLED>)]
+    #[version(0x1)]
+    #[readonly]
+    disabled_profiles: ci::ProfileIds,
+}
+
+const ENABLED: usize = 0;
+const DISABLED: usize = 1;
+
+const ERR_INCONSISTENT_PROFILE_LISTS: &str = "Profile list lengths do not match the message size";
+
+// the enabled list begins straight after the header,
+// and the disabled list straight after the enabled list
+fn list_offset(buffer: &[u8], list: usize) -> usize {
+    if list == ENABLED {
+        14
+    } else {
+        16 + 5 * list_length(buffer, ENABLED)
+    }
+}
+
+fn list_length(buffer: &[u8], list: usize) -> usize {
+    let offset = list_offset(buffer, list);
+    ci::common_properties::read_length(&buffer[offset..offset + 2])
+}
+
+fn message_size(enabled: usize, disabled: usize) -> usize {
+    14 + 2 + 5 * enabled + 2 + 5 * disabled + 1
+}
+
+pub(crate) struct ProfilesWriteProperty<'a, const LIST: usize>(core::marker::PhantomData<&'a u8>);
+
+impl<const LIST: usize> ProfilesWriteProperty<'_, LIST> {
+    fn length(profiles: &[ci::ProfileId]) -> usize {
+        profiles.len().min(ci::common_properties::max_length(2))
+    }
+    // lengths of the (enabled, disabled) lists before and after the update
+    fn lengths(buffer: &[u8], profiles: &[ci::ProfileId]) -> ((usize, usize), (usize, usize)) {
+        let current = (list_length(buffer, ENABLED), list_length(buffer, DISABLED));
+        let updated = if LIST == ENABLED {
+            (Self::length(profiles), current.1)
+        } else {
+            (current.0, Self::length(profiles))
+        };
+        (current, updated)
+    }
+    // makes room for the enabled list by moving the disabled list
+    fn relocate_disabled_list(buffer: &mut [u8], current: (usize, usize), updated: (usize, usize)) {
+        if LIST == ENABLED {
+            let source = 16 + 5 * current.0;
+            buffer.copy_within(source..source + 2 + 5 * current.1, 16 + 5 * updated.0);
+        }
+    }
+}
+
+impl<'a, const LIST: usize, B: crate::buffer::Bytes> property::Property<B>
+    for ProfilesWriteProperty<'a, LIST>
+{
+    type Type = &'a [ci::ProfileId];
+}
+
+impl<const LIST: usize, B: crate::buffer::Bytes + crate::buffer::BufferMut>
+    property::WriteProperty<B> for ProfilesWriteProperty<'_, LIST>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let offset = list_offset(buffer.buffer(), LIST);
+        let length = Self::length(v);
+        let buffer = buffer.buffer_mut();
+        ci::common_properties::write_length(&mut buffer[offset..offset + 2], length);
+        for (bytes, id) in buffer[offset + 2..offset + 2 + 5 * length]
+            .chunks_exact_mut(5)
+            .zip(v.iter())
+        {
+            id.write(bytes);
+        }
+    }
+}
+
+impl<const LIST: usize, B: crate::buffer::Bytes + crate::buffer::BufferMut>
+    property::ResizeProperty<B> for ProfilesWriteProperty<'_, LIST>
+{
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        let (current, updated) = Self::lengths(buffer.buffer(), value);
+        let size = message_size(updated.0, updated.1);
+        if size > message_size(current.0, current.1) {
+            buffer.resize(size);
+            Self::relocate_disabled_list(buffer.buffer_mut(), current, updated);
+        } else {
+            Self::relocate_disabled_list(buffer.buffer_mut(), current, updated);
+            buffer.resize(size);
+        }
+        buffer.buffer_mut()[size - 1] = 0xF7;
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        let (current, updated) = Self::lengths(buffer.buffer(), value);
+        let size = message_size(updated.0, updated.1);
+        buffer.try_resize(size)?;
+        Self::relocate_disabled_list(buffer.buffer_mut(), current, updated);
+        buffer.buffer_mut()[size - 1] = 0xF7;
+        Ok(())
+    }
+}
+
+pub(crate) struct ProfilesReadProperty<'a, const LIST: usize>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, const LIST: usize, B: crate::buffer::Bytes> property::Property<B>
+    for ProfilesReadProperty<'a, LIST>
+{
+    type Type = ci::ProfileIds<'a>;
+}
+
+impl<'a, const LIST: usize, B: 'a + crate::buffer::Bytes> property::ReadProperty<'a, B>
+    for ProfilesReadProperty<'a, LIST>
+{
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        let buffer = buffer.buffer();
+        let enabled_end = 16 + 5 * list_length(buffer, ENABLED);
+        if buffer.len() < enabled_end + 2 {
+            return Err(crate::error::InvalidData(ERR_INCONSISTENT_PROFILE_LISTS));
+        }
+        let size = message_size(list_length(buffer, ENABLED), list_length(buffer, DISABLED));
+        if buffer.get(size - 1) == Some(&0xF7) {
+            Ok(())
+        } else {
+            Err(crate::error::InvalidData(ERR_INCONSISTENT_PROFILE_LISTS))
+        }
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let buffer = buffer.buffer();
+        let begin = list_offset(buffer, LIST) + 2;
+        let end = (begin + 5 * list_length(buffer, LIST)).min(buffer.len());
+        ci::ProfileIds::new(&buffer[begin..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn standard(number: u8) -> ci::ProfileId {
+        ci::ProfileId::Standard {
+            bank: ux::u7::new(0x1),
+            number: ux::u7::new(number),
+            version: ux::u7::new(0x1),
+            level: ux::u7::new(0x1),
+        }
+    }
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = ProfileInquiryReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+        assert_eq!(message.enabled_profiles().len(), 0);
+        assert_eq!(message.disabled_profiles().len(), 0);
+    }
+
+    #[test]
+    fn set_profiles() {
+        use crate::Data;
+
+        let mut message = ProfileInquiryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_enabled_profiles(&[standard(0x2)]);
+        message.set_disabled_profiles(&[standard(0x3), standard(0x4)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01,
+                0x00, 0x7E, 0x01, 0x02, 0x01, 0x01, 0x02, 0x00, 0x7E, 0x01, 0x03, 0x01, 0x01, 0x7E,
+                0x01, 0x04, 0x01, 0x01, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn set_enabled_profiles_moves_disabled_profiles() {
+        let mut message = ProfileInquiryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_disabled_profiles(&[standard(0x3), standard(0x4)]);
+        message.set_enabled_profiles(&[standard(0x1), standard(0x2)]);
+        assert_eq!(
+            message.enabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard(0x1), standard(0x2)]
+        );
+        assert_eq!(
+            message.disabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard(0x3), standard(0x4)]
+        );
+
+        message.set_enabled_profiles(&[]);
+        assert_eq!(message.enabled_profiles().len(), 0);
+        assert_eq!(
+            message.disabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard(0x3), standard(0x4)]
+        );
+    }
+
+    #[test]
+    fn try_set_profiles() {
+        use crate::Data;
+
+        let mut buffer = [0x0; 29];
+        let mut message = ProfileInquiryReply::<0x2, _>::try_new_with_buffer(&mut buffer[..])
+            .expect("Buffer is large enough");
+        message
+            .try_set_disabled_profiles(&[standard(0x3)])
+            .expect("Buffer is large enough");
+        message
+            .try_set_enabled_profiles(&[standard(0x2)])
+            .expect("Buffer is large enough");
+        assert_eq!(message.data().len(), 29);
+        assert!(message
+            .try_set_enabled_profiles(&[standard(0x1), standard(0x2)])
+            .is_err());
+        assert_eq!(
+            message.disabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard(0x3)]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = ProfileInquiryReply::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x21, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01,
+                0x00, 0x7E, 0x01, 0x02, 0x01, 0x01, 0x00, 0x00, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(
+            message.enabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard(0x2)]
+        );
+        assert_eq!(message.disabled_profiles().len(), 0);
+    }
+
+    #[test]
+    fn try_from_slice_inconsistent_lengths() {
+        assert!(ProfileInquiryReply::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x21, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x02,
+                0x00, 0x7E, 0x01, 0x02, 0x01, 0x01, 0x00, 0x00, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
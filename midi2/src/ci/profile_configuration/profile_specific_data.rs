@@ -0,0 +1,105 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x2F;
+
+/// Carries data whose meaning is defined by the specification of the profile.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 24),
+    SupportedVersion(version = 0x2, min_size = 24)
+)]
+struct ProfileSpecificData {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::profile_configuration::ProfileIdProperty<14>)]
+    #[version(0x1)]
+    profile_id: ci::ProfileId,
+    #[property(ci::common_properties::DataWriteProperty<19, 4>)]
+    #[version(0x1)]
+    #[writeonly]
+    #[resize]
+    profile_data: &[ux::u7],
+    #[property(ci::common_properties::DataReadProperty<19, 4>)]
+    #[version(0x1)]
+    #[readonly]
+    profile_data: &[u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = ProfileSpecificData::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x2F, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_data() {
+        use crate::Data;
+
+        let mut message = ProfileSpecificData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(ci::ProfileId::Standard {
+            bank: ux::u7::new(0x1),
+            number: ux::u7::new(0x2),
+            version: ux::u7::new(0x3),
+            level: ux::u7::new(0x4),
+        });
+        message.set_profile_data(&[ux::u7::new(0x10), ux::u7::new(0x11), ux::u7::new(0x12)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x2F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x01, 0x02, 0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x10, 0x11, 0x12, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = ProfileSpecificData::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x2F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x01, 0x02, 0x03, 0x04, 0x01, 0x00, 0x00, 0x00, 0x10, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.profile_data(), &[0x10]);
+    }
+
+    #[test]
+    fn try_from_slice_inconsistent_length() {
+        assert!(ProfileSpecificData::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x2F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x01, 0x02, 0x03, 0x04, 0x02, 0x00, 0x00, 0x00, 0x10, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,33 @@
+mod capabilities_inquiry;
+mod capabilities_reply;
+#[cfg(feature = "std")]
+mod chunk_assembler;
+mod chunks;
+mod get_property_data;
+mod get_property_data_reply;
+mod notify;
+mod property_data;
+#[cfg(feature = "pe-resources")]
+pub mod resources;
+mod set_property_data;
+mod set_property_data_reply;
+mod subscription;
+mod subscription_reply;
+
+pub use capabilities_inquiry::*;
+pub use capabilities_reply::*;
+#[cfg(feature = "std")]
+pub use chunk_assembler::{AssembledPropertyData, ChunkAssembler, PropertyDataKind};
+pub use chunks::{Chunk, Chunks};
+pub use get_property_data::*;
+pub use get_property_data_reply::*;
+pub use notify::*;
+pub use set_property_data::*;
+pub use set_property_data_reply::*;
+pub use subscription::*;
+pub use subscription_reply::*;
+
+pub(crate) use property_data::{
+    ChunkNumberProperty, HeaderReadProperty, HeaderWriteProperty, NumberOfChunksProperty,
+    PropertyDataReadProperty, PropertyDataWriteProperty,
+};
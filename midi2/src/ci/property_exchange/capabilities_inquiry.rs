@@ -0,0 +1,91 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x30;
+
+/// Announces the property exchange capabilities of the initiator and
+/// requests those of the responder.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 16),
+    SupportedVersion(version = 0x2, min_size = 18)
+)]
+struct PropertyExchangeCapabilitiesInquiry {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x1)]
+    number_of_simultaneous_requests: ux::u7,
+    #[property(ci::common_properties::U7Property<15>)]
+    #[version(0x2)]
+    major_version: ux::u7,
+    #[property(ci::common_properties::U7Property<16>)]
+    #[version(0x2)]
+    minor_version: ux::u7,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = PropertyExchangeCapabilitiesInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        message.set_number_of_simultaneous_requests(ux::u7::new(0x4));
+        message.set_major_version(ux::u7::new(0x0));
+        message.set_minor_version(ux::u7::new(0x2));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x30, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x04, 0x00, 0x02, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn new_v1() {
+        use crate::Data;
+
+        let message = PropertyExchangeCapabilitiesInquiry::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x30, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = PropertyExchangeCapabilitiesInquiry::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x30, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x04, 0x00, 0x02, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.number_of_simultaneous_requests(), ux::u7::new(0x4));
+        assert_eq!(message.minor_version(), ux::u7::new(0x2));
+    }
+}
@@ -0,0 +1,475 @@
+use crate::{
+    ci::{
+        common_properties::{DestinationProperty, SourceProperty},
+        property_exchange::property_data,
+    },
+    detail::property::ReadProperty,
+    error::InvalidData,
+};
+use ux::{u28, u7};
+
+const ERR_NOT_PROPERTY_DATA: &str = "Not a property exchange data message";
+const ERR_MESSAGE_TOO_LARGE: &str = "Message exceeds the max sysex size";
+const ERR_UNEXPECTED_CHUNK: &str = "Chunk received out of order";
+const ERR_INVALID_DATA_BYTE: &str = "Sysex data bytes must not have the top bit set";
+
+/// The property exchange data messages which may be split across chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyDataKind {
+    GetPropertyData,
+    GetPropertyDataReply,
+    SetPropertyData,
+    SetPropertyDataReply,
+    Subscription,
+    SubscriptionReply,
+    Notify,
+}
+
+impl PropertyDataKind {
+    fn from_status(status: u8) -> Option<Self> {
+        use PropertyDataKind::*;
+        match status {
+            0x34 => Some(GetPropertyData),
+            0x35 => Some(GetPropertyDataReply),
+            0x36 => Some(SetPropertyData),
+            0x37 => Some(SetPropertyDataReply),
+            0x38 => Some(Subscription),
+            0x39 => Some(SubscriptionReply),
+            0x3F => Some(Notify),
+            _ => None,
+        }
+    }
+}
+
+/// A property exchange message rebuilt from all of its chunks
+/// by a [ChunkAssembler].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembledPropertyData {
+    pub kind: PropertyDataKind,
+    pub source: u28,
+    pub destination: u28,
+    pub request_id: u7,
+    pub header: std::vec::Vec<u8>,
+    pub property_data: std::vec::Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct Pending {
+    message: AssembledPropertyData,
+    next_chunk: u16,
+}
+
+/// Rebuilds property exchange messages which have been split into chunks.
+///
+/// Chunks of concurrent transactions are told apart by the source MUID and request id.
+/// Messages larger than the max sysex size which was announced to the sender,
+/// e.g. with [DiscoveryQuery::set_max_sysex_size](crate::ci::DiscoveryQuery::set_max_sysex_size),
+/// are rejected.
+///
+/// A number of chunks of zero marks a message whose total number of chunks
+/// is not known up front. Such a message is complete once a chunk with empty
+/// property data is received, or once a chunk declares a total which its
+/// chunk number has reached.
+///
+/// ```rust
+/// use midi2::{ci::*, ux::*, Data};
+///
+/// let mut assembler = ChunkAssembler::new(u28::new(512));
+///
+/// let mut first = GetPropertyDataReply::<0x2, Vec<u8>>::new();
+/// first.set_header(&[u7::new(b'{'), u7::new(b'}')]);
+/// first.set_number_of_chunks(u14::new(2));
+/// first.set_chunk_number(u14::new(1));
+/// first.set_property_data(&[u7::new(b'[')]);
+///
+/// let mut second = GetPropertyDataReply::<0x2, Vec<u8>>::new();
+/// second.set_number_of_chunks(u14::new(2));
+/// second.set_chunk_number(u14::new(2));
+/// second.set_property_data(&[u7::new(b']')]);
+///
+/// assert_eq!(assembler.push(first.data()), Ok(None));
+///
+/// let assembled = assembler.push(second.data()).unwrap().unwrap();
+/// assert_eq!(assembled.kind, PropertyDataKind::GetPropertyDataReply);
+/// assert_eq!(assembled.header, b"{}");
+/// assert_eq!(assembled.property_data, b"[]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChunkAssembler {
+    max_sysex_size: usize,
+    pending: std::vec::Vec<Pending>,
+}
+
+impl ChunkAssembler {
+    pub fn new(max_sysex_size: u28) -> Self {
+        ChunkAssembler {
+            max_sysex_size: u32::from(max_sysex_size) as usize,
+            pending: std::vec::Vec::new(),
+        }
+    }
+
+    pub fn max_sysex_size(&self) -> u28 {
+        u28::new(self.max_sysex_size as u32)
+    }
+
+    /// The number of messages awaiting further chunks.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Drops the chunks received so far for the given transaction.
+    pub fn cancel(&mut self, source: u28, request_id: u7) {
+        self.pending
+            .retain(|p| p.message.source != source || p.message.request_id != request_id);
+    }
+
+    /// Feeds the bytes of a property exchange data message to the assembler.
+    ///
+    /// Returns the assembled message once its final chunk has been received.
+    pub fn push(&mut self, data: &[u8]) -> Result<Option<AssembledPropertyData>, InvalidData> {
+        let data = sysex_data(data)?;
+        if data.len() > self.max_sysex_size {
+            return Err(InvalidData(ERR_MESSAGE_TOO_LARGE));
+        }
+        let kind =
+            PropertyDataKind::from_status(data[4]).ok_or(InvalidData(ERR_NOT_PROPERTY_DATA))?;
+        property_data::validate(data)?;
+
+        let source = <SourceProperty as ReadProperty<&[u8]>>::read(&data);
+        let destination = <DestinationProperty as ReadProperty<&[u8]>>::read(&data);
+        let request_id = u7::new(data[14]);
+        let number_of_chunks = u16::from(property_data::number_of_chunks(data));
+        let chunk_number = u16::from(property_data::chunk_number(data));
+
+        let position = self
+            .pending
+            .iter()
+            .position(|p| p.message.source == source && p.message.request_id == request_id);
+
+        let index = if chunk_number <= 1 {
+            // the first chunk starts a new transaction
+            let pending = Pending {
+                message: AssembledPropertyData {
+                    kind,
+                    source,
+                    destination,
+                    request_id,
+                    header: std::vec::Vec::new(),
+                    property_data: std::vec::Vec::new(),
+                },
+                next_chunk: 1,
+            };
+            match position {
+                Some(index) => {
+                    self.pending[index] = pending;
+                    index
+                }
+                None => {
+                    self.pending.push(pending);
+                    self.pending.len() - 1
+                }
+            }
+        } else {
+            match position {
+                Some(index)
+                    if self.pending[index].next_chunk == chunk_number
+                        && self.pending[index].message.kind == kind =>
+                {
+                    index
+                }
+                Some(index) => {
+                    self.pending.remove(index);
+                    return Err(InvalidData(ERR_UNEXPECTED_CHUNK));
+                }
+                None => return Err(InvalidData(ERR_UNEXPECTED_CHUNK)),
+            }
+        };
+
+        let chunk_property_data = property_data::property_data(data);
+        let pending = &mut self.pending[index];
+        pending
+            .message
+            .header
+            .extend_from_slice(property_data::header(data));
+        pending
+            .message
+            .property_data
+            .extend_from_slice(chunk_property_data);
+        pending.next_chunk += 1;
+
+        let is_last_chunk = if number_of_chunks == 0 {
+            // the total is unknown, an empty chunk ends the message
+            chunk_property_data.is_empty()
+        } else {
+            chunk_number >= number_of_chunks
+        };
+        if is_last_chunk {
+            Ok(Some(self.pending.remove(index).message))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// the message bytes up to and including the end byte
+fn sysex_data(data: &[u8]) -> Result<&[u8], InvalidData> {
+    let size = data
+        .iter()
+        .position(|b| *b == 0xF7)
+        .map(|i| i + 1)
+        .ok_or(InvalidData(ERR_NOT_PROPERTY_DATA))?;
+    let data = &data[..size];
+    if data.len() < property_data::MIN_SIZE || data[0] != 0xF0 || data[1] != 0x7E || data[3] != 0x0D
+    {
+        return Err(InvalidData(ERR_NOT_PROPERTY_DATA));
+    }
+    if data[1..data.len() - 1].iter().any(|b| b & 0x80 != 0) {
+        return Err(InvalidData(ERR_INVALID_DATA_BYTE));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ci::{Chunks, GetPropertyData, GetPropertyDataReply, SetPropertyData},
+        Data,
+    };
+    use pretty_assertions::assert_eq;
+    use ux::u14;
+
+    fn u7s(data: &[u8]) -> std::vec::Vec<u7> {
+        data.iter().map(|b| u7::new(*b)).collect()
+    }
+
+    fn chunked_reply(
+        source: u28,
+        request_id: u7,
+        header: &[u8],
+        property_data: &[u8],
+        max_sysex_size: u28,
+    ) -> std::vec::Vec<GetPropertyDataReply<0x2, std::vec::Vec<u8>>> {
+        let header = u7s(header);
+        let property_data = u7s(property_data);
+        Chunks::new(&header, &property_data, max_sysex_size)
+            .unwrap()
+            .map(|chunk| {
+                let mut message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+                message.set_source(source);
+                message.set_request_id(request_id);
+                message.set_header(chunk.header);
+                message.set_number_of_chunks(chunk.number_of_chunks);
+                message.set_chunk_number(chunk.chunk_number);
+                message.set_property_data(chunk.property_data);
+                message
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_chunk() {
+        let mut assembler = ChunkAssembler::new(u28::new(512));
+        let mut message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234));
+        message.set_destination(ux::u28::new(0x5678));
+        message.set_request_id(u7::new(0x3));
+        message.set_header(&u7s(br#"{"resource":"DeviceInfo"}"#));
+
+        assert_eq!(
+            assembler.push(message.data()),
+            Ok(Some(AssembledPropertyData {
+                kind: PropertyDataKind::GetPropertyData,
+                source: u28::new(0x1234),
+                destination: u28::new(0x5678),
+                request_id: u7::new(0x3),
+                header: br#"{"resource":"DeviceInfo"}"#.to_vec(),
+                property_data: std::vec::Vec::new(),
+            }))
+        );
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn multiple_chunks() {
+        let property_data = (0..200)
+            .map(|i| (i % 0x80) as u8)
+            .collect::<std::vec::Vec<_>>();
+        let messages = chunked_reply(
+            u28::new(0x1234),
+            u7::new(0x1),
+            br#"{"status":200}"#,
+            &property_data,
+            u28::new(64),
+        );
+        assert_eq!(messages.len(), 6);
+
+        let mut assembler = ChunkAssembler::new(u28::new(64));
+        for message in &messages[..messages.len() - 1] {
+            assert_eq!(assembler.push(message.data()), Ok(None));
+        }
+        assert_eq!(assembler.pending(), 1);
+
+        let assembled = assembler
+            .push(messages.last().unwrap().data())
+            .unwrap()
+            .unwrap();
+        assert_eq!(assembled.header, br#"{"status":200}"#.to_vec());
+        assert_eq!(assembled.property_data, property_data);
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn interleaved_transactions() {
+        let first = chunked_reply(u28::new(0x1), u7::new(0x1), b"{}", &[0x1; 20], u28::new(40));
+        let second = chunked_reply(u28::new(0x1), u7::new(0x2), b"{}", &[0x2; 20], u28::new(40));
+
+        let mut assembler = ChunkAssembler::new(u28::new(40));
+        let mut assembled = std::vec::Vec::new();
+        for (a, b) in first.iter().zip(second.iter()) {
+            assembled.extend(assembler.push(a.data()).unwrap());
+            assembled.extend(assembler.push(b.data()).unwrap());
+        }
+
+        assert_eq!(assembled.len(), 2);
+        assert_eq!(assembled[0].request_id, u7::new(0x1));
+        assert_eq!(assembled[0].property_data, std::vec![0x1; 20]);
+        assert_eq!(assembled[1].request_id, u7::new(0x2));
+        assert_eq!(assembled[1].property_data, std::vec![0x2; 20]);
+    }
+
+    #[test]
+    fn message_too_large() {
+        let messages = chunked_reply(
+            u28::new(0x1),
+            u7::new(0x1),
+            b"{}",
+            &[0x1; 40],
+            u28::new(128),
+        );
+        let mut assembler = ChunkAssembler::new(u28::new(64));
+        assert_eq!(
+            assembler.push(messages[0].data()),
+            Err(InvalidData(ERR_MESSAGE_TOO_LARGE))
+        );
+    }
+
+    #[test]
+    fn out_of_order_chunk() {
+        let messages = chunked_reply(u28::new(0x1), u7::new(0x1), b"{}", &[0x1; 40], u28::new(40));
+        let mut assembler = ChunkAssembler::new(u28::new(40));
+        assert_eq!(assembler.push(messages[0].data()), Ok(None));
+        assert_eq!(
+            assembler.push(messages[2].data()),
+            Err(InvalidData(ERR_UNEXPECTED_CHUNK))
+        );
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn chunk_without_first_chunk() {
+        let messages = chunked_reply(u28::new(0x1), u7::new(0x1), b"{}", &[0x1; 40], u28::new(40));
+        let mut assembler = ChunkAssembler::new(u28::new(40));
+        assert_eq!(
+            assembler.push(messages[1].data()),
+            Err(InvalidData(ERR_UNEXPECTED_CHUNK))
+        );
+    }
+
+    #[test]
+    fn cancel() {
+        let messages = chunked_reply(u28::new(0x1), u7::new(0x1), b"{}", &[0x1; 40], u28::new(40));
+        let mut assembler = ChunkAssembler::new(u28::new(40));
+        assert_eq!(assembler.push(messages[0].data()), Ok(None));
+        assembler.cancel(u28::new(0x1), u7::new(0x1));
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn restarted_transaction() {
+        let messages = chunked_reply(u28::new(0x1), u7::new(0x1), b"{}", &[0x1; 40], u28::new(40));
+        let mut assembler = ChunkAssembler::new(u28::new(40));
+        assert_eq!(assembler.push(messages[0].data()), Ok(None));
+        assert_eq!(assembler.push(messages[0].data()), Ok(None));
+        for message in &messages[1..messages.len() - 1] {
+            assert_eq!(assembler.push(message.data()), Ok(None));
+        }
+        let assembled = assembler
+            .push(messages.last().unwrap().data())
+            .unwrap()
+            .unwrap();
+        assert_eq!(assembled.property_data, std::vec![0x1; 40]);
+    }
+
+    #[test]
+    fn not_property_data() {
+        let message = SetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        let mut data = message.data().to_vec();
+        data[4] = 0x30;
+        let mut assembler = ChunkAssembler::new(u28::new(512));
+        assert_eq!(
+            assembler.push(&data),
+            Err(InvalidData(ERR_NOT_PROPERTY_DATA))
+        );
+    }
+
+    #[test]
+    fn data_byte_with_top_bit_set() {
+        let message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        let mut data = message.data().to_vec();
+        data[14] = 0x80;
+        let mut assembler = ChunkAssembler::new(u28::new(512));
+        assert_eq!(
+            assembler.push(&data),
+            Err(InvalidData(ERR_INVALID_DATA_BYTE))
+        );
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    fn unknown_total_chunk(chunk_number: u16, property_data: &[u8]) -> std::vec::Vec<u8> {
+        let mut message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_request_id(u7::new(0x1));
+        if chunk_number == 1 {
+            message.set_header(&u7s(b"{}"));
+        }
+        message.set_number_of_chunks(u14::new(0));
+        message.set_chunk_number(u14::new(chunk_number));
+        message.set_property_data(&u7s(property_data));
+        message.data().to_vec()
+    }
+
+    #[test]
+    fn unknown_number_of_chunks() {
+        let mut assembler = ChunkAssembler::new(u28::new(512));
+        assert_eq!(assembler.push(&unknown_total_chunk(1, b"[1,")), Ok(None));
+        assert_eq!(assembler.push(&unknown_total_chunk(2, b"2]")), Ok(None));
+        let assembled = assembler
+            .push(&unknown_total_chunk(3, b""))
+            .unwrap()
+            .unwrap();
+        assert_eq!(assembled.header, b"{}");
+        assert_eq!(assembled.property_data, b"[1,2]");
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn unknown_number_of_chunks_resolved_by_last_chunk() {
+        let mut assembler = ChunkAssembler::new(u28::new(512));
+        assert_eq!(assembler.push(&unknown_total_chunk(1, b"[1,")), Ok(None));
+
+        let mut last = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        last.set_request_id(u7::new(0x1));
+        last.set_number_of_chunks(u14::new(2));
+        last.set_chunk_number(u14::new(2));
+        last.set_property_data(&u7s(b"2]"));
+        let assembled = assembler.push(last.data()).unwrap().unwrap();
+        assert_eq!(assembled.property_data, b"[1,2]");
+    }
+
+    #[test]
+    fn chunk_numbers() {
+        let messages = chunked_reply(u28::new(0x1), u7::new(0x1), b"{}", &[0x1; 40], u28::new(40));
+        assert_eq!(messages[0].number_of_chunks(), u14::new(3));
+        assert_eq!(messages[2].chunk_number(), u14::new(3));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{ci::property_exchange::property_data, error::InvalidData};
+use ux::{u14, u28, u7};
+
+const ERR_MAX_SYSEX_SIZE_TOO_SMALL: &str =
+    "Max sysex size is too small to carry the header and property data";
+const ERR_TOO_MANY_CHUNKS: &str = "Property data requires more than 16383 chunks";
+
+/// One chunk of a property exchange message, as produced by [Chunks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunk<'a> {
+    pub number_of_chunks: u14,
+    pub chunk_number: u14,
+    pub header: &'a [u7],
+    pub property_data: &'a [u7],
+}
+
+/// Splits the header and property data of a property exchange message
+/// into chunks which fit within the max sysex size of the receiver,
+/// as reported by [DiscoveryQuery::max_sysex_size](crate::ci::DiscoveryQuery::max_sysex_size)
+/// or [DiscoveryReply::max_sysex_size](crate::ci::DiscoveryReply::max_sysex_size).
+///
+/// The header is carried by the first chunk only.
+///
+/// ```rust
+/// use midi2::{ci::Chunks, ux::*, Data};
+///
+/// let header = [u7::new(b'{'), u7::new(b'}')];
+/// let property_data = [u7::new(0x0); 100];
+///
+/// let chunks = Chunks::new(&header, &property_data, u28::new(64)).expect("Large enough");
+/// assert_eq!(chunks.len(), 3);
+///
+/// for chunk in chunks {
+///     let mut message = midi2::ci::GetPropertyDataReply::<0x2, Vec<u8>>::new();
+///     message.set_header(chunk.header);
+///     message.set_number_of_chunks(chunk.number_of_chunks);
+///     message.set_chunk_number(chunk.chunk_number);
+///     message.set_property_data(chunk.property_data);
+///     assert!(message.data().len() <= 64);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chunks<'a> {
+    header: &'a [u7],
+    property_data: &'a [u7],
+    first_chunk_size: usize,
+    chunk_size: usize,
+    number_of_chunks: usize,
+    chunk_number: usize,
+}
+
+impl<'a> Chunks<'a> {
+    pub fn new(
+        header: &'a [u7],
+        property_data: &'a [u7],
+        max_sysex_size: u28,
+    ) -> Result<Self, InvalidData> {
+        let chunk_size = (u32::from(max_sysex_size) as usize)
+            .checked_sub(property_data::MIN_SIZE)
+            .ok_or(InvalidData(ERR_MAX_SYSEX_SIZE_TOO_SMALL))?;
+        let first_chunk_size = chunk_size
+            .checked_sub(header.len())
+            .ok_or(InvalidData(ERR_MAX_SYSEX_SIZE_TOO_SMALL))?;
+
+        let remaining = property_data.len().saturating_sub(first_chunk_size);
+        let number_of_chunks = if remaining == 0 {
+            1
+        } else if chunk_size == 0 {
+            return Err(InvalidData(ERR_MAX_SYSEX_SIZE_TOO_SMALL));
+        } else {
+            1 + remaining.div_ceil(chunk_size)
+        };
+        if number_of_chunks > usize::from(u16::from(u14::MAX)) {
+            return Err(InvalidData(ERR_TOO_MANY_CHUNKS));
+        }
+
+        Ok(Chunks {
+            header,
+            property_data,
+            first_chunk_size,
+            chunk_size,
+            number_of_chunks,
+            chunk_number: 1,
+        })
+    }
+}
+
+impl<'a> core::iter::Iterator for Chunks<'a> {
+    type Item = Chunk<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunk_number > self.number_of_chunks {
+            return None;
+        }
+
+        let (header, chunk_size) = if self.chunk_number == 1 {
+            (self.header, self.first_chunk_size)
+        } else {
+            (&[][..], self.chunk_size)
+        };
+        let (property_data, remaining) = self
+            .property_data
+            .split_at(chunk_size.min(self.property_data.len()));
+        self.property_data = remaining;
+
+        let chunk = Chunk {
+            number_of_chunks: u14::new(self.number_of_chunks as u16),
+            chunk_number: u14::new(self.chunk_number as u16),
+            header,
+            property_data,
+        };
+        self.chunk_number += 1;
+        Some(chunk)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.number_of_chunks + 1 - self.chunk_number;
+        (remaining, Some(remaining))
+    }
+}
+
+impl core::iter::ExactSizeIterator for Chunks<'_> {}
+
+impl core::iter::FusedIterator for Chunks<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn single_chunk() {
+        let header = [u7::new(0x1); 4];
+        let property_data = [u7::new(0x2); 10];
+        let mut chunks = Chunks::new(&header, &property_data, u28::new(128)).unwrap();
+        assert_eq!(
+            chunks.next(),
+            Some(Chunk {
+                number_of_chunks: u14::new(1),
+                chunk_number: u14::new(1),
+                header: &header,
+                property_data: &property_data,
+            })
+        );
+        assert_eq!(chunks.next(), None);
+    }
+
+    #[test]
+    fn empty_property_data() {
+        let header = [u7::new(0x1); 4];
+        let chunks = Chunks::new(&header, &[], u28::new(28)).unwrap();
+        assert_eq!(chunks.len(), 1);
+    }
+
+    #[test]
+    fn multiple_chunks() {
+        let header = [u7::new(0x1); 4];
+        let property_data = (0..20).map(u7::new).collect::<std::vec::Vec<_>>();
+        let chunks = Chunks::new(&header, &property_data, u28::new(32))
+            .unwrap()
+            .collect::<std::vec::Vec<_>>();
+        assert_eq!(
+            chunks,
+            std::vec![
+                Chunk {
+                    number_of_chunks: u14::new(3),
+                    chunk_number: u14::new(1),
+                    header: &header,
+                    property_data: &property_data[..4],
+                },
+                Chunk {
+                    number_of_chunks: u14::new(3),
+                    chunk_number: u14::new(2),
+                    header: &[],
+                    property_data: &property_data[4..12],
+                },
+                Chunk {
+                    number_of_chunks: u14::new(3),
+                    chunk_number: u14::new(3),
+                    header: &[],
+                    property_data: &property_data[12..],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn max_sysex_size_too_small_for_header() {
+        let header = [u7::new(0x1); 10];
+        assert_eq!(
+            Chunks::new(&header, &[], u28::new(33)).err(),
+            Some(InvalidData(ERR_MAX_SYSEX_SIZE_TOO_SMALL))
+        );
+    }
+
+    #[test]
+    fn max_sysex_size_too_small_for_property_data() {
+        assert_eq!(
+            Chunks::new(&[], &[u7::new(0x1)], u28::new(24)).err(),
+            Some(InvalidData(ERR_MAX_SYSEX_SIZE_TOO_SMALL))
+        );
+    }
+}
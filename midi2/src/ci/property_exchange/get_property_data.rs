@@ -0,0 +1,166 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x34;
+
+/// Requests the value of a property from the responder.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 24),
+    SupportedVersion(version = 0x2, min_size = 24)
+)]
+struct GetPropertyData {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x1)]
+    request_id: ux::u7,
+    #[property(ci::property_exchange::HeaderWriteProperty)]
+    #[version(0x1)]
+    #[writeonly]
+    #[resize]
+    header: &[ux::u7],
+    #[property(ci::property_exchange::HeaderReadProperty)]
+    #[version(0x1)]
+    #[readonly]
+    header: &[u8],
+    #[property(ci::property_exchange::NumberOfChunksProperty)]
+    #[version(0x1)]
+    number_of_chunks: ux::u14,
+    #[property(ci::property_exchange::ChunkNumberProperty)]
+    #[version(0x1)]
+    chunk_number: ux::u14,
+    #[property(ci::property_exchange::PropertyDataWriteProperty)]
+    #[version(0x1)]
+    #[writeonly]
+    #[resize]
+    property_data: &[ux::u7],
+    #[property(ci::property_exchange::PropertyDataReadProperty)]
+    #[version(0x1)]
+    #[readonly]
+    property_data: &[u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        use crate::Data;
+
+        let message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x34, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters() {
+        use crate::Data;
+
+        let mut message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xB48D9D9));
+        message.set_destination(ux::u28::new(0x2A1D5B3));
+        message.set_request_id(ux::u7::new(0x1));
+        message.set_header(&[ux::u7::new(b'{'), ux::u7::new(b'}')]);
+        message.set_number_of_chunks(ux::u14::new(2));
+        message.set_chunk_number(ux::u14::new(1));
+        message.set_property_data(&[ux::u7::new(b'['), ux::u7::new(b'1'), ux::u7::new(b']')]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x34, 0x02, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x01, 0x02, 0x00, b'{', b'}', 0x02, 0x00, 0x01, 0x00, 0x03, 0x00, b'[', b'1', b']',
+                0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = GetPropertyData::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x34, 0x01, 0x59, 0x33, 0x23, 0x5A, 0x33, 0x2B, 0x07, 0x15,
+                0x01, 0x02, 0x00, b'{', b'}', 0x02, 0x00, 0x01, 0x00, 0x03, 0x00, b'[', b'1', b']',
+                0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.request_id(), ux::u7::new(0x1));
+        assert_eq!(message.header(), b"{}");
+        assert_eq!(message.number_of_chunks(), ux::u14::new(2));
+        assert_eq!(message.chunk_number(), ux::u14::new(1));
+        assert_eq!(message.property_data(), b"[1]");
+    }
+
+    #[test]
+    fn set_header_moves_property_data() {
+        let mut message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_number_of_chunks(ux::u14::new(3));
+        message.set_chunk_number(ux::u14::new(2));
+        message.set_property_data(&[ux::u7::new(0x10), ux::u7::new(0x11)]);
+        message.set_header(&[ux::u7::new(0x20), ux::u7::new(0x21), ux::u7::new(0x22)]);
+
+        assert_eq!(message.header(), &[0x20, 0x21, 0x22]);
+        assert_eq!(message.number_of_chunks(), ux::u14::new(3));
+        assert_eq!(message.chunk_number(), ux::u14::new(2));
+        assert_eq!(message.property_data(), &[0x10, 0x11]);
+
+        message.set_header(&[ux::u7::new(0x23)]);
+
+        assert_eq!(message.header(), &[0x23]);
+        assert_eq!(message.number_of_chunks(), ux::u14::new(3));
+        assert_eq!(message.chunk_number(), ux::u14::new(2));
+        assert_eq!(message.property_data(), &[0x10, 0x11]);
+    }
+
+    #[test]
+    fn try_set_header() {
+        use crate::Data;
+
+        let mut buffer = [0x0; 28];
+        let mut message = GetPropertyData::<0x2, _>::try_new_with_buffer(&mut buffer[..])
+            .expect("Buffer is large enough");
+        message
+            .try_set_property_data(&[ux::u7::new(0x10), ux::u7::new(0x11)])
+            .expect("Buffer is large enough");
+        message
+            .try_set_header(&[ux::u7::new(0x20), ux::u7::new(0x21)])
+            .expect("Buffer is large enough");
+        assert_eq!(message.data().len(), 28);
+        assert!(message
+            .try_set_header(&[ux::u7::new(0x20), ux::u7::new(0x21), ux::u7::new(0x22)])
+            .is_err());
+    }
+
+    #[test]
+    fn try_from_slice_inconsistent_lengths() {
+        assert!(GetPropertyData::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x34, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01,
+                0x05, 0x00, b'{', b'}', 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{
+    buffer::{BufferMut, Bytes},
+    ci::common_properties::{max_length, read_length, write_length},
+    detail::property,
+};
+
+// Property exchange data messages are laid out as
+//
+//   header | request id | header length | header data |
+//   number of chunks | chunk number | property data length | property data | 0xF7
+//
+// so the position of the fields following the header data
+// depends on the length of the header data.
+pub(crate) const MIN_SIZE: usize = 24;
+const HEADER_LENGTH_OFFSET: usize = 15;
+const HEADER_DATA_OFFSET: usize = 17;
+
+const NUMBER_OF_CHUNKS: usize = 0;
+const CHUNK_NUMBER: usize = 1;
+const PROPERTY_DATA_LENGTH: usize = 2;
+
+const ERR_INCONSISTENT_LENGTHS: &str =
+    "Header and property data lengths do not match the message size";
+
+fn header_length(buffer: &[u8]) -> usize {
+    read_length(&buffer[HEADER_LENGTH_OFFSET..HEADER_DATA_OFFSET])
+}
+
+fn chunk_fields_offset(buffer: &[u8]) -> usize {
+    HEADER_DATA_OFFSET + header_length(buffer)
+}
+
+fn field_offset(buffer: &[u8], field: usize) -> usize {
+    chunk_fields_offset(buffer) + 2 * field
+}
+
+fn property_data_length(buffer: &[u8]) -> usize {
+    let offset = field_offset(buffer, PROPERTY_DATA_LENGTH);
+    read_length(&buffer[offset..offset + 2])
+}
+
+pub(crate) fn message_size(header_length: usize, property_data_length: usize) -> usize {
+    MIN_SIZE + header_length + property_data_length
+}
+
+pub(crate) fn validate(buffer: &[u8]) -> Result<(), crate::error::InvalidData> {
+    let err = crate::error::InvalidData(ERR_INCONSISTENT_LENGTHS);
+    if buffer.len() < MIN_SIZE || buffer.len() < message_size(header_length(buffer), 0) {
+        return Err(err);
+    }
+    let size = message_size(header_length(buffer), property_data_length(buffer));
+    if buffer.get(size - 1) == Some(&0xF7) {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+pub(crate) fn header(buffer: &[u8]) -> &[u8] {
+    let end = chunk_fields_offset(buffer).min(buffer.len());
+    &buffer[HEADER_DATA_OFFSET..end]
+}
+
+pub(crate) fn property_data(buffer: &[u8]) -> &[u8] {
+    let begin = field_offset(buffer, PROPERTY_DATA_LENGTH) + 2;
+    let end = (begin + property_data_length(buffer)).min(buffer.len());
+    &buffer[begin..end]
+}
+
+fn chunk_field(buffer: &[u8], field: usize) -> ux::u14 {
+    use crate::detail::Encode7Bit;
+    let offset = field_offset(buffer, field);
+    ux::u14::from_u7s(&buffer[offset..offset + 2])
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn number_of_chunks(buffer: &[u8]) -> ux::u14 {
+    chunk_field(buffer, NUMBER_OF_CHUNKS)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn chunk_number(buffer: &[u8]) -> ux::u14 {
+    chunk_field(buffer, CHUNK_NUMBER)
+}
+
+fn length(data: &[ux::u7]) -> usize {
+    data.len().min(max_length(2))
+}
+
+fn write_data(buffer: &mut [u8], length_offset: usize, data: &[ux::u7]) {
+    let length = length(data);
+    write_length(&mut buffer[length_offset..length_offset + 2], length);
+    for (dst, src) in buffer[length_offset + 2..length_offset + 2 + length]
+        .iter_mut()
+        .zip(data.iter())
+    {
+        *dst = (*src).into();
+    }
+}
+
+pub(crate) struct HeaderWriteProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl HeaderWriteProperty<'_> {
+    // moves the fields following the header data to make room for the new header data
+    fn relocate_tail(buffer: &mut [u8], current_header_length: usize, header_length: usize) {
+        let source = HEADER_DATA_OFFSET + current_header_length;
+        let tail_length = 6 + property_data_length(buffer);
+        buffer.copy_within(
+            source..source + tail_length,
+            HEADER_DATA_OFFSET + header_length,
+        );
+    }
+}
+
+impl<'a, B: Bytes> property::Property<B> for HeaderWriteProperty<'a> {
+    type Type = &'a [ux::u7];
+}
+
+impl<B: Bytes + BufferMut> property::WriteProperty<B> for HeaderWriteProperty<'_> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        write_data(buffer.buffer_mut(), HEADER_LENGTH_OFFSET, v);
+    }
+}
+
+impl<B: Bytes + BufferMut> property::ResizeProperty<B> for HeaderWriteProperty<'_> {
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        let current_header_length = header_length(buffer.buffer());
+        let data_length = property_data_length(buffer.buffer());
+        let current_size = message_size(current_header_length, data_length);
+        let size = message_size(length(value), data_length);
+        if size > current_size {
+            buffer.resize(size);
+            Self::relocate_tail(buffer.buffer_mut(), current_header_length, length(value));
+        } else {
+            Self::relocate_tail(buffer.buffer_mut(), current_header_length, length(value));
+            buffer.resize(size);
+        }
+        buffer.buffer_mut()[size - 1] = 0xF7;
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        let current_header_length = header_length(buffer.buffer());
+        let size = message_size(length(value), property_data_length(buffer.buffer()));
+        buffer.try_resize(size)?;
+        Self::relocate_tail(buffer.buffer_mut(), current_header_length, length(value));
+        buffer.buffer_mut()[size - 1] = 0xF7;
+        Ok(())
+    }
+}
+
+pub(crate) struct HeaderReadProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, B: Bytes> property::Property<B> for HeaderReadProperty<'a> {
+    type Type = &'a [u8];
+}
+
+impl<'a, B: 'a + Bytes> property::ReadProperty<'a, B> for HeaderReadProperty<'a> {
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        validate(buffer.buffer())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        header(buffer.buffer())
+    }
+}
+
+pub(crate) struct ChunkFieldProperty<const FIELD: usize>;
+
+pub(crate) type NumberOfChunksProperty = ChunkFieldProperty<NUMBER_OF_CHUNKS>;
+pub(crate) type ChunkNumberProperty = ChunkFieldProperty<CHUNK_NUMBER>;
+
+impl<const FIELD: usize, B: Bytes> property::Property<B> for ChunkFieldProperty<FIELD> {
+    type Type = ux::u14;
+}
+
+impl<'a, const FIELD: usize, B: Bytes> property::ReadProperty<'a, B> for ChunkFieldProperty<FIELD> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        chunk_field(buffer.buffer(), FIELD)
+    }
+}
+
+impl<const FIELD: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for ChunkFieldProperty<FIELD>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    // messages are complete within a single chunk by default
+    fn default() -> Self::Type {
+        ux::u14::new(1)
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        use crate::detail::Encode7Bit;
+        let offset = field_offset(buffer.buffer(), FIELD);
+        v.to_u7s(&mut buffer.buffer_mut()[offset..offset + 2]);
+    }
+}
+
+pub(crate) struct PropertyDataWriteProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, B: Bytes> property::Property<B> for PropertyDataWriteProperty<'a> {
+    type Type = &'a [ux::u7];
+}
+
+impl<B: Bytes + BufferMut> property::WriteProperty<B> for PropertyDataWriteProperty<'_> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let offset = field_offset(buffer.buffer(), PROPERTY_DATA_LENGTH);
+        write_data(buffer.buffer_mut(), offset, v);
+    }
+}
+
+impl<B: Bytes + BufferMut> property::ResizeProperty<B> for PropertyDataWriteProperty<'_> {
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        let size = message_size(header_length(buffer.buffer()), length(value));
+        buffer.resize(size);
+        buffer.buffer_mut()[size - 1] = 0xF7;
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        let size = message_size(header_length(buffer.buffer()), length(value));
+        buffer.try_resize(size)?;
+        buffer.buffer_mut()[size - 1] = 0xF7;
+        Ok(())
+    }
+}
+
+pub(crate) struct PropertyDataReadProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, B: Bytes> property::Property<B> for PropertyDataReadProperty<'a> {
+    type Type = &'a [u8];
+}
+
+impl<'a, B: 'a + Bytes> property::ReadProperty<'a, B> for PropertyDataReadProperty<'a> {
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        validate(buffer.buffer())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        property_data(buffer.buffer())
+    }
+}
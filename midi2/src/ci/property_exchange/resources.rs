@@ -0,0 +1,259 @@
+//! Typed models of the common property exchange resources.
+//!
+//! Property exchange headers and most resource bodies are JSON documents.
+//! The types here (de)serialize them, applying the `mutualEncoding`
+//! of the message to the property data where necessary.
+//!
+//! ```rust
+//! use midi2::ci::{property_exchange::resources::*, *};
+//! use midi2::{ux::*, Data};
+//!
+//! let info = DeviceInfo {
+//!     manufacturer_id: [u7::new(0x7D), u7::new(0x0), u7::new(0x0)],
+//!     family_id: [u7::new(0x1), u7::new(0x0)],
+//!     model_id: [u7::new(0x2), u7::new(0x0)],
+//!     version_id: [u7::new(0x0), u7::new(0x0), u7::new(0x1), u7::new(0x0)],
+//!     manufacturer: "Acme".into(),
+//!     family: "Synths".into(),
+//!     model: "Bleep".into(),
+//!     version: "1.0".into(),
+//!     serial_number: None,
+//! };
+//!
+//! let mut reply = GetPropertyDataReply::<0x2, Vec<u8>>::new();
+//! reply.set_header(&ReplyHeader::new(200).to_json());
+//! reply.set_property_data(&info.encode(MutualEncoding::Ascii).unwrap());
+//!
+//! let mut assembler = ChunkAssembler::new(u28::new(512));
+//! let assembled = assembler.push(reply.data()).unwrap().unwrap();
+//! assert_eq!(assembled.reply_header().unwrap().status, 200);
+//! assert_eq!(assembled.resource::<DeviceInfo>(), Ok(info));
+//! ```
+
+mod channel_list;
+mod device_info;
+mod header;
+mod json_schema;
+mod mode;
+mod program_list;
+mod resource_list;
+mod state;
+
+pub use channel_list::*;
+pub use device_info::*;
+pub use header::*;
+pub use json_schema::*;
+pub use mode::*;
+pub use program_list::*;
+pub use resource_list::*;
+pub use state::*;
+
+use crate::{ci::property_exchange::AssembledPropertyData, error::InvalidData};
+
+const ERR_INVALID_JSON: &str = "Invalid property exchange JSON";
+
+/// A property exchange resource which can be carried in the property data
+/// of the property exchange messages.
+pub trait Resource: Sized {
+    /// The name of the resource as it appears in the `resource` field of a request header.
+    const NAME: &'static str;
+
+    /// Read the resource from its unencoded property data.
+    fn from_property_data(data: &[u8]) -> Result<Self, InvalidData>;
+
+    /// Serialize the resource into its unencoded property data.
+    fn to_property_data(&self) -> std::vec::Vec<u8>;
+
+    /// Read the resource from property data sent with the given encoding.
+    fn decode(data: &[u8], encoding: MutualEncoding) -> Result<Self, InvalidData> {
+        Self::from_property_data(&encoding.decode(data)?)
+    }
+
+    /// Serialize the resource into property data sent with the given encoding.
+    fn encode(&self, encoding: MutualEncoding) -> Result<std::vec::Vec<ux::u7>, InvalidData> {
+        encoding.encode(&self.to_property_data())
+    }
+}
+
+impl AssembledPropertyData {
+    /// Parse the header of a request message.
+    pub fn request_header(&self) -> Result<RequestHeader, InvalidData> {
+        RequestHeader::from_json(&self.header)
+    }
+
+    /// Parse the header of a reply message.
+    pub fn reply_header(&self) -> Result<ReplyHeader, InvalidData> {
+        ReplyHeader::from_json(&self.header)
+    }
+
+    /// Read the property data as the given resource,
+    /// decoding it with the `mutualEncoding` declared in the header.
+    pub fn resource<R: Resource>(&self) -> Result<R, InvalidData> {
+        let header: EncodingHeader = from_json(&self.header)?;
+        R::decode(
+            &self.property_data,
+            header.mutual_encoding.unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodingHeader {
+    mutual_encoding: Option<MutualEncoding>,
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, InvalidData> {
+    serde_json::from_slice(data).map_err(|_| InvalidData(ERR_INVALID_JSON))
+}
+
+// property exchange JSON must be ASCII,
+// so characters outside of it are written as escape sequences
+fn to_json<T: serde::Serialize>(value: &T) -> std::vec::Vec<u8> {
+    let json = serde_json::to_string(value).expect("Serializable to JSON");
+    let mut data = std::vec::Vec::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            data.push(c as u8);
+        } else {
+            let mut units = [0_u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                data.extend_from_slice(std::format!("\\u{unit:04x}").as_bytes());
+            }
+        }
+    }
+    data
+}
+
+macro_rules! json_resource {
+    ($resource:ty, $name:literal) => {
+        impl crate::ci::property_exchange::resources::Resource for $resource {
+            const NAME: &'static str = $name;
+            fn from_property_data(data: &[u8]) -> Result<Self, crate::error::InvalidData> {
+                crate::ci::property_exchange::resources::from_json(data)
+            }
+            fn to_property_data(&self) -> std::vec::Vec<u8> {
+                crate::ci::property_exchange::resources::to_json(self)
+            }
+        }
+    };
+}
+
+use json_resource;
+
+// (de)serializes 7-bit values as a JSON array of integers
+mod u7_array {
+    pub fn serialize<S: serde::Serializer, const N: usize>(
+        value: &[ux::u7; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(|v| u8::from(*v)))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[ux::u7; N], D::Error> {
+        use serde::de::Error;
+
+        let values = <std::vec::Vec<u8> as serde::Deserialize>::deserialize(deserializer)?;
+        if values.len() != N {
+            return Err(D::Error::invalid_length(
+                values.len(),
+                &"an array of 7-bit values",
+            ));
+        }
+        let mut ret = [ux::u7::default(); N];
+        for (v, value) in ret.iter_mut().zip(values) {
+            if value > 0x7F {
+                return Err(D::Error::custom("value exceeds 7 bits"));
+            }
+            *v = ux::u7::new(value);
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ci::{ChunkAssembler, GetPropertyData, GetPropertyDataReply},
+        Data,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn to_json_escapes_non_ascii() {
+        assert_eq!(to_json(&"é𝄞"), br#""\u00e9\ud834\udd1e""#);
+    }
+
+    #[test]
+    fn escaped_json_round_trip() {
+        let value = std::string::String::from("é𝄞");
+        assert_eq!(
+            from_json::<std::string::String>(&to_json(&value)),
+            Ok(value)
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert_eq!(
+            from_json::<RequestHeader>(b"{"),
+            Err(InvalidData(ERR_INVALID_JSON))
+        );
+    }
+
+    #[test]
+    fn assembled_request_header() {
+        let mut message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_header(&RequestHeader::new(ChannelList::NAME).to_json());
+
+        let assembled = ChunkAssembler::new(ux::u28::new(512))
+            .push(message.data())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            assembled.request_header(),
+            Ok(RequestHeader::new("ChannelList"))
+        );
+    }
+
+    #[test]
+    fn assembled_mcoded7_resource() {
+        let modes = ModeList(std::vec![ModeListEntry {
+            mode: "default".into(),
+            title: "Default".into(),
+            description: None,
+        }]);
+        let header = ReplyHeader {
+            mutual_encoding: Some(MutualEncoding::Mcoded7),
+            ..ReplyHeader::new(200)
+        };
+
+        let mut message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_header(&header.to_json());
+        message.set_property_data(&modes.encode(MutualEncoding::Mcoded7).unwrap());
+
+        let assembled = ChunkAssembler::new(ux::u28::new(512))
+            .push(message.data())
+            .unwrap()
+            .unwrap();
+        assert_eq!(assembled.resource::<ModeList>(), Ok(modes));
+    }
+
+    #[test]
+    fn assembled_resource_invalid_header() {
+        let mut message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_header(&[ux::u7::new(b'[')]);
+
+        let assembled = ChunkAssembler::new(ux::u28::new(512))
+            .push(message.data())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            assembled.resource::<ModeList>(),
+            Err(InvalidData(ERR_INVALID_JSON))
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::ci::property_exchange::resources::{json_resource, u7_array};
+
+/// The `ChannelList` resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ChannelList(pub std::vec::Vec<ChannelListEntry>);
+
+json_resource!(ChannelList, "ChannelList");
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelListEntry {
+    pub title: std::string::String,
+    /// The channel number as it appears in the resource, 1 - 256.
+    ///
+    /// Channels are numbered across the 16 groups,
+    /// so channel 1 of group 2 is channel 17.
+    pub channel: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_title: Option<std::string::String>,
+    /// The bank select MSB, bank select LSB and program change number.
+    #[serde(
+        rename = "bankPC",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "option_u7_array"
+    )]
+    pub bank_pc: Option<[ux::u7; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_channel_start: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_length: Option<u16>,
+}
+
+mod option_u7_array {
+    use super::u7_array;
+
+    pub fn serialize<S: serde::Serializer>(
+        value: &Option<[ux::u7; 3]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => u7_array::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[ux::u7; 3]>, D::Error> {
+        u7_array::deserialize(deserializer).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::Resource;
+    use pretty_assertions::assert_eq;
+
+    const JSON: &[u8] = br#"[{"title":"Lead","channel":1,"programTitle":"Saw","bankPC":[0,1,5]},{"title":"Drums","channel":10}]"#;
+
+    fn channel_list() -> ChannelList {
+        ChannelList(std::vec![
+            ChannelListEntry {
+                title: "Lead".into(),
+                channel: 1,
+                program_title: Some("Saw".into()),
+                bank_pc: Some([ux::u7::new(0), ux::u7::new(1), ux::u7::new(5)]),
+                ..Default::default()
+            },
+            ChannelListEntry {
+                title: "Drums".into(),
+                channel: 10,
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn from_property_data() {
+        assert_eq!(ChannelList::from_property_data(JSON), Ok(channel_list()));
+    }
+
+    #[test]
+    fn to_property_data() {
+        assert_eq!(channel_list().to_property_data(), JSON);
+    }
+
+    #[test]
+    fn channel_beyond_first_group_round_trip() {
+        let data = br#"[{"title":"Pad","channel":17,"clusterChannelStart":17,"clusterLength":16}]"#;
+        let list = ChannelList(std::vec![ChannelListEntry {
+            title: "Pad".into(),
+            channel: 17,
+            cluster_channel_start: Some(17),
+            cluster_length: Some(16),
+            ..Default::default()
+        }]);
+        assert_eq!(ChannelList::from_property_data(data), Ok(list.clone()));
+        assert_eq!(list.to_property_data(), data);
+    }
+
+    #[test]
+    fn last_channel_round_trip() {
+        let data =
+            br#"[{"title":"MPE","channel":256,"clusterChannelStart":1,"clusterLength":256}]"#;
+        let list = ChannelList(std::vec![ChannelListEntry {
+            title: "MPE".into(),
+            channel: 256,
+            cluster_channel_start: Some(1),
+            cluster_length: Some(256),
+            ..Default::default()
+        }]);
+        assert_eq!(ChannelList::from_property_data(data), Ok(list.clone()));
+        assert_eq!(list.to_property_data(), data);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::ci::property_exchange::resources::{json_resource, u7_array};
+
+/// The `DeviceInfo` resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    #[serde(with = "u7_array")]
+    pub manufacturer_id: [ux::u7; 3],
+    #[serde(with = "u7_array")]
+    pub family_id: [ux::u7; 2],
+    #[serde(with = "u7_array")]
+    pub model_id: [ux::u7; 2],
+    #[serde(with = "u7_array")]
+    pub version_id: [ux::u7; 4],
+    pub manufacturer: std::string::String,
+    pub family: std::string::String,
+    pub model: std::string::String,
+    pub version: std::string::String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<std::string::String>,
+}
+
+json_resource!(DeviceInfo, "DeviceInfo");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::Resource;
+    use pretty_assertions::assert_eq;
+
+    const JSON: &[u8] = br#"{"manufacturerId":[125,0,0],"familyId":[1,0],"modelId":[2,0],"versionId":[0,0,1,0],"manufacturer":"Acme","family":"Synths","model":"Bleep","version":"1.0","serialNumber":"0001"}"#;
+
+    fn device_info() -> DeviceInfo {
+        DeviceInfo {
+            manufacturer_id: [ux::u7::new(0x7D), ux::u7::new(0x0), ux::u7::new(0x0)],
+            family_id: [ux::u7::new(0x1), ux::u7::new(0x0)],
+            model_id: [ux::u7::new(0x2), ux::u7::new(0x0)],
+            version_id: [
+                ux::u7::new(0x0),
+                ux::u7::new(0x0),
+                ux::u7::new(0x1),
+                ux::u7::new(0x0),
+            ],
+            manufacturer: "Acme".into(),
+            family: "Synths".into(),
+            model: "Bleep".into(),
+            version: "1.0".into(),
+            serial_number: Some("0001".into()),
+        }
+    }
+
+    #[test]
+    fn from_property_data() {
+        assert_eq!(DeviceInfo::from_property_data(JSON), Ok(device_info()));
+    }
+
+    #[test]
+    fn to_property_data() {
+        assert_eq!(device_info().to_property_data(), JSON);
+    }
+
+    #[test]
+    fn id_out_of_range() {
+        assert!(DeviceInfo::from_property_data(
+            br#"{"manufacturerId":[128,0,0],"familyId":[1,0],"modelId":[2,0],"versionId":[0,0,1,0],"manufacturer":"","family":"","model":"","version":""}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn id_wrong_length() {
+        assert!(DeviceInfo::from_property_data(
+            br#"{"manufacturerId":[0,0],"familyId":[1,0],"modelId":[2,0],"versionId":[0,0,1,0],"manufacturer":"","family":"","model":"","version":""}"#
+        )
+        .is_err());
+    }
+}
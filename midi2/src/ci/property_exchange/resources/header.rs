@@ -0,0 +1,245 @@
+use crate::{ci::mcoded7, error::InvalidData};
+
+const ERR_NOT_ASCII: &str = "ASCII encoded property data contains 8-bit values";
+#[cfg(not(feature = "zlib"))]
+const ERR_ZLIB_UNSUPPORTED: &str = "zlib+Mcoded7 encoding requires the zlib feature";
+
+/// The encoding applied to the property data of a message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum MutualEncoding {
+    #[default]
+    #[serde(rename = "ASCII")]
+    Ascii,
+    #[serde(rename = "Mcoded7")]
+    Mcoded7,
+    #[serde(rename = "zlib+Mcoded7")]
+    ZlibMcoded7,
+}
+
+impl MutualEncoding {
+    /// Encode the provided data for the property data field of a message.
+    ///
+    /// Fails when ASCII encoding data containing 8-bit values,
+    /// or zlib+Mcoded7 encoding without the `zlib` feature.
+    pub fn encode(self, data: &[u8]) -> Result<std::vec::Vec<ux::u7>, InvalidData> {
+        match self {
+            MutualEncoding::Ascii => data
+                .iter()
+                .map(|b| ux::u7::try_from(*b).map_err(|_| InvalidData(ERR_NOT_ASCII)))
+                .collect(),
+            MutualEncoding::Mcoded7 => Ok(mcoded7::encode(data.iter().copied()).collect()),
+            #[cfg(feature = "zlib")]
+            MutualEncoding::ZlibMcoded7 => Ok(mcoded7::zlib::encode(data).collect()),
+            #[cfg(not(feature = "zlib"))]
+            MutualEncoding::ZlibMcoded7 => Err(InvalidData(ERR_ZLIB_UNSUPPORTED)),
+        }
+    }
+
+    /// Decode the property data field of a message.
+    pub fn decode(self, data: &[u8]) -> Result<std::vec::Vec<u8>, InvalidData> {
+        let u7s = data.iter().map(|b| ux::u7::new(b & 0x7F));
+        match self {
+            MutualEncoding::Ascii => Ok(u7s.map(u8::from).collect()),
+            MutualEncoding::Mcoded7 => Ok(mcoded7::decode(u7s).collect()),
+            #[cfg(feature = "zlib")]
+            MutualEncoding::ZlibMcoded7 => mcoded7::zlib::decode(u7s),
+            #[cfg(not(feature = "zlib"))]
+            MutualEncoding::ZlibMcoded7 => Err(InvalidData(ERR_ZLIB_UNSUPPORTED)),
+        }
+    }
+}
+
+/// The header of an inquiry, set or subscription message.
+///
+/// Fields which are not modelled here are kept in `other`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHeader {
+    pub resource: std::string::String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub res_id: Option<std::string::String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutual_encoding: Option<MutualEncoding>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<std::string::String, serde_json::Value>,
+}
+
+impl RequestHeader {
+    pub fn new(resource: &str) -> Self {
+        RequestHeader {
+            resource: resource.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_json(data: &[u8]) -> Result<Self, InvalidData> {
+        super::from_json(data)
+    }
+
+    /// Serialize the header for the header data field of a message.
+    pub fn to_json(&self) -> std::vec::Vec<ux::u7> {
+        super::to_json(self).into_iter().map(ux::u7::new).collect()
+    }
+}
+
+/// The header of a reply or notify message.
+///
+/// Fields which are not modelled here are kept in `other`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyHeader {
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<std::string::String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutual_encoding: Option<MutualEncoding>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<std::string::String, serde_json::Value>,
+}
+
+impl ReplyHeader {
+    pub fn new(status: u16) -> Self {
+        ReplyHeader {
+            status,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_json(data: &[u8]) -> Result<Self, InvalidData> {
+        super::from_json(data)
+    }
+
+    /// Serialize the header for the header data field of a message.
+    pub fn to_json(&self) -> std::vec::Vec<ux::u7> {
+        super::to_json(self).into_iter().map(ux::u7::new).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn bytes(u7s: std::vec::Vec<ux::u7>) -> std::vec::Vec<u8> {
+        u7s.into_iter().map(u8::from).collect()
+    }
+
+    #[test]
+    fn request_header_to_json() {
+        let header = RequestHeader {
+            res_id: Some("abcd".into()),
+            offset: Some(0),
+            limit: Some(10),
+            mutual_encoding: Some(MutualEncoding::ZlibMcoded7),
+            ..RequestHeader::new("ProgramList")
+        };
+        assert_eq!(
+            std::string::String::from_utf8(bytes(header.to_json())).unwrap(),
+            r#"{"resource":"ProgramList","resId":"abcd","offset":0,"limit":10,"mutualEncoding":"zlib+Mcoded7"}"#,
+        );
+    }
+
+    #[test]
+    fn request_header_from_json() {
+        let header =
+            RequestHeader::from_json(br#"{"resource":"State","command":"start","resId":"x"}"#)
+                .unwrap();
+        assert_eq!(header.resource, "State");
+        assert_eq!(header.res_id, Some("x".into()));
+        assert_eq!(header.mutual_encoding, None);
+        assert_eq!(header.other["command"], "start");
+    }
+
+    #[test]
+    fn request_header_missing_resource() {
+        assert!(RequestHeader::from_json(br#"{"resId":"x"}"#).is_err());
+    }
+
+    #[test]
+    fn reply_header_round_trip() {
+        let header = ReplyHeader {
+            message: Some("Résumé".into()),
+            total_count: Some(128),
+            mutual_encoding: Some(MutualEncoding::Mcoded7),
+            ..ReplyHeader::new(200)
+        };
+        assert_eq!(ReplyHeader::from_json(&bytes(header.to_json())), Ok(header));
+    }
+
+    #[test]
+    fn reply_header_from_json() {
+        assert_eq!(
+            ReplyHeader::from_json(br#"{"status":404,"message":"Not found"}"#),
+            Ok(ReplyHeader {
+                message: Some("Not found".into()),
+                ..ReplyHeader::new(404)
+            }),
+        );
+    }
+
+    #[test]
+    fn ascii_encoding() {
+        assert_eq!(
+            MutualEncoding::Ascii.encode(b"{}").map(bytes),
+            Ok(b"{}".to_vec())
+        );
+        assert_eq!(MutualEncoding::Ascii.decode(b"{}"), Ok(b"{}".to_vec()));
+    }
+
+    #[test]
+    fn ascii_encoding_8_bit_data() {
+        assert_eq!(
+            MutualEncoding::Ascii.encode(&[0x80]),
+            Err(InvalidData(ERR_NOT_ASCII))
+        );
+    }
+
+    #[test]
+    fn mcoded7_encoding() {
+        let encoded = MutualEncoding::Mcoded7.encode(&[0xFF, 0x01]).unwrap();
+        assert_eq!(bytes(encoded.clone()), [0x40, 0x7F, 0x01]);
+        assert_eq!(
+            MutualEncoding::Mcoded7.decode(&bytes(encoded)),
+            Ok(std::vec![0xFF, 0x01])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zlib")]
+    fn zlib_mcoded7_encoding() {
+        let data = [0xAA_u8; 256];
+        let encoded = MutualEncoding::ZlibMcoded7.encode(&data).unwrap();
+        assert_eq!(
+            MutualEncoding::ZlibMcoded7.decode(&bytes(encoded)),
+            Ok(data.to_vec())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "zlib"))]
+    fn zlib_mcoded7_encoding_unsupported() {
+        assert_eq!(
+            MutualEncoding::ZlibMcoded7.encode(&[0x0]),
+            Err(InvalidData(ERR_ZLIB_UNSUPPORTED))
+        );
+    }
+
+    #[test]
+    fn mutual_encoding_names() {
+        assert_eq!(
+            serde_json::to_string(&[
+                MutualEncoding::Ascii,
+                MutualEncoding::Mcoded7,
+                MutualEncoding::ZlibMcoded7
+            ])
+            .unwrap(),
+            r#"["ASCII","Mcoded7","zlib+Mcoded7"]"#,
+        );
+    }
+}
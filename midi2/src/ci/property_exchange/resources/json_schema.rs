@@ -0,0 +1,23 @@
+use crate::ci::property_exchange::resources::json_resource;
+
+/// The `JSONSchema` resource, describing the data of manufacturer specific resources.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct JsonSchema(pub serde_json::Value);
+
+json_resource!(JsonSchema, "JSONSchema");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::Resource;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() {
+        let data = br#"{"properties":{"gain":{"type":"number"}},"type":"object"}"#;
+        let schema = JsonSchema::from_property_data(data).unwrap();
+        assert_eq!(schema.0["properties"]["gain"]["type"], "number");
+        assert_eq!(schema.to_property_data(), data);
+    }
+}
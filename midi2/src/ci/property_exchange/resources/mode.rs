@@ -0,0 +1,69 @@
+use crate::ci::property_exchange::resources::json_resource;
+
+/// The `ModeList` resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ModeList(pub std::vec::Vec<ModeListEntry>);
+
+json_resource!(ModeList, "ModeList");
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ModeListEntry {
+    pub mode: std::string::String,
+    pub title: std::string::String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<std::string::String>,
+}
+
+/// The `CurrentMode` resource, one of the modes of the [ModeList].
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct CurrentMode(pub std::string::String);
+
+json_resource!(CurrentMode, "CurrentMode");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::Resource;
+    use pretty_assertions::assert_eq;
+
+    const JSON: &[u8] = br#"[{"mode":"single","title":"Single"},{"mode":"multi","title":"Multi","description":"One part per channel"}]"#;
+
+    fn mode_list() -> ModeList {
+        ModeList(std::vec![
+            ModeListEntry {
+                mode: "single".into(),
+                title: "Single".into(),
+                description: None,
+            },
+            ModeListEntry {
+                mode: "multi".into(),
+                title: "Multi".into(),
+                description: Some("One part per channel".into()),
+            },
+        ])
+    }
+
+    #[test]
+    fn mode_list_from_property_data() {
+        assert_eq!(ModeList::from_property_data(JSON), Ok(mode_list()));
+    }
+
+    #[test]
+    fn mode_list_to_property_data() {
+        assert_eq!(mode_list().to_property_data(), JSON);
+    }
+
+    #[test]
+    fn current_mode() {
+        assert_eq!(
+            CurrentMode::from_property_data(br#""multi""#),
+            Ok(CurrentMode("multi".into()))
+        );
+        assert_eq!(
+            CurrentMode("single".into()).to_property_data(),
+            br#""single""#
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::ci::property_exchange::resources::{json_resource, u7_array};
+
+/// The `ProgramList` resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ProgramList(pub std::vec::Vec<ProgramListEntry>);
+
+json_resource!(ProgramList, "ProgramList");
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramListEntry {
+    pub title: std::string::String,
+    /// The bank select MSB, bank select LSB and program change number.
+    #[serde(rename = "bankPC", with = "u7_array")]
+    pub bank_pc: [ux::u7; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<std::vec::Vec<std::string::String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<std::vec::Vec<std::string::String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::Resource;
+    use pretty_assertions::assert_eq;
+
+    const JSON: &[u8] = br#"[{"title":"Grand Piano","bankPC":[0,0,0],"category":["Piano"]},{"title":"Strings","bankPC":[0,0,48],"tags":["warm","slow"]}]"#;
+
+    fn program_list() -> ProgramList {
+        ProgramList(std::vec![
+            ProgramListEntry {
+                title: "Grand Piano".into(),
+                category: Some(std::vec!["Piano".into()]),
+                ..Default::default()
+            },
+            ProgramListEntry {
+                title: "Strings".into(),
+                bank_pc: [ux::u7::new(0), ux::u7::new(0), ux::u7::new(48)],
+                tags: Some(std::vec!["warm".into(), "slow".into()]),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn from_property_data() {
+        assert_eq!(ProgramList::from_property_data(JSON), Ok(program_list()));
+    }
+
+    #[test]
+    fn to_property_data() {
+        assert_eq!(program_list().to_property_data(), JSON);
+    }
+
+    #[test]
+    fn missing_bank_pc() {
+        assert!(ProgramList::from_property_data(br#"[{"title":"Piano"}]"#).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::ci::property_exchange::resources::json_resource;
+
+/// The `ResourceList` resource, listing the resources supported by a device.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ResourceList(pub std::vec::Vec<ResourceListEntry>);
+
+json_resource!(ResourceList, "ResourceList");
+
+/// A resource supported by a device.
+///
+/// Properties which are omitted take the defaults defined
+/// by the property exchange specification.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceListEntry {
+    pub resource: std::string::String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_get: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_set: Option<CanSet>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_subscribe: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_res_id: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_types: Option<std::vec::Vec<std::string::String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encodings: Option<std::vec::Vec<std::string::String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_paginate: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<std::vec::Vec<Column>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CanSet {
+    #[default]
+    None,
+    Full,
+    Partial,
+}
+
+/// A column to display when presenting the entries of a list resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Column {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property: Option<std::string::String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<std::string::String>,
+    pub title: std::string::String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::Resource;
+    use pretty_assertions::assert_eq;
+
+    const JSON: &[u8] = br#"[{"resource":"DeviceInfo"},{"resource":"ChannelList","canSet":"partial","canSubscribe":true,"columns":[{"property":"title","title":"Title"},{"link":"ProgramList","title":"Programs"}]}]"#;
+
+    fn resource_list() -> ResourceList {
+        ResourceList(std::vec![
+            ResourceListEntry {
+                resource: "DeviceInfo".into(),
+                ..Default::default()
+            },
+            ResourceListEntry {
+                resource: "ChannelList".into(),
+                can_set: Some(CanSet::Partial),
+                can_subscribe: Some(true),
+                columns: Some(std::vec![
+                    Column {
+                        property: Some("title".into()),
+                        link: None,
+                        title: "Title".into(),
+                    },
+                    Column {
+                        property: None,
+                        link: Some("ProgramList".into()),
+                        title: "Programs".into(),
+                    },
+                ]),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn from_property_data() {
+        assert_eq!(ResourceList::from_property_data(JSON), Ok(resource_list()));
+    }
+
+    #[test]
+    fn to_property_data() {
+        assert_eq!(resource_list().to_property_data(), JSON);
+    }
+
+    #[test]
+    fn schema() {
+        let list = ResourceList::from_property_data(
+            br#"[{"resource":"X-Gain","schema":{"type":"number"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            list.0[0].schema,
+            Some(serde_json::json!({ "type": "number" }))
+        );
+    }
+}
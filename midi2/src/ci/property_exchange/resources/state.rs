@@ -0,0 +1,46 @@
+use crate::{ci::property_exchange::resources::Resource, error::InvalidData};
+
+/// The `State` resource.
+///
+/// The state data is opaque to the receiver, so it is held as raw bytes.
+/// Typically it is sent with the [Mcoded7](super::MutualEncoding::Mcoded7)
+/// or [zlib+Mcoded7](super::MutualEncoding::ZlibMcoded7) encodings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct State(pub std::vec::Vec<u8>);
+
+impl Resource for State {
+    const NAME: &'static str = "State";
+    fn from_property_data(data: &[u8]) -> Result<Self, InvalidData> {
+        Ok(State(data.to_vec()))
+    }
+    fn to_property_data(&self) -> std::vec::Vec<u8> {
+        self.0.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::property_exchange::resources::MutualEncoding;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn mcoded7_round_trip() {
+        let state = State(std::vec![0x00, 0x80, 0xFF, 0x7F]);
+        let encoded: std::vec::Vec<u8> = state
+            .encode(MutualEncoding::Mcoded7)
+            .unwrap()
+            .into_iter()
+            .map(u8::from)
+            .collect();
+        assert_eq!(encoded, [0x30, 0x00, 0x00, 0x7F, 0x7F]);
+        assert_eq!(State::decode(&encoded, MutualEncoding::Mcoded7), Ok(state));
+    }
+
+    #[test]
+    fn ascii_rejects_binary_state() {
+        assert!(State(std::vec![0x80])
+            .encode(MutualEncoding::Ascii)
+            .is_err());
+    }
+}
@@ -0,0 +1,669 @@
+//! Read and write MIDI Clip Files.
+//!
+//! A MIDI Clip File stores a sequence of UMP messages with their timing.
+//! The file begins with the `SMF2CLIP` file header,
+//! followed by the clip configuration header and the clip sequence data.
+//! Every message is preceded by a [DeltaClockstamp] giving the number of ticks
+//! since the previous message.
+//! The configuration header declares the tick resolution
+//! with a [DeltaClockstampTpq] message and the sequence data
+//! is enclosed by [StartOfClip] and [EndOfClip] messages.
+//!
+//! ```rust
+//! use midi2::{
+//!     prelude::*,
+//!     clip::{ClipReader, ClipWriter},
+//!     channel_voice2::{ChannelVoice2, NoteOff, NoteOn},
+//! };
+//!
+//! let mut writer = ClipWriter::new(Vec::new(), 96).unwrap();
+//! writer.write_message(0, &NoteOn::<[u32; 4]>::new()).unwrap();
+//! writer.write_message(96, &NoteOff::<[u32; 4]>::new()).unwrap();
+//! let file = writer.finish(192).unwrap();
+//!
+//! let reader = ClipReader::try_from(&file[..]).unwrap();
+//! assert_eq!(reader.ticks_per_quarter_note(), 96);
+//!
+//! let events: Vec<(u64, UmpMessage<&[u32]>)> = reader.events().collect();
+//! assert_eq!(events.len(), 2);
+//! assert_eq!(events[1].0, 96);
+//! assert!(matches!(
+//!     events[1].1,
+//!     UmpMessage::ChannelVoice2(ChannelVoice2::NoteOff(_))
+//! ));
+//! ```
+
+use crate::{
+    buffer::Ump,
+    detail::BitOps,
+    error::InvalidData,
+    packet::size_from_ump_message_type,
+    traits::Data,
+    ump_stream::{EndOfClip, StartOfClip, UmpStream},
+    utility::{DeltaClockstamp, DeltaClockstampTpq, NoOp, Utility},
+    ux::u20,
+    UmpMessage, UmpStreamDecoder,
+};
+
+#[cfg(all(
+    feature = "channel-voice1",
+    feature = "channel-voice2",
+    feature = "flex-data",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+mod convert;
+
+#[cfg(all(
+    feature = "channel-voice1",
+    feature = "channel-voice2",
+    feature = "flex-data",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+pub use convert::{ChannelVoiceProtocol, SmfClipConverter};
+
+/// The eight bytes at the start of every MIDI Clip File.
+pub const FILE_HEADER: &[u8; 8] = b"SMF2CLIP";
+
+const ERR_MISSING_FILE_HEADER: &str = "Missing SMF2CLIP file header";
+const ERR_TRUNCATED_DATA: &str = "Clip data ends part way through a packet";
+const ERR_EXPECTED_TICKS_PER_QUARTER_NOTE: &str =
+    "Expected Delta Clockstamp Ticks Per Quarter Note at the start of the clip header";
+const ERR_ZERO_TICKS_PER_QUARTER_NOTE: &str = "Ticks per quarter note must be non zero";
+const ERR_UNEXPECTED_TICKS_PER_QUARTER_NOTE: &str =
+    "Unexpected Delta Clockstamp Ticks Per Quarter Note";
+const ERR_UNEXPECTED_START_OF_CLIP: &str = "Unexpected Start of Clip";
+const ERR_UNEXPECTED_END_OF_CLIP: &str = "Unexpected End of Clip";
+const ERR_MISSING_END_OF_CLIP: &str = "Missing End of Clip";
+const ERR_INCOMPLETE_MESSAGE: &str = "Incomplete multi packet message";
+const ERR_STRUCTURAL_MESSAGE: &str = "Clip structure messages are written by the clip writer";
+const ERR_HEADER_AFTER_SEQUENCE: &str =
+    "Header messages can't be written after the clip sequence has started";
+const ERR_TICK_BEFORE_PREVIOUS: &str = "Tick is before the previous message";
+
+/// Reads the messages of a MIDI Clip File.
+///
+/// The whole clip is read and validated on construction.
+/// The messages are then borrowed from the reader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipReader {
+    ticks_per_quarter_note: u16,
+    header: std::vec::Vec<u32>,
+    sequence: std::vec::Vec<u32>,
+    // the tick of each packet in the sequence by word offset
+    ticks: std::vec::Vec<(usize, u64)>,
+    end_of_clip_tick: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    Sequence,
+    End,
+}
+
+impl<'a> core::convert::TryFrom<&'a [u8]> for ClipReader {
+    type Error = InvalidData;
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let body = data
+            .strip_prefix(&FILE_HEADER[..])
+            .ok_or(InvalidData(ERR_MISSING_FILE_HEADER))?;
+        if body.len() % 4 != 0 {
+            return Err(InvalidData(ERR_TRUNCATED_DATA));
+        }
+        let words: std::vec::Vec<u32> = body
+            .chunks_exact(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        let mut reader = ClipReader {
+            ticks_per_quarter_note: 0,
+            header: std::vec::Vec::new(),
+            sequence: std::vec::Vec::new(),
+            ticks: std::vec::Vec::new(),
+            end_of_clip_tick: 0,
+        };
+        let mut section = Section::Header;
+        let mut tick = 0_u64;
+        let mut offset = 0;
+
+        while offset < words.len() && section != Section::End {
+            let size = size_from_ump_message_type(u8::from(words[offset].nibble(0)));
+            let packet = words
+                .get(offset..offset + size)
+                .ok_or(InvalidData(ERR_TRUNCATED_DATA))?;
+            offset += size;
+
+            if let Ok(delta) = DeltaClockstamp::try_from(packet) {
+                tick += u64::from(u32::from(delta.time_data()));
+            } else if let Ok(tpq) = DeltaClockstampTpq::try_from(packet) {
+                if section != Section::Header || reader.ticks_per_quarter_note != 0 {
+                    return Err(InvalidData(ERR_UNEXPECTED_TICKS_PER_QUARTER_NOTE));
+                }
+                if tpq.time_data() == 0 {
+                    return Err(InvalidData(ERR_ZERO_TICKS_PER_QUARTER_NOTE));
+                }
+                reader.ticks_per_quarter_note = tpq.time_data();
+            } else if NoOp::try_from(packet).is_ok() {
+                continue;
+            } else if reader.ticks_per_quarter_note == 0 {
+                return Err(InvalidData(ERR_EXPECTED_TICKS_PER_QUARTER_NOTE));
+            } else if StartOfClip::try_from(packet).is_ok() {
+                if section != Section::Header {
+                    return Err(InvalidData(ERR_UNEXPECTED_START_OF_CLIP));
+                }
+                section = Section::Sequence;
+                tick = 0;
+            } else if EndOfClip::try_from(packet).is_ok() {
+                if section != Section::Sequence {
+                    return Err(InvalidData(ERR_UNEXPECTED_END_OF_CLIP));
+                }
+                section = Section::End;
+                reader.end_of_clip_tick = tick;
+            } else if section == Section::Header {
+                reader.header.extend_from_slice(packet);
+            } else {
+                reader.ticks.push((reader.sequence.len(), tick));
+                reader.sequence.extend_from_slice(packet);
+            }
+        }
+
+        if section != Section::End {
+            return Err(InvalidData(ERR_MISSING_END_OF_CLIP));
+        }
+        validate_messages(&reader.header)?;
+        validate_messages(&reader.sequence)?;
+
+        Ok(reader)
+    }
+}
+
+impl ClipReader {
+    /// Read a whole clip file from the reader.
+    ///
+    /// Invalid clip data is reported as an error of kind
+    /// [InvalidData](std::io::ErrorKind::InvalidData).
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Self> {
+        let mut data = std::vec::Vec::new();
+        reader.read_to_end(&mut data)?;
+        ClipReader::try_from(&data[..])
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn ticks_per_quarter_note(&self) -> u16 {
+        self.ticks_per_quarter_note
+    }
+
+    /// The tick of the [EndOfClip] message relative to the start of the clip.
+    pub fn end_of_clip_tick(&self) -> u64 {
+        self.end_of_clip_tick
+    }
+
+    /// The messages of the clip configuration header,
+    /// other than the ticks per quarter note declaration.
+    pub fn header_messages(&self) -> impl core::iter::Iterator<Item = UmpMessage<&[u32]>> {
+        UmpStreamDecoder::new(&self.header)
+            .map(|result| result.expect("Validated on construction").1)
+    }
+
+    /// The messages of the clip sequence with their tick
+    /// relative to the start of the clip.
+    pub fn events(&self) -> ClipEvents<'_> {
+        ClipEvents {
+            decoder: UmpStreamDecoder::new(&self.sequence),
+            ticks: &self.ticks,
+        }
+    }
+}
+
+/// Iterates over the messages of a clip sequence.
+///
+/// See [ClipReader::events].
+#[derive(Debug, Clone)]
+pub struct ClipEvents<'a> {
+    decoder: UmpStreamDecoder<'a>,
+    ticks: &'a [(usize, u64)],
+}
+
+impl<'a> core::iter::Iterator for ClipEvents<'a> {
+    type Item = (u64, UmpMessage<&'a [u32]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (offset, message) = self.decoder.next()?.expect("Validated on construction");
+        let index = self.ticks.partition_point(|(o, _)| *o <= offset) - 1;
+        Some((self.ticks[index].1, message))
+    }
+}
+
+impl core::iter::FusedIterator for ClipEvents<'_> {}
+
+fn validate_messages(words: &[u32]) -> Result<(), InvalidData> {
+    let mut decoder = UmpStreamDecoder::new(words);
+    for result in decoder.by_ref() {
+        result.map_err(|e| e.error)?;
+    }
+    if decoder.remainder().is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidData(ERR_INCOMPLETE_MESSAGE))
+    }
+}
+
+/// Writes a MIDI Clip File.
+///
+/// The file header, the clip configuration header and the
+/// [StartOfClip] and [EndOfClip] messages are written by the writer.
+/// Messages written into the clip configuration header with
+/// [write_header_message](ClipWriter::write_header_message)
+/// must precede the messages of the clip sequence.
+///
+/// Invalid usage is reported as an error of kind
+/// [InvalidInput](std::io::ErrorKind::InvalidInput).
+#[derive(Debug)]
+pub struct ClipWriter<W: std::io::Write> {
+    writer: W,
+    tick: u64,
+    section: Section,
+}
+
+impl<W: std::io::Write> ClipWriter<W> {
+    /// Write the file header and the clip configuration header.
+    ///
+    /// # Fails
+    ///
+    /// If `ticks_per_quarter_note` is zero.
+    pub fn new(writer: W, ticks_per_quarter_note: u16) -> std::io::Result<Self> {
+        if ticks_per_quarter_note == 0 {
+            return Err(invalid_input(InvalidData(ERR_ZERO_TICKS_PER_QUARTER_NOTE)));
+        }
+        let mut clip_writer = ClipWriter {
+            writer,
+            tick: 0,
+            section: Section::Header,
+        };
+        clip_writer.writer.write_all(&FILE_HEADER[..])?;
+        let mut tpq = DeltaClockstampTpq::<[u32; 1]>::new();
+        tpq.set_time_data(ticks_per_quarter_note);
+        clip_writer.write_packets(0, tpq.data())?;
+        Ok(clip_writer)
+    }
+
+    /// Write a message into the clip configuration header.
+    ///
+    /// # Fails
+    ///
+    /// If a message has already been written into the clip sequence,
+    /// or if the message is part of the clip structure.
+    pub fn write_header_message<B: Ump, M: Data<B>>(&mut self, message: &M) -> std::io::Result<()> {
+        if self.section != Section::Header {
+            return Err(invalid_input(InvalidData(ERR_HEADER_AFTER_SEQUENCE)));
+        }
+        validate_written_message(message.data())?;
+        self.write_packets(0, message.data())
+    }
+
+    /// Write a message into the clip sequence at the given tick,
+    /// relative to the start of the clip.
+    ///
+    /// # Fails
+    ///
+    /// If the tick is before the tick of the previous message,
+    /// or if the message is part of the clip structure.
+    pub fn write_message<B: Ump, M: Data<B>>(
+        &mut self,
+        tick: u64,
+        message: &M,
+    ) -> std::io::Result<()> {
+        validate_written_message(message.data())?;
+        let delta = self.advance(tick)?;
+        self.write_packets(delta, message.data())
+    }
+
+    /// Write the end of the clip at the given tick
+    /// and return the underlying writer.
+    ///
+    /// # Fails
+    ///
+    /// If the tick is before the tick of the previous message.
+    pub fn finish(mut self, tick: u64) -> std::io::Result<W> {
+        let delta = self.advance(tick)?;
+        self.write_packets(delta, EndOfClip::<[u32; 4]>::new().data())?;
+        self.section = Section::End;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn advance(&mut self, tick: u64) -> std::io::Result<u64> {
+        if tick < self.tick {
+            return Err(invalid_input(InvalidData(ERR_TICK_BEFORE_PREVIOUS)));
+        }
+        if self.section == Section::Header {
+            self.section = Section::Sequence;
+            self.write_packets(0, StartOfClip::<[u32; 4]>::new().data())?;
+        }
+        let delta = tick - self.tick;
+        self.tick = tick;
+        Ok(delta)
+    }
+
+    // each packet is preceded by a delta clockstamp,
+    // deltas which overflow a single delta clockstamp
+    // are spread across several
+    fn write_packets(&mut self, mut delta: u64, data: &[u32]) -> std::io::Result<()> {
+        let max_delta = u64::from(u32::from(u20::MAX));
+        let mut offset = 0;
+        while offset < data.len() {
+            loop {
+                let step = delta.min(max_delta);
+                let mut clockstamp = DeltaClockstamp::<[u32; 1]>::new();
+                clockstamp.set_time_data(u20::new(step as u32));
+                self.write_words(clockstamp.data())?;
+                delta -= step;
+                if delta == 0 {
+                    break;
+                }
+            }
+            let size = size_from_ump_message_type(u8::from(data[offset].nibble(0)));
+            self.write_words(&data[offset..offset + size])?;
+            offset += size;
+        }
+        Ok(())
+    }
+
+    fn write_words(&mut self, words: &[u32]) -> std::io::Result<()> {
+        for word in words {
+            self.writer.write_all(&word.to_be_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_written_message(data: &[u32]) -> std::io::Result<()> {
+    let message = UmpMessage::try_from(data).map_err(invalid_input)?;
+    if matches!(
+        message,
+        UmpMessage::Utility(
+            Utility::DeltaClockstamp(_) | Utility::DeltaClockstampTpq(_) | Utility::NoOp(_)
+        ) | UmpMessage::UmpStream(UmpStream::StartOfClip(_) | UmpStream::EndOfClip(_))
+    ) {
+        return Err(invalid_input(InvalidData(ERR_STRUCTURAL_MESSAGE)));
+    }
+    Ok(())
+}
+
+fn invalid_input(error: InvalidData) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn clip(words: &[u32]) -> std::vec::Vec<u8> {
+        let mut data = FILE_HEADER.to_vec();
+        for word in words {
+            data.extend_from_slice(&word.to_be_bytes());
+        }
+        data
+    }
+
+    const DCS_0: u32 = 0x0040_0000;
+    const DCTPQ_96: u32 = 0x0030_0060;
+    const START_OF_CLIP: [u32; 4] = [0xF020_0000, 0x0, 0x0, 0x0];
+    const END_OF_CLIP: [u32; 4] = [0xF021_0000, 0x0, 0x0, 0x0];
+    const NOTE_ON: [u32; 2] = [0x4090_3C00, 0xFFFF_0000];
+    const NOTE_OFF: [u32; 2] = [0x4080_3C00, 0x0000_0000];
+
+    fn minimal_clip() -> std::vec::Vec<u32> {
+        let mut words = std::vec![DCS_0, DCTPQ_96, DCS_0];
+        words.extend_from_slice(&START_OF_CLIP);
+        words.push(DCS_0);
+        words.extend_from_slice(&NOTE_ON);
+        words.push(0x0040_0060);
+        words.extend_from_slice(&NOTE_OFF);
+        words.push(0x0040_0060);
+        words.extend_from_slice(&END_OF_CLIP);
+        words
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn read() {
+        let reader = ClipReader::try_from(&clip(&minimal_clip())[..]).unwrap();
+        assert_eq!(reader.ticks_per_quarter_note(), 96);
+        assert_eq!(
+            reader
+                .events()
+                .map(|(tick, message)| (tick, message.data().to_vec()))
+                .collect::<std::vec::Vec<_>>(),
+            [(0, NOTE_ON.to_vec()), (96, NOTE_OFF.to_vec())],
+        );
+        assert_eq!(reader.end_of_clip_tick(), 192);
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn read_from_reader() {
+        let data = clip(&minimal_clip());
+        let reader = ClipReader::from_reader(&data[..]).unwrap();
+        assert_eq!(reader.events().count(), 2);
+    }
+
+    #[test]
+    fn read_missing_file_header() {
+        let mut data = clip(&minimal_clip());
+        data[0] = b'X';
+        assert_eq!(
+            ClipReader::try_from(&data[..]),
+            Err(InvalidData(ERR_MISSING_FILE_HEADER)),
+        );
+    }
+
+    #[test]
+    fn read_from_reader_invalid_data() {
+        let data = b"SMF2CLIX".to_vec();
+        assert_eq!(
+            ClipReader::from_reader(&data[..]).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData,
+        );
+    }
+
+    #[test]
+    fn read_truncated_data() {
+        let mut data = clip(&minimal_clip());
+        data.pop();
+        assert_eq!(
+            ClipReader::try_from(&data[..]),
+            Err(InvalidData(ERR_TRUNCATED_DATA)),
+        );
+    }
+
+    #[test]
+    fn read_missing_ticks_per_quarter_note() {
+        let mut words = std::vec![DCS_0];
+        words.extend_from_slice(&START_OF_CLIP);
+        words.push(DCS_0);
+        words.extend_from_slice(&END_OF_CLIP);
+        assert_eq!(
+            ClipReader::try_from(&clip(&words)[..]),
+            Err(InvalidData(ERR_EXPECTED_TICKS_PER_QUARTER_NOTE)),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn read_missing_end_of_clip() {
+        let mut words = minimal_clip();
+        words.truncate(words.len() - 5);
+        assert_eq!(
+            ClipReader::try_from(&clip(&words)[..]),
+            Err(InvalidData(ERR_MISSING_END_OF_CLIP)),
+        );
+    }
+
+    #[test]
+    fn read_end_of_clip_before_start() {
+        let mut words = std::vec![DCS_0, DCTPQ_96, DCS_0];
+        words.extend_from_slice(&END_OF_CLIP);
+        assert_eq!(
+            ClipReader::try_from(&clip(&words)[..]),
+            Err(InvalidData(ERR_UNEXPECTED_END_OF_CLIP)),
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice2", feature = "flex-data"))]
+    fn read_header_messages() {
+        let mut words = std::vec![DCS_0, DCTPQ_96, DCS_0];
+        // set tempo
+        words.extend_from_slice(&[0xD010_0000, 0x02FA_F080, 0x0, 0x0]);
+        words.extend_from_slice(&minimal_clip()[2..]);
+        let reader = ClipReader::try_from(&clip(&words)[..]).unwrap();
+        assert_eq!(
+            reader
+                .header_messages()
+                .map(|message| message.data().to_vec())
+                .collect::<std::vec::Vec<_>>(),
+            [std::vec![0xD010_0000, 0x02FA_F080, 0x0, 0x0]],
+        );
+        assert_eq!(reader.events().count(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn read_multi_packet_message() {
+        let mut words = std::vec![DCS_0, DCTPQ_96, DCS_0];
+        words.extend_from_slice(&START_OF_CLIP);
+        words.extend_from_slice(&[0x0040_0010, 0x3016_0001, 0x0203_0405]);
+        words.extend_from_slice(&[DCS_0, 0x3032_0607, 0x0000_0000]);
+        words.push(DCS_0);
+        words.extend_from_slice(&END_OF_CLIP);
+        let reader = ClipReader::try_from(&clip(&words)[..]).unwrap();
+        assert_eq!(
+            reader
+                .events()
+                .map(|(tick, message)| (tick, message.data().to_vec()))
+                .collect::<std::vec::Vec<_>>(),
+            [(
+                16,
+                std::vec![0x3016_0001, 0x0203_0405, 0x3032_0607, 0x0000_0000]
+            )],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn read_incomplete_multi_packet_message() {
+        let mut words = std::vec![DCS_0, DCTPQ_96, DCS_0];
+        words.extend_from_slice(&START_OF_CLIP);
+        words.extend_from_slice(&[DCS_0, 0x3016_0001, 0x0203_0405]);
+        words.push(DCS_0);
+        words.extend_from_slice(&END_OF_CLIP);
+        assert_eq!(
+            ClipReader::try_from(&clip(&words)[..]),
+            Err(InvalidData(ERR_INCOMPLETE_MESSAGE)),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn write() {
+        let mut writer = ClipWriter::new(std::vec::Vec::new(), 96).unwrap();
+        writer
+            .write_message(
+                0,
+                &crate::channel_voice2::NoteOn::try_from(&NOTE_ON[..]).unwrap(),
+            )
+            .unwrap();
+        writer
+            .write_message(
+                96,
+                &crate::channel_voice2::NoteOff::try_from(&NOTE_OFF[..]).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(writer.finish(192).unwrap(), clip(&minimal_clip()));
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn write_large_delta() {
+        let mut writer = ClipWriter::new(std::vec::Vec::new(), 96).unwrap();
+        writer
+            .write_message(
+                0x10_0001,
+                &crate::channel_voice2::NoteOn::try_from(&NOTE_ON[..]).unwrap(),
+            )
+            .unwrap();
+        let data = writer.finish(0x10_0001).unwrap();
+
+        let mut words = std::vec![DCS_0, DCTPQ_96, DCS_0];
+        words.extend_from_slice(&START_OF_CLIP);
+        words.extend_from_slice(&[0x004F_FFFF, 0x0040_0002]);
+        words.extend_from_slice(&NOTE_ON);
+        words.push(DCS_0);
+        words.extend_from_slice(&END_OF_CLIP);
+        assert_eq!(data, clip(&words));
+
+        let reader = ClipReader::try_from(&data[..]).unwrap();
+        assert_eq!(reader.events().next().unwrap().0, 0x10_0001);
+    }
+
+    #[test]
+    #[cfg(feature = "flex-data")]
+    fn write_header_message() {
+        let mut writer = ClipWriter::new(std::vec::Vec::new(), 96).unwrap();
+        let tempo = crate::flex_data::SetTempo::<[u32; 4]>::new();
+        writer.write_header_message(&tempo).unwrap();
+        let data = writer.finish(0).unwrap();
+        let reader = ClipReader::try_from(&data[..]).unwrap();
+        assert_eq!(reader.header_messages().count(), 1);
+        assert_eq!(reader.events().count(), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice2", feature = "flex-data"))]
+    fn write_header_message_after_sequence() {
+        let mut writer = ClipWriter::new(std::vec::Vec::new(), 96).unwrap();
+        writer
+            .write_message(0, &crate::channel_voice2::NoteOn::<[u32; 4]>::new())
+            .unwrap();
+        assert_eq!(
+            writer
+                .write_header_message(&crate::flex_data::SetTempo::<[u32; 4]>::new())
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidInput,
+        );
+    }
+
+    #[test]
+    fn write_zero_ticks_per_quarter_note() {
+        assert!(ClipWriter::new(std::vec::Vec::new(), 0).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn write_tick_before_previous() {
+        let mut writer = ClipWriter::new(std::vec::Vec::new(), 96).unwrap();
+        writer
+            .write_message(10, &crate::channel_voice2::NoteOn::<[u32; 4]>::new())
+            .unwrap();
+        assert!(writer
+            .write_message(9, &crate::channel_voice2::NoteOn::<[u32; 4]>::new())
+            .is_err());
+        assert!(writer.finish(9).is_err());
+    }
+
+    #[test]
+    fn write_structural_message() {
+        let mut writer = ClipWriter::new(std::vec::Vec::new(), 96).unwrap();
+        assert!(writer
+            .write_message(0, &EndOfClip::<[u32; 4]>::new())
+            .is_err());
+        assert!(writer
+            .write_message(0, &DeltaClockstamp::<[u32; 4]>::new())
+            .is_err());
+    }
+}
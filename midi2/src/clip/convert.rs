@@ -0,0 +1,636 @@
+use crate::{
+    channel_voice1::ChannelVoice1,
+    clip::{invalid_input, ClipReader, ClipWriter},
+    error::InvalidData,
+    flex_data::FlexData,
+    smf::{self, Event, Format, Smf, Timing, Track, TrackEvent},
+    sysex7::Sysex7,
+    system_common::SystemCommon,
+    traits::{Grouped, IntoBytes, IntoUmp, RebufferInto},
+    ux::u4,
+    BytesMessage, Midi1ToMidi2Translator, Midi2ToMidi1Translator, UmpMessage,
+};
+
+const ERR_SMPTE_TIMING: &str = "SMPTE timing has no clip equivalent";
+const ERR_TICKS_PER_QUARTER_NOTE_OUT_OF_RANGE: &str =
+    "Ticks per quarter note out of range for an SMF";
+const ERR_DELTA_OUT_OF_RANGE: &str = "Delta time out of range for an SMF";
+
+const MAX_SMF_TICKS_PER_QUARTER_NOTE: u16 = 0x7FFF;
+const MAX_SMF_DELTA: u64 = 0x0FFF_FFFF;
+
+/// Converts Standard MIDI Files into MIDI Clip Files and back.
+///
+/// When converting an [Smf] into a clip
+/// - the tracks are merged into a single clip sequence.
+///   The tracks of a [Sequential](Format::Sequential) file are played one after another.
+/// - the ticks per quarter note of the file become the
+///   [DeltaClockstampTpq](crate::utility::DeltaClockstampTpq) of the clip.
+/// - channel voice messages are upgraded to MIDI 2.0 with a [Midi1ToMidi2Translator],
+///   or kept as MIDI 1.0 channel voice UMPs, depending on the [ChannelVoiceProtocol].
+/// - all messages are placed on the converter's group.
+/// - raw meta events, divided system exclusive messages and escape events
+///   have no clip equivalent and are skipped.
+///
+/// When converting a clip into an [Smf]
+/// - a [SingleTrack](Format::SingleTrack) file is produced.
+/// - only messages on the converter's group are converted.
+///   Header messages are placed at the start of the track.
+/// - MIDI 2.0 channel voice messages are translated with a [Midi2ToMidi1Translator].
+/// - flex data messages without a meta event equivalent and
+///   all other UMP only messages are skipped.
+///
+/// ```rust
+/// use midi2::{
+///     prelude::*,
+///     channel_voice1::NoteOn,
+///     clip::{ClipReader, SmfClipConverter},
+///     smf::{Event, Format, Smf, Timing, Track, TrackEvent},
+/// };
+///
+/// let mut note_on = NoteOn::<Vec<u8>>::new();
+/// note_on.set_note_number(u7::new(0x3C));
+/// note_on.set_velocity(u7::new(0x40));
+///
+/// let smf = Smf {
+///     format: Format::SingleTrack,
+///     timing: Timing::TicksPerQuarterNote(96),
+///     tracks: vec![Track {
+///         events: vec![TrackEvent {
+///             delta: 0,
+///             event: Event::Midi(BytesMessage::ChannelVoice1(note_on.into())),
+///         }],
+///         end_of_track_delta: 96,
+///     }],
+/// };
+///
+/// let converter = SmfClipConverter::new();
+/// let clip = converter.smf_to_clip(&smf, Vec::new()).unwrap();
+///
+/// let reader = ClipReader::try_from(&clip[..]).unwrap();
+/// assert_eq!(reader.ticks_per_quarter_note(), 96);
+/// assert_eq!(reader.end_of_clip_tick(), 96);
+/// assert!(matches!(
+///     reader.events().next(),
+///     Some((0, UmpMessage::ChannelVoice2(_)))
+/// ));
+///
+/// assert_eq!(converter.clip_to_smf(&reader), Ok(smf));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SmfClipConverter {
+    group: u4,
+    protocol: ChannelVoiceProtocol,
+}
+
+/// The protocol of the channel voice messages
+/// in clips produced by a [SmfClipConverter].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelVoiceProtocol {
+    /// Channel voice messages are kept as MIDI 1.0 channel voice UMPs.
+    Midi1,
+    /// Channel voice messages are translated into MIDI 2.0 channel voice messages.
+    #[default]
+    Midi2,
+}
+
+impl SmfClipConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn group(&self) -> u4 {
+        self.group
+    }
+
+    pub fn set_group(&mut self, group: u4) {
+        self.group = group;
+    }
+
+    pub fn protocol(&self) -> ChannelVoiceProtocol {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: ChannelVoiceProtocol) {
+        self.protocol = protocol;
+    }
+
+    /// Write the SMF as a clip into the writer and return the writer.
+    ///
+    /// The clip ends at the latest End of Track event of the file.
+    ///
+    /// # Fails
+    ///
+    /// With an error of kind [InvalidInput](std::io::ErrorKind::InvalidInput)
+    /// if the file has [Smpte](Timing::Smpte) timing.
+    pub fn smf_to_clip<W: std::io::Write>(&self, smf: &Smf, writer: W) -> std::io::Result<W> {
+        let Timing::TicksPerQuarterNote(ticks_per_quarter_note) = smf.timing else {
+            return Err(invalid_input(InvalidData(ERR_SMPTE_TIMING)));
+        };
+
+        let mut events = std::vec::Vec::new();
+        let mut track_start = 0_u64;
+        let mut end_of_clip = 0_u64;
+        for track in smf.tracks.iter() {
+            let mut tick = track_start;
+            for TrackEvent { delta, event } in track.events.iter() {
+                tick += u64::from(*delta);
+                events.push((tick, event));
+            }
+            tick += u64::from(track.end_of_track_delta);
+            end_of_clip = end_of_clip.max(tick);
+            if smf.format == Format::Sequential {
+                track_start = tick;
+            }
+        }
+        // a stable sort keeps the order of simultaneous events
+        events.sort_by_key(|(tick, _)| *tick);
+
+        let mut translator = Midi1ToMidi2Translator::new();
+        let mut clip = ClipWriter::new(writer, ticks_per_quarter_note)?;
+        for (tick, event) in events {
+            match event {
+                Event::Midi(BytesMessage::ChannelVoice1(message)) => {
+                    let mut message: ChannelVoice1<std::vec::Vec<u32>> = message.clone().into_ump();
+                    message.set_group(self.group);
+                    match self.protocol {
+                        ChannelVoiceProtocol::Midi1 => clip.write_message(tick, &message)?,
+                        ChannelVoiceProtocol::Midi2 => {
+                            if let Some(translated) = translator.translate(&message) {
+                                clip.write_message(tick, &translated)?;
+                            }
+                        }
+                    }
+                }
+                Event::Midi(BytesMessage::Sysex7(message)) => {
+                    let mut message: Sysex7<std::vec::Vec<u32>> = message.clone().into_ump();
+                    message.set_group(self.group);
+                    clip.write_message(tick, &message)?;
+                }
+                Event::Midi(BytesMessage::SystemCommon(message)) => {
+                    let mut message: SystemCommon<std::vec::Vec<u32>> = message.clone().into_ump();
+                    message.set_group(self.group);
+                    clip.write_message(tick, &message)?;
+                }
+                Event::FlexData(message) => {
+                    let mut message = message.clone();
+                    message.set_group(self.group);
+                    clip.write_message(tick, &message)?;
+                }
+                Event::Meta { .. } | Event::Sysex(_) | Event::Escape(_) => {}
+            }
+        }
+        clip.finish(end_of_clip)
+    }
+
+    /// Convert the clip into a single track SMF.
+    ///
+    /// The End of Track event of the file is placed at the end of the clip.
+    ///
+    /// # Fails
+    ///
+    /// If the ticks per quarter note of the clip or the time between two
+    /// converted messages is out of range for an SMF.
+    pub fn clip_to_smf(&self, clip: &ClipReader) -> Result<Smf, InvalidData> {
+        if clip.ticks_per_quarter_note() > MAX_SMF_TICKS_PER_QUARTER_NOTE {
+            return Err(InvalidData(ERR_TICKS_PER_QUARTER_NOTE_OUT_OF_RANGE));
+        }
+
+        let translator = Midi2ToMidi1Translator::new();
+        let mut track = Track::default();
+        let mut previous_tick = 0_u64;
+        let messages = clip
+            .header_messages()
+            .map(|message| (0, message))
+            .chain(clip.events());
+        for (tick, message) in messages {
+            for event in self.smf_events(&translator, message) {
+                track.events.push(TrackEvent {
+                    delta: smf_delta(tick - previous_tick)?,
+                    event,
+                });
+                previous_tick = tick;
+            }
+        }
+        track.end_of_track_delta = smf_delta(clip.end_of_clip_tick() - previous_tick)?;
+
+        Ok(Smf {
+            format: Format::SingleTrack,
+            timing: Timing::TicksPerQuarterNote(clip.ticks_per_quarter_note()),
+            tracks: std::vec![track],
+        })
+    }
+
+    fn smf_events(
+        &self,
+        translator: &Midi2ToMidi1Translator,
+        message: UmpMessage<&[u32]>,
+    ) -> std::vec::Vec<Event> {
+        let midi = |message: BytesMessage<std::vec::Vec<u8>>| Event::Midi(message);
+        match message {
+            UmpMessage::ChannelVoice1(m) if m.group() == self.group => {
+                let m: ChannelVoice1<std::vec::Vec<u8>> = m.into_bytes();
+                std::vec![midi(m.into())]
+            }
+            UmpMessage::ChannelVoice2(m) if m.group() == self.group => translator
+                .translate(&m)
+                .into_iter()
+                .flatten()
+                .map(|m| {
+                    let m: ChannelVoice1<std::vec::Vec<u8>> = m.into_bytes();
+                    midi(m.into())
+                })
+                .collect(),
+            UmpMessage::Sysex7(m) if m.group() == self.group => {
+                let m: Sysex7<std::vec::Vec<u8>> = m.into_bytes();
+                std::vec![midi(m.into())]
+            }
+            UmpMessage::SystemCommon(m) if m.group() == self.group => {
+                let m: SystemCommon<std::vec::Vec<u8>> = m.into_bytes();
+                std::vec![midi(m.into())]
+            }
+            UmpMessage::FlexData(m) if m.group() == self.group => {
+                let m: FlexData<std::vec::Vec<u32>> = m.rebuffer_into();
+                if smf::has_smf_equivalent(&m) {
+                    std::vec![Event::FlexData(m)]
+                } else {
+                    std::vec![]
+                }
+            }
+            _ => std::vec![],
+        }
+    }
+}
+
+fn smf_delta(delta: u64) -> Result<u32, InvalidData> {
+    if delta > MAX_SMF_DELTA {
+        return Err(InvalidData(ERR_DELTA_OUT_OF_RANGE));
+    }
+    Ok(delta as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{flex_data, traits::Data};
+    use pretty_assertions::assert_eq;
+
+    fn midi(bytes: &[u8]) -> Event {
+        Event::Midi(BytesMessage::try_from(bytes).unwrap().rebuffer_into())
+    }
+
+    fn event(delta: u32, event: Event) -> TrackEvent {
+        TrackEvent { delta, event }
+    }
+
+    fn track(events: std::vec::Vec<TrackEvent>, end_of_track_delta: u32) -> Track {
+        Track {
+            events,
+            end_of_track_delta,
+        }
+    }
+
+    fn smf(format: Format, tracks: std::vec::Vec<Track>) -> Smf {
+        Smf {
+            format,
+            timing: Timing::TicksPerQuarterNote(96),
+            tracks,
+        }
+    }
+
+    fn tempo() -> FlexData<std::vec::Vec<u32>> {
+        let mut message = flex_data::SetTempo::<std::vec::Vec<u32>>::new();
+        message.set_number_of_10_nanosecond_units_per_quarter_note(50_000_000);
+        message.into()
+    }
+
+    fn clip(converter: &SmfClipConverter, smf: &Smf) -> ClipReader {
+        let data = converter.smf_to_clip(smf, std::vec::Vec::new()).unwrap();
+        ClipReader::try_from(&data[..]).unwrap()
+    }
+
+    fn clip_events(reader: &ClipReader) -> std::vec::Vec<(u64, std::vec::Vec<u32>)> {
+        reader
+            .events()
+            .map(|(tick, message)| (tick, message.data().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn smf_to_clip_merges_tracks() {
+        let reader = clip(
+            &SmfClipConverter::new(),
+            &smf(
+                Format::MultiTrack,
+                std::vec![
+                    track(
+                        std::vec![
+                            event(0, midi(&[0x90, 0x3C, 0x7F])),
+                            event(96, midi(&[0x80, 0x3C, 0x7F]))
+                        ],
+                        0,
+                    ),
+                    track(std::vec![event(48, midi(&[0x91, 0x40, 0x7F]))], 144),
+                ],
+            ),
+        );
+        assert_eq!(reader.ticks_per_quarter_note(), 96);
+        assert_eq!(
+            clip_events(&reader),
+            std::vec![
+                (0, std::vec![0x4090_3C00, 0xFFFF_0000]),
+                (48, std::vec![0x4091_4000, 0xFFFF_0000]),
+                (96, std::vec![0x4080_3C00, 0xFFFF_0000]),
+            ],
+        );
+        assert_eq!(reader.end_of_clip_tick(), 192);
+    }
+
+    #[test]
+    fn smf_to_clip_sequential_tracks() {
+        let reader = clip(
+            &SmfClipConverter::new(),
+            &smf(
+                Format::Sequential,
+                std::vec![
+                    track(std::vec![event(0, midi(&[0x90, 0x3C, 0x7F]))], 96),
+                    track(std::vec![event(0, midi(&[0x90, 0x3E, 0x7F]))], 96),
+                ],
+            ),
+        );
+        assert_eq!(
+            clip_events(&reader),
+            std::vec![
+                (0, std::vec![0x4090_3C00, 0xFFFF_0000]),
+                (96, std::vec![0x4090_3E00, 0xFFFF_0000]),
+            ],
+        );
+        assert_eq!(reader.end_of_clip_tick(), 192);
+    }
+
+    #[test]
+    fn smf_to_clip_midi1_protocol() {
+        let mut converter = SmfClipConverter::new();
+        converter.set_protocol(ChannelVoiceProtocol::Midi1);
+        let reader = clip(
+            &converter,
+            &smf(
+                Format::SingleTrack,
+                std::vec![track(std::vec![event(0, midi(&[0x90, 0x3C, 0x7F]))], 0)],
+            ),
+        );
+        assert_eq!(clip_events(&reader), std::vec![(0, std::vec![0x2090_3C7F])]);
+    }
+
+    #[test]
+    fn smf_to_clip_absorbs_bank_select() {
+        let reader = clip(
+            &SmfClipConverter::new(),
+            &smf(
+                Format::SingleTrack,
+                std::vec![track(
+                    std::vec![
+                        event(0, midi(&[0xB0, 0x00, 0x01])),
+                        event(0, midi(&[0xB0, 0x20, 0x02])),
+                        event(0, midi(&[0xC0, 0x05])),
+                    ],
+                    0,
+                )],
+            ),
+        );
+        assert_eq!(
+            clip_events(&reader),
+            std::vec![(0, std::vec![0x40C0_0001, 0x0500_0201])],
+        );
+    }
+
+    #[test]
+    fn smf_to_clip_group() {
+        let mut converter = SmfClipConverter::new();
+        converter.set_group(u4::new(0x3));
+        let reader = clip(
+            &converter,
+            &smf(
+                Format::SingleTrack,
+                std::vec![track(
+                    std::vec![
+                        event(0, Event::FlexData(tempo())),
+                        event(0, midi(&[0x90, 0x3C, 0x7F])),
+                        event(0, midi(&[0xF0, 0x01, 0x02, 0xF7])),
+                        event(0, midi(&[0xF3, 0x05])),
+                    ],
+                    0,
+                )],
+            ),
+        );
+        assert_eq!(
+            clip_events(&reader),
+            std::vec![
+                (0, std::vec![0xD310_0000, 0x02FA_F080, 0x0, 0x0]),
+                (0, std::vec![0x4390_3C00, 0xFFFF_0000]),
+                (0, std::vec![0x3302_0102, 0x0]),
+                (0, std::vec![0x13F3_0500]),
+            ],
+        );
+    }
+
+    #[test]
+    fn smf_to_clip_skips_events_without_clip_equivalent() {
+        let reader = clip(
+            &SmfClipConverter::new(),
+            &smf(
+                Format::SingleTrack,
+                std::vec![track(
+                    std::vec![
+                        event(
+                            0,
+                            Event::Meta {
+                                kind: 0x03,
+                                data: std::vec![b'A'],
+                            }
+                        ),
+                        event(0, Event::Sysex(std::vec![0x01])),
+                        event(0, Event::Escape(std::vec![0x02, 0xF7])),
+                    ],
+                    96,
+                )],
+            ),
+        );
+        assert_eq!(clip_events(&reader), std::vec![]);
+        assert_eq!(reader.end_of_clip_tick(), 96);
+    }
+
+    #[test]
+    fn smf_to_clip_smpte_timing() {
+        let smf = Smf {
+            format: Format::SingleTrack,
+            timing: Timing::Smpte {
+                frames_per_second: 25,
+                ticks_per_frame: 40,
+            },
+            tracks: std::vec![Track::default()],
+        };
+        assert_eq!(
+            SmfClipConverter::new()
+                .smf_to_clip(&smf, std::vec::Vec::new())
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidInput,
+        );
+    }
+
+    fn clip_data(words: &[u32]) -> ClipReader {
+        let mut data = crate::clip::FILE_HEADER.to_vec();
+        for word in words {
+            data.extend_from_slice(&word.to_be_bytes());
+        }
+        ClipReader::try_from(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn clip_to_smf() {
+        let reader = clip_data(&[
+            0x0040_0000, // delta clockstamp 0
+            0x0030_0060, // delta clockstamp tpq 96
+            0x0040_0000,
+            0xD010_0000, // set tempo
+            0x02FA_F080,
+            0x0,
+            0x0,
+            0x0040_0000,
+            0xF020_0000, // start of clip
+            0x0,
+            0x0,
+            0x0,
+            0x0040_0000,
+            0x4090_3C00, // midi2 note on
+            0xFFFF_0000,
+            0x0040_0060,
+            0x2080_3C40, // midi1 note off
+            0x0040_0000,
+            0x4190_3C00, // note on on another group
+            0xFFFF_0000,
+            0x0040_0060,
+            0x3002_0102, // sysex7
+            0x0,
+            0x0040_0060,
+            0xF021_0000, // end of clip
+            0x0,
+            0x0,
+            0x0,
+        ]);
+        assert_eq!(
+            SmfClipConverter::new().clip_to_smf(&reader),
+            Ok(smf(
+                Format::SingleTrack,
+                std::vec![track(
+                    std::vec![
+                        event(0, Event::FlexData(tempo())),
+                        event(0, midi(&[0x90, 0x3C, 0x7F])),
+                        event(96, midi(&[0x80, 0x3C, 0x40])),
+                        event(96, midi(&[0xF0, 0x01, 0x02, 0xF7])),
+                    ],
+                    96,
+                )],
+            )),
+        );
+    }
+
+    #[test]
+    fn clip_to_smf_expands_program_change() {
+        let reader = clip_data(&[
+            0x0040_0000,
+            0x0030_0060,
+            0x0040_0000,
+            0xF020_0000,
+            0x0,
+            0x0,
+            0x0,
+            0x0040_0000,
+            0x40C0_0001, // program change with bank
+            0x0500_0201,
+            0x0040_0000,
+            0xF021_0000,
+            0x0,
+            0x0,
+            0x0,
+        ]);
+        assert_eq!(
+            SmfClipConverter::new().clip_to_smf(&reader).unwrap().tracks[0].events,
+            std::vec![
+                event(0, midi(&[0xB0, 0x00, 0x01])),
+                event(0, midi(&[0xB0, 0x20, 0x02])),
+                event(0, midi(&[0xC0, 0x05])),
+            ],
+        );
+    }
+
+    #[test]
+    fn clip_to_smf_skips_flex_data_without_smf_equivalent() {
+        let reader = clip_data(&[
+            0x0040_0000,
+            0x0030_0060,
+            0x0040_0000,
+            0xF020_0000,
+            0x0,
+            0x0,
+            0x0,
+            0x0040_0000,
+            0xD010_0002, // set metronome
+            0x0,
+            0x0,
+            0x0,
+            0x0040_0000,
+            0xF021_0000,
+            0x0,
+            0x0,
+            0x0,
+        ]);
+        assert_eq!(
+            SmfClipConverter::new().clip_to_smf(&reader).unwrap().tracks,
+            std::vec![Track::default()],
+        );
+    }
+
+    #[test]
+    fn clip_to_smf_ticks_per_quarter_note_out_of_range() {
+        let reader = clip_data(&[
+            0x0040_0000,
+            0x0030_8000,
+            0x0040_0000,
+            0xF020_0000,
+            0x0,
+            0x0,
+            0x0,
+            0x0040_0000,
+            0xF021_0000,
+            0x0,
+            0x0,
+            0x0,
+        ]);
+        assert_eq!(
+            SmfClipConverter::new().clip_to_smf(&reader),
+            Err(InvalidData(ERR_TICKS_PER_QUARTER_NOTE_OUT_OF_RANGE)),
+        );
+    }
+
+    #[test]
+    fn clip_to_smf_delta_out_of_range() {
+        let mut words = std::vec![
+            0x0040_0000,
+            0x0030_0060,
+            0x0040_0000,
+            0xF020_0000,
+            0x0,
+            0x0,
+            0x0
+        ];
+        // 0x101 delta clockstamps of 0xFFFFF ticks
+        words.extend(core::iter::repeat_n(0x004F_FFFF, 0x101));
+        words.extend_from_slice(&[0xF021_0000, 0x0, 0x0, 0x0]);
+        assert_eq!(
+            SmfClipConverter::new().clip_to_smf(&clip_data(&words)),
+            Err(InvalidData(ERR_DELTA_OUT_OF_RANGE)),
+        );
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     },
 };
 
+mod assembler;
 mod packet;
 mod text;
 
@@ -769,6 +770,7 @@ mod ruby_language {
 
 pub use accompanying_performer_name::*;
 pub use arranger_name::*;
+pub use assembler::{FlexDataAssembler, FlexDataAssemblerError};
 pub use composer_name::*;
 pub use composition_name::*;
 pub use copyright_notice::*;
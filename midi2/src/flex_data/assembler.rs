@@ -0,0 +1,529 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Ump},
+    error::{BufferOverflow, InvalidData},
+    flex_data::{FlexData, Format, Packet},
+    ux::u4,
+    Grouped,
+};
+
+/// Reassembles [FlexData] messages from individual [Packet]s.
+///
+/// The packets of messages sent on different groups and channels,
+/// or with different banks and statuses, may be interleaved.
+/// The assembler demultiplexes the packets by
+/// `(group, optional_channel, bank, status)`
+/// and produces a message once its final packet has been received.
+///
+/// At most `STREAMS` messages are assembled concurrently, each one
+/// written into its own backing buffer.
+/// With a resizable buffer (e.g. `Vec<u32>`) use [push](FlexDataAssembler::push).
+/// With a fixed size buffer (e.g. `[u32; N]`) use [try_push](FlexDataAssembler::try_push),
+/// which reports messages which are too large for the buffer.
+///
+/// ```rust
+/// use midi2::flex_data::{FlexData, FlexDataAssembler, Packet};
+///
+/// let mut assembler = FlexDataAssembler::<Vec<u32>, 2>::new();
+///
+/// let packets = [
+///     // lyricist name on channel 0
+///     [0xD040_0106, 0x4769_6D6D, 0x6520_736F, 0x6D65_2073],
+///     // lyricist name on channel 1
+///     [0xD041_0106, 0x536F_6D65, 0x6F6E_6520, 0x656C_7365],
+///     [0xD0C0_0106, 0x6967_6E61, 0x6C21_0000, 0x0000_0000],
+///     [0xD0C1_0106, 0x2100_0000, 0x0000_0000, 0x0000_0000],
+/// ];
+///
+/// let mut names = Vec::new();
+/// for packet in packets {
+///     let packet = Packet::try_from(&packet[..]).expect("Valid packet");
+///     if let Some(FlexData::LyricistName(message)) = assembler.push(&packet).expect("Valid stream") {
+///         names.push(message.text());
+///     }
+/// }
+///
+/// assert_eq!(names, ["Gimme some signal!", "Someone else!"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlexDataAssembler<B: Ump + BufferMut, const STREAMS: usize> {
+    streams: [Stream<B>; STREAMS],
+    complete: B,
+}
+
+/// The errors reported by a [FlexDataAssembler].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlexDataAssemblerError {
+    BufferOverflow,
+    InvalidData(InvalidData),
+    /// A packet with the given format was received out of sequence
+    /// for its group, channel, bank and status.
+    UnexpectedFormat(Format),
+}
+
+impl core::convert::From<BufferOverflow> for FlexDataAssemblerError {
+    fn from(_value: BufferOverflow) -> Self {
+        FlexDataAssemblerError::BufferOverflow
+    }
+}
+
+impl core::convert::From<InvalidData> for FlexDataAssemblerError {
+    fn from(value: InvalidData) -> Self {
+        FlexDataAssemblerError::InvalidData(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FlexDataAssemblerError {}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for FlexDataAssemblerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        <Self as std::fmt::Debug>::fmt(self, f)
+    }
+}
+
+type Key = (u4, Option<u4>, u8, u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stream<B> {
+    buffer: B,
+    size: usize,
+    key: Option<Key>,
+    // the message overflowed the buffer and the remaining
+    // packets are discarded
+    discarding: bool,
+}
+
+const ERR_TOO_MANY_STREAMS: &str = "Too many concurrent streams";
+
+impl<B: Ump + BufferMut + BufferDefault, const STREAMS: usize> core::default::Default
+    for FlexDataAssembler<B, STREAMS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault, const STREAMS: usize> FlexDataAssembler<B, STREAMS> {
+    pub fn new() -> Self {
+        FlexDataAssembler {
+            streams: core::array::from_fn(|_| Stream {
+                buffer: B::default(),
+                size: 0,
+                key: None,
+                discarding: false,
+            }),
+            complete: B::default(),
+        }
+    }
+}
+
+impl<B: Ump + BufferMut, const STREAMS: usize> FlexDataAssembler<B, STREAMS> {
+    /// Drop all partially assembled messages.
+    pub fn reset(&mut self) {
+        for stream in self.streams.iter_mut() {
+            stream.key = None;
+        }
+    }
+
+    /// Feed the next packet into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// A [Format::Start] packet received while a message of the same stream
+    /// is being assembled drops the partial message and begins a new one.
+    ///
+    /// # Fails
+    ///
+    /// With [UnexpectedFormat](FlexDataAssemblerError::UnexpectedFormat)
+    /// when the packet breaks the format sequence of its stream.
+    /// - A [Format::Continue] or [Format::End] packet without a preceding
+    ///   [Format::Start] packet is dropped.
+    /// - A [Format::Complete] packet received while a message of the same stream
+    ///   is being assembled is dropped along with the partial message.
+    ///
+    /// With [InvalidData](FlexDataAssemblerError::InvalidData) when a start packet is
+    /// received while the maximum number of streams are already being assembled,
+    /// or when the assembled message is not a valid [FlexData] message.
+    pub fn push(
+        &mut self,
+        packet: &Packet,
+    ) -> Result<Option<FlexData<&[u32]>>, FlexDataAssemblerError>
+    where
+        B: BufferResize,
+    {
+        self.push_impl(packet, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+
+    /// Feed the next packet into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// # Fails
+    ///
+    /// With [UnexpectedFormat](FlexDataAssemblerError::UnexpectedFormat)
+    /// when the packet breaks the format sequence of its stream.
+    /// See [push](FlexDataAssembler::push) for details.
+    ///
+    /// With [InvalidData](FlexDataAssemblerError::InvalidData) when a start packet is
+    /// received while the maximum number of streams are already being assembled,
+    /// or when the assembled message is not a valid [FlexData] message.
+    ///
+    /// With [BufferOverflow](FlexDataAssemblerError::BufferOverflow) when the message
+    /// does not fit in the buffer.
+    /// The error is reported once and the remaining packets of the
+    /// message are discarded.
+    pub fn try_push(
+        &mut self,
+        packet: &Packet,
+    ) -> Result<Option<FlexData<&[u32]>>, FlexDataAssemblerError>
+    where
+        B: BufferTryResize,
+    {
+        self.push_impl(packet, |buffer, size| buffer.try_resize(size))
+    }
+
+    fn push_impl<Resize>(
+        &mut self,
+        packet: &Packet,
+        try_resize: Resize,
+    ) -> Result<Option<FlexData<&[u32]>>, FlexDataAssemblerError>
+    where
+        Resize: Fn(&mut B, usize) -> Result<(), BufferOverflow>,
+    {
+        let key = (
+            packet.group(),
+            super::optional_channel_from_slice(packet),
+            super::bank_from_buffer(packet),
+            super::status_from_buffer(packet),
+        );
+        let format = packet.format();
+        let position = self.streams.iter().position(|s| s.key == Some(key));
+
+        let index = match (format, position) {
+            (Format::Complete, Some(index)) => {
+                self.streams[index].key = None;
+                return Err(FlexDataAssemblerError::UnexpectedFormat(format));
+            }
+            (Format::Complete, None) => {
+                try_resize(&mut self.complete, packet.len())?;
+                self.complete.buffer_mut()[..packet.len()].copy_from_slice(packet);
+                return Ok(Some(FlexData::try_from(
+                    &self.complete.buffer()[..packet.len()],
+                )?));
+            }
+            (Format::Start, Some(index)) => {
+                self.begin(index, key);
+                index
+            }
+            (Format::Start, None) => {
+                let index = self
+                    .streams
+                    .iter()
+                    .position(|s| s.key.is_none())
+                    .ok_or(InvalidData(ERR_TOO_MANY_STREAMS))?;
+                self.begin(index, key);
+                index
+            }
+            (Format::Continue | Format::End, Some(index)) => index,
+            (Format::Continue | Format::End, None) => {
+                return Err(FlexDataAssemblerError::UnexpectedFormat(format));
+            }
+        };
+
+        let is_end = format == Format::End;
+        let result = self.append(index, packet, &try_resize);
+        let stream = &mut self.streams[index];
+        if is_end {
+            stream.key = None;
+        }
+        result?;
+
+        if is_end && !stream.discarding {
+            Ok(Some(FlexData::try_from(
+                &stream.buffer.buffer()[..stream.size],
+            )?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn begin(&mut self, index: usize, key: Key) {
+        let stream = &mut self.streams[index];
+        stream.key = Some(key);
+        stream.size = 0;
+        stream.discarding = false;
+    }
+
+    fn append<Resize>(
+        &mut self,
+        index: usize,
+        packet: &Packet,
+        try_resize: &Resize,
+    ) -> Result<(), BufferOverflow>
+    where
+        Resize: Fn(&mut B, usize) -> Result<(), BufferOverflow>,
+    {
+        let stream = &mut self.streams[index];
+        if stream.discarding {
+            return Ok(());
+        }
+        let size = stream.size + packet.len();
+        if let Err(e) = try_resize(&mut stream.buffer, size) {
+            stream.discarding = true;
+            return Err(e);
+        }
+        stream.buffer.buffer_mut()[stream.size..size].copy_from_slice(packet);
+        stream.size = size;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn packet(data: [u32; 4]) -> Packet {
+        Packet::try_from(&data[..]).unwrap()
+    }
+
+    const START_CHANNEL_0: [u32; 4] = [0xD040_0106, 0x4769_6D6D, 0x6520_736F, 0x6D65_2073];
+    const CONTINUE_CHANNEL_0: [u32; 4] = [0xD080_0106, 0x6967_6E61, 0x6C21_2020, 0x2020_2020];
+    const END_CHANNEL_0: [u32; 4] = [0xD0C0_0106, 0x6967_6E61, 0x6C21_0000, 0x0000_0000];
+    const START_CHANNEL_1: [u32; 4] = [0xD041_0106, 0x536F_6D65, 0x6F6E_6520, 0x656C_7365];
+    const END_CHANNEL_1: [u32; 4] = [0xD0C1_0106, 0x2100_0000, 0x0000_0000, 0x0000_0000];
+    const SET_TEMPO: [u32; 4] = [0xD010_0000, 0xF751_FE05, 0x0, 0x0];
+
+    #[test]
+    fn complete_packet() {
+        let mut assembler = FlexDataAssembler::<[u32; 4], 1>::new();
+        let message = assembler.try_push(&packet(SET_TEMPO)).unwrap().unwrap();
+        assert_eq!(message.data(), &SET_TEMPO[..]);
+    }
+
+    #[test]
+    fn multi_packet_message() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        let message = assembler.try_push(&packet(END_CHANNEL_0)).unwrap().unwrap();
+        assert_eq!(
+            message.data(),
+            &[
+                0xD040_0106,
+                0x4769_6D6D,
+                0x6520_736F,
+                0x6D65_2073,
+                0xD0C0_0106,
+                0x6967_6E61,
+                0x6C21_0000,
+                0x0000_0000,
+            ][..],
+        );
+    }
+
+    #[test]
+    fn message_with_continue_packets() {
+        let mut assembler = FlexDataAssembler::<[u32; 12], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(CONTINUE_CHANNEL_0)), Ok(None));
+        let message = assembler.try_push(&packet(END_CHANNEL_0)).unwrap().unwrap();
+        assert_eq!(message.data().len(), 12);
+        assert!(matches!(message, FlexData::LyricistName(_)));
+    }
+
+    #[test]
+    fn interleaved_channels() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 2>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_1)), Ok(None));
+        assert_eq!(
+            assembler
+                .try_push(&packet(END_CHANNEL_1))
+                .unwrap()
+                .unwrap()
+                .data(),
+            &[
+                0xD041_0106,
+                0x536F_6D65,
+                0x6F6E_6520,
+                0x656C_7365,
+                0xD0C1_0106,
+                0x2100_0000,
+                0x0000_0000,
+                0x0000_0000,
+            ][..],
+        );
+        assert_eq!(
+            assembler
+                .try_push(&packet(END_CHANNEL_0))
+                .unwrap()
+                .unwrap()
+                .data()[0],
+            0xD040_0106,
+        );
+    }
+
+    #[test]
+    fn interleaved_groups() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 2>::new();
+        let mut start = START_CHANNEL_0;
+        let mut end = END_CHANNEL_0;
+        start[0] |= 0x0200_0000;
+        end[0] |= 0x0200_0000;
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(start)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(end)).unwrap().unwrap().group(),
+            u4::new(0x2),
+        );
+        assert_eq!(
+            assembler
+                .try_push(&packet(END_CHANNEL_0))
+                .unwrap()
+                .unwrap()
+                .group(),
+            u4::new(0x0),
+        );
+    }
+
+    #[test]
+    fn interleaved_statuses() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 2>::new();
+        let mut start = START_CHANNEL_0;
+        let mut end = END_CHANNEL_0;
+        // project name
+        start[0] = 0xD040_0101;
+        end[0] = 0xD0C0_0101;
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(start)), Ok(None));
+        assert!(matches!(
+            assembler.try_push(&packet(end)),
+            Ok(Some(FlexData::ProjectName(_))),
+        ));
+        assert!(matches!(
+            assembler.try_push(&packet(END_CHANNEL_0)),
+            Ok(Some(FlexData::LyricistName(_))),
+        ));
+    }
+
+    #[test]
+    fn continue_without_start() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        assert_eq!(
+            assembler.try_push(&packet(CONTINUE_CHANNEL_0)),
+            Err(FlexDataAssemblerError::UnexpectedFormat(Format::Continue)),
+        );
+    }
+
+    #[test]
+    fn end_without_start() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        assert_eq!(
+            assembler.try_push(&packet(END_CHANNEL_0)),
+            Err(FlexDataAssemblerError::UnexpectedFormat(Format::End)),
+        );
+    }
+
+    #[test]
+    fn end_on_other_channel() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 2>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(END_CHANNEL_1)),
+            Err(FlexDataAssemblerError::UnexpectedFormat(Format::End)),
+        );
+    }
+
+    #[test]
+    fn start_interrupts_message() {
+        let mut assembler = FlexDataAssembler::<[u32; 12], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(CONTINUE_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        let message = assembler.try_push(&packet(END_CHANNEL_0)).unwrap().unwrap();
+        assert_eq!(message.data().len(), 8);
+    }
+
+    #[test]
+    fn complete_interrupts_message() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        let mut complete = END_CHANNEL_0;
+        complete[0] = 0xD000_0106;
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(complete)),
+            Err(FlexDataAssemblerError::UnexpectedFormat(Format::Complete)),
+        );
+        assert_eq!(
+            assembler.try_push(&packet(END_CHANNEL_0)),
+            Err(FlexDataAssemblerError::UnexpectedFormat(Format::End)),
+        );
+    }
+
+    #[test]
+    fn too_many_streams() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(START_CHANNEL_1)),
+            Err(FlexDataAssemblerError::InvalidData(InvalidData(
+                ERR_TOO_MANY_STREAMS
+            ))),
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(CONTINUE_CHANNEL_0)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(CONTINUE_CHANNEL_0)),
+            Err(FlexDataAssemblerError::BufferOverflow),
+        );
+        assert_eq!(assembler.try_push(&packet(END_CHANNEL_0)), Ok(None));
+        // the stream is free again
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assert!(assembler
+            .try_push(&packet(END_CHANNEL_0))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn reset() {
+        let mut assembler = FlexDataAssembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_CHANNEL_0)), Ok(None));
+        assembler.reset();
+        assert_eq!(
+            assembler.try_push(&packet(END_CHANNEL_0)),
+            Err(FlexDataAssemblerError::UnexpectedFormat(Format::End)),
+        );
+    }
+
+    #[test]
+    fn invalid_status() {
+        let mut assembler = FlexDataAssembler::<[u32; 4], 1>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0xD010_0330, 0x0, 0x0, 0x0])),
+            Err(FlexDataAssemblerError::InvalidData(InvalidData(
+                "Couldn't interpret flex data status / bank fields"
+            ))),
+        );
+    }
+
+    #[test]
+    fn resizable_buffer() {
+        let mut assembler = FlexDataAssembler::<std::vec::Vec<u32>, 1>::new();
+        assert_eq!(assembler.push(&packet(START_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.push(&packet(CONTINUE_CHANNEL_0)), Ok(None));
+        assert_eq!(assembler.push(&packet(CONTINUE_CHANNEL_0)), Ok(None));
+        let message = assembler.push(&packet(END_CHANNEL_0)).unwrap().unwrap();
+        assert_eq!(message.data().len(), 16);
+    }
+}
@@ -59,7 +59,7 @@ impl crate::Grouped<[u32; 4]> for Packet {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Format {
     Complete,
     Start,
@@ -11,14 +11,26 @@ pub mod channel_voice1;
 pub mod channel_voice2;
 #[cfg(feature = "ci")]
 pub mod ci;
+#[cfg(all(feature = "std", feature = "ump-stream", feature = "utility"))]
+pub mod clip;
 #[cfg(feature = "flex-data")]
 pub mod flex_data;
+#[cfg(all(
+    feature = "std",
+    feature = "channel-voice1",
+    feature = "flex-data",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+pub mod smf;
 #[cfg(feature = "sysex7")]
 pub mod sysex7;
 #[cfg(feature = "sysex8")]
 pub mod sysex8;
 #[cfg(feature = "system-common")]
 pub mod system_common;
+#[cfg(all(feature = "std", feature = "flex-data", feature = "utility"))]
+pub mod tempo_map;
 #[cfg(feature = "ump-stream")]
 pub mod ump_stream;
 #[cfg(feature = "utility")]
@@ -63,8 +75,36 @@ mod packet;
     feature = "utility"
 ))]
 mod packets;
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "channel-voice2",
+    feature = "ci",
+    feature = "flex-data",
+    feature = "sysex7",
+    feature = "sysex8",
+    feature = "system-common",
+    feature = "ump-stream",
+    feature = "utility"
+))]
+mod ump_stream_decoder;
 
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+mod bytes_stream_parser;
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+mod bytes_stream_serializer;
 mod detail;
+#[cfg(all(feature = "channel-voice1", feature = "channel-voice2"))]
+mod midi1_to_midi2;
+#[cfg(all(feature = "channel-voice1", feature = "channel-voice2"))]
+mod midi2_to_midi1;
 mod traits;
 
 pub use ux;
@@ -93,10 +133,40 @@ pub use message::*;
     feature = "utility"
 ))]
 pub use packets::*;
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "channel-voice2",
+    feature = "ci",
+    feature = "flex-data",
+    feature = "sysex7",
+    feature = "sysex8",
+    feature = "system-common",
+    feature = "ump-stream",
+    feature = "utility"
+))]
+pub use ump_stream_decoder::*;
 
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+pub use bytes_stream_parser::*;
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+pub use bytes_stream_serializer::*;
+#[cfg(all(feature = "channel-voice1", feature = "channel-voice2"))]
+pub use midi1_to_midi2::*;
+#[cfg(all(feature = "channel-voice1", feature = "channel-voice2"))]
+pub use midi2_to_midi1::*;
 pub use traits::*;
 
 pub mod num {
+    pub mod scale;
+
     pub use ux::*;
     pub type Fixed7_9 = fixed::FixedU16<fixed::types::extra::U9>;
     pub type Fixed7_25 = fixed::FixedU32<fixed::types::extra::U25>;
@@ -0,0 +1,471 @@
+use crate::{
+    buffer::Ump,
+    channel_voice1::ChannelVoice1,
+    channel_voice2::{self, ChannelVoice2},
+    num::scale,
+    ux::{u14, u4, u7},
+    Channeled, Grouped,
+};
+
+/// Translates MIDI 1.0 channel voice messages into MIDI 2.0 channel voice messages
+/// following the default translation of the MIDI 2.0 UMP specification.
+///
+/// Velocity, controller, pressure and pitch bend values are upscaled
+/// with the min-center-max algorithm (see [scale](crate::num::scale)).
+///
+/// The translator keeps track of state per group and channel so that
+/// compound MIDI 1.0 sequences can be assembled into single MIDI 2.0 messages.
+/// - Bank Select MSB / LSB (CC 0 / CC 32) are absorbed and applied to the
+///   bank of the following program change message.
+/// - Registered (CC 101 / CC 100) and non-registered (CC 99 / CC 98) parameter
+///   numbers together with Data Entry MSB / LSB (CC 6 / CC 38) are assembled
+///   into [RegisteredController](channel_voice2::RegisteredController) and
+///   [AssignableController](channel_voice2::AssignableController) messages.
+///   A message is produced for each received data entry controller.
+/// - Note on messages with zero velocity are translated into note off messages.
+///
+/// ```rust
+/// use midi2::{
+///     prelude::*,
+///     channel_voice1::{ChannelVoice1, ControlChange, ProgramChange},
+///     channel_voice2::ChannelVoice2,
+/// };
+///
+/// let mut translator = Midi1ToMidi2Translator::new();
+///
+/// let mut bank_select = ControlChange::<[u32; 4]>::new();
+/// bank_select.set_control(u7::new(0x0));
+/// bank_select.set_control_data(u7::new(0x1));
+/// assert_eq!(translator.translate(&ChannelVoice1::from(bank_select)), None);
+///
+/// let mut program_change = ProgramChange::<[u32; 4]>::new();
+/// program_change.set_program(u7::new(0x2A));
+///
+/// let Some(ChannelVoice2::ProgramChange(translated)) =
+///     translator.translate(&ChannelVoice1::from(program_change))
+/// else {
+///     panic!("Expected a program change");
+/// };
+/// assert_eq!(translated.program(), u7::new(0x2A));
+/// assert_eq!(translated.bank(), Some(u14::new(0x80)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Midi1ToMidi2Translator {
+    channels: [[ChannelState; 16]; 16],
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ChannelState {
+    bank_msb: Option<u7>,
+    bank_lsb: Option<u7>,
+    parameter_type: Option<ParameterType>,
+    parameter_msb: Option<u7>,
+    parameter_lsb: Option<u7>,
+    data_msb: u7,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParameterType {
+    Registered,
+    Assignable,
+}
+
+const BANK_SELECT_MSB: u8 = 0;
+const DATA_ENTRY_MSB: u8 = 6;
+const BANK_SELECT_LSB: u8 = 32;
+const DATA_ENTRY_LSB: u8 = 38;
+const NRPN_LSB: u8 = 98;
+const NRPN_MSB: u8 = 99;
+const RPN_LSB: u8 = 100;
+const RPN_MSB: u8 = 101;
+
+/// The velocity given to note off messages translated from
+/// note on messages with zero velocity.
+const DEFAULT_NOTE_OFF_VELOCITY: u7 = u7::new(0x40);
+
+impl core::default::Default for Midi1ToMidi2Translator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Midi1ToMidi2Translator {
+    pub fn new() -> Self {
+        Midi1ToMidi2Translator {
+            channels: [[ChannelState::default(); 16]; 16],
+        }
+    }
+
+    /// Forget all bank and parameter number selections on every group and channel.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Translate the next MIDI 1.0 message of the stream.
+    ///
+    /// Returns `None` when the message has been absorbed into the translator state
+    /// (e.g. bank select and parameter number controllers) or when data entry
+    /// is received without a selected parameter.
+    ///
+    /// The group and channel of the translated message match the provided message.
+    pub fn translate<B: Ump>(
+        &mut self,
+        message: &ChannelVoice1<B>,
+    ) -> Option<ChannelVoice2<[u32; 4]>> {
+        use ChannelVoice1 as M;
+
+        let group = message.group();
+        let channel = message.channel();
+        let state =
+            &mut self.channels[usize::from(u8::from(group))][usize::from(u8::from(channel))];
+
+        match message {
+            M::NoteOn(m) if u8::from(m.velocity()) == 0 => {
+                let mut translated = channel_voice2::NoteOff::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                translated.set_velocity(scale::u7_to_u16(DEFAULT_NOTE_OFF_VELOCITY));
+                Some(finish(translated.into(), group, channel))
+            }
+            M::NoteOn(m) => {
+                let mut translated = channel_voice2::NoteOn::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                translated.set_velocity(scale::u7_to_u16(m.velocity()));
+                Some(finish(translated.into(), group, channel))
+            }
+            M::NoteOff(m) => {
+                let mut translated = channel_voice2::NoteOff::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                translated.set_velocity(scale::u7_to_u16(m.velocity()));
+                Some(finish(translated.into(), group, channel))
+            }
+            M::KeyPressure(m) => {
+                let mut translated = channel_voice2::KeyPressure::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                translated.set_key_pressure_data(scale::u7_to_u32(m.pressure()));
+                Some(finish(translated.into(), group, channel))
+            }
+            M::ChannelPressure(m) => {
+                let mut translated = channel_voice2::ChannelPressure::<[u32; 4]>::new();
+                translated.set_channel_pressure_data(scale::u7_to_u32(m.pressure()));
+                Some(finish(translated.into(), group, channel))
+            }
+            M::PitchBend(m) => {
+                let mut translated = channel_voice2::ChannelPitchBend::<[u32; 4]>::new();
+                translated.set_pitch_bend_data(scale::u14_to_u32(m.bend()));
+                Some(finish(translated.into(), group, channel))
+            }
+            M::ProgramChange(m) => {
+                let mut translated = channel_voice2::ProgramChange::<[u32; 4]>::new();
+                translated.set_program(m.program());
+                if state.bank_msb.is_some() || state.bank_lsb.is_some() {
+                    let msb = u16::from(state.bank_msb.unwrap_or_default());
+                    let lsb = u16::from(state.bank_lsb.unwrap_or_default());
+                    translated.set_bank(Some(u14::new(msb << 7 | lsb)));
+                }
+                Some(finish(translated.into(), group, channel))
+            }
+            M::ControlChange(m) => {
+                let data = m.control_data();
+                match u8::from(m.control()) {
+                    BANK_SELECT_MSB => {
+                        state.bank_msb = Some(data);
+                        None
+                    }
+                    BANK_SELECT_LSB => {
+                        state.bank_lsb = Some(data);
+                        None
+                    }
+                    RPN_MSB => {
+                        state.select_parameter(ParameterType::Registered);
+                        state.parameter_msb = Some(data);
+                        None
+                    }
+                    RPN_LSB => {
+                        state.select_parameter(ParameterType::Registered);
+                        state.parameter_lsb = Some(data);
+                        None
+                    }
+                    NRPN_MSB => {
+                        state.select_parameter(ParameterType::Assignable);
+                        state.parameter_msb = Some(data);
+                        None
+                    }
+                    NRPN_LSB => {
+                        state.select_parameter(ParameterType::Assignable);
+                        state.parameter_lsb = Some(data);
+                        None
+                    }
+                    DATA_ENTRY_MSB => {
+                        state.data_msb = data;
+                        state
+                            .parameter_controller(u7::new(0x0))
+                            .map(|m| finish(m, group, channel))
+                    }
+                    DATA_ENTRY_LSB => state
+                        .parameter_controller(data)
+                        .map(|m| finish(m, group, channel)),
+                    _ => {
+                        let mut translated = channel_voice2::ControlChange::<[u32; 4]>::new();
+                        translated.set_control(m.control());
+                        translated.set_control_change_data(scale::u7_to_u32(data));
+                        Some(finish(translated.into(), group, channel))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ChannelState {
+    fn select_parameter(&mut self, parameter_type: ParameterType) {
+        if self.parameter_type != Some(parameter_type) {
+            self.parameter_type = Some(parameter_type);
+            self.parameter_msb = None;
+            self.parameter_lsb = None;
+        }
+        self.data_msb = u7::new(0x0);
+    }
+
+    fn parameter_controller(&self, data_lsb: u7) -> Option<ChannelVoice2<[u32; 4]>> {
+        let (Some(parameter_type), Some(bank), Some(index)) =
+            (self.parameter_type, self.parameter_msb, self.parameter_lsb)
+        else {
+            return None;
+        };
+        if u8::from(bank) == 0x7F && u8::from(index) == 0x7F {
+            // the null parameter number
+            return None;
+        }
+
+        let data = scale::u14_to_u32(u14::new(
+            u16::from(self.data_msb) << 7 | u16::from(data_lsb),
+        ));
+        Some(match parameter_type {
+            ParameterType::Registered => {
+                let mut message = channel_voice2::RegisteredController::<[u32; 4]>::new();
+                message.set_bank(bank);
+                message.set_index(index);
+                message.set_controller_data(data);
+                message.into()
+            }
+            ParameterType::Assignable => {
+                let mut message = channel_voice2::AssignableController::<[u32; 4]>::new();
+                message.set_bank(bank);
+                message.set_index(index);
+                message.set_controller_data(data);
+                message.into()
+            }
+        })
+    }
+}
+
+fn finish(mut message: ChannelVoice2<[u32; 4]>, group: u4, channel: u4) -> ChannelVoice2<[u32; 4]> {
+    message.set_group(group);
+    message.set_channel(channel);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn translate_all(
+        translator: &mut Midi1ToMidi2Translator,
+        messages: &[u32],
+    ) -> std::vec::Vec<std::vec::Vec<u32>> {
+        messages
+            .iter()
+            .filter_map(|w| {
+                let message = ChannelVoice1::try_from(core::slice::from_ref(w)).unwrap();
+                translator.translate(&message)
+            })
+            .map(|m| m.data().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn note_on() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x2391_3C7F, 0x2391_3C40, 0x2391_3C01]),
+            [
+                [0x4391_3C00, 0xFFFF_0000],
+                [0x4391_3C00, 0x8000_0000],
+                [0x4391_3C00, 0x0200_0000],
+            ],
+        );
+    }
+
+    #[test]
+    fn note_on_zero_velocity() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x2391_3C00]),
+            [[0x4381_3C00, 0x8000_0000]],
+        );
+    }
+
+    #[test]
+    fn note_off() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x2A8F_3C41]),
+            [[0x4A8F_3C00, 0x8208_0000]],
+        );
+    }
+
+    #[test]
+    fn key_pressure() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x20A2_3C7F]),
+            [[0x40A2_3C00, 0xFFFF_FFFF]],
+        );
+    }
+
+    #[test]
+    fn channel_pressure() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x20D2_4100]),
+            [[0x40D2_0000, 0x8208_2082]],
+        );
+    }
+
+    #[test]
+    fn pitch_bend() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x20E0_0040, 0x20E0_0000, 0x20E0_7F7F]),
+            [
+                [0x40E0_0000, 0x8000_0000],
+                [0x40E0_0000, 0x0000_0000],
+                [0x40E0_0000, 0xFFFF_FFFF],
+            ],
+        );
+    }
+
+    #[test]
+    fn control_change() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x25B3_0740]),
+            [[0x45B3_0700, 0x8000_0000]],
+        );
+    }
+
+    #[test]
+    fn program_change_without_bank() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x20C0_2A00]),
+            [[0x40C0_0000, 0x2A00_0000]],
+        );
+    }
+
+    #[test]
+    fn program_change_with_bank() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        let translated = translate_all(&mut translator, &[0x20B0_0003, 0x20B0_2005, 0x20C0_2A00]);
+        assert_eq!(translated.len(), 1);
+        let message = channel_voice2::ProgramChange::try_from(&translated[0][..]).unwrap();
+        assert_eq!(message.program(), u7::new(0x2A));
+        assert_eq!(message.bank(), Some(u14::new(0x3 << 7 | 0x5)));
+    }
+
+    #[test]
+    fn bank_persists() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        let translated = translate_all(&mut translator, &[0x20B0_0003, 0x20C0_2A00, 0x20C0_2B00]);
+        for data in translated {
+            let message = channel_voice2::ProgramChange::try_from(&data[..]).unwrap();
+            assert_eq!(message.bank(), Some(u14::new(0x3 << 7)));
+        }
+    }
+
+    #[test]
+    fn bank_is_per_channel() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        let translated = translate_all(&mut translator, &[0x20B0_0003, 0x20C1_2A00, 0x21C0_2A00]);
+        for data in translated {
+            let message = channel_voice2::ProgramChange::try_from(&data[..]).unwrap();
+            assert_eq!(message.bank(), None);
+        }
+    }
+
+    #[test]
+    fn registered_controller() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(
+                &mut translator,
+                &[0x20B4_6500, 0x20B4_6400, 0x20B4_0602, 0x20B4_2600]
+            ),
+            [[0x4024_0000, 0x0400_0000], [0x4024_0000, 0x0400_0000]],
+        );
+    }
+
+    #[test]
+    fn assignable_controller() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(
+                &mut translator,
+                &[0x20B4_6301, 0x20B4_6202, 0x20B4_0640, 0x20B4_267F]
+            ),
+            [[0x4034_0102, 0x8000_0000], [0x4034_0102, 0x81FC_0FE0]],
+        );
+    }
+
+    #[test]
+    fn data_entry_without_parameter() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(&mut translator, &[0x20B4_0640, 0x20B4_2640]),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn null_parameter() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(
+                &mut translator,
+                &[
+                    0x20B4_6500,
+                    0x20B4_6400,
+                    0x20B4_657F,
+                    0x20B4_647F,
+                    0x20B4_0640
+                ]
+            ),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn switching_parameter_type_clears_selection() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        assert_eq!(
+            translate_all(
+                &mut translator,
+                &[0x20B4_6500, 0x20B4_6400, 0x20B4_6301, 0x20B4_0640]
+            ),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut translator = Midi1ToMidi2Translator::new();
+        translate_all(&mut translator, &[0x20B0_0003]);
+        translator.reset();
+        assert_eq!(
+            translate_all(&mut translator, &[0x20C0_2A00]),
+            [[0x40C0_0000, 0x2A00_0000]],
+        );
+    }
+}
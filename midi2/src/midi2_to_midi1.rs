@@ -0,0 +1,428 @@
+use crate::{
+    buffer::Ump,
+    channel_voice1::{self, ChannelVoice1},
+    channel_voice2::ChannelVoice2,
+    error::InvalidData,
+    num::scale,
+    ux::{u4, u7},
+    Channeled, Grouped,
+};
+
+/// Translates MIDI 2.0 channel voice messages into MIDI 1.0 channel voice messages
+/// following the default translation of the MIDI 2.0 UMP specification.
+///
+/// Velocity, controller, pressure and pitch bend values are downscaled
+/// by discarding their least significant bits (see [scale](crate::num::scale)).
+///
+/// Some MIDI 2.0 messages expand into a sequence of MIDI 1.0 messages.
+/// - [RegisteredController](crate::channel_voice2::RegisteredController) and
+///   [AssignableController](crate::channel_voice2::AssignableController) messages
+///   expand into RPN (CC 101 / CC 100) or NRPN (CC 99 / CC 98) parameter number
+///   controllers followed by Data Entry MSB / LSB (CC 6 / CC 38).
+/// - [ProgramChange](crate::channel_voice2::ProgramChange) messages with a valid bank
+///   are preceded by Bank Select MSB / LSB (CC 0 / CC 32).
+///
+/// Per-note messages and relative controllers have no MIDI 1.0 equivalent.
+/// How these are handled is configured with the [UntranslatablePolicy].
+///
+/// The translated messages are [Ump] backed and keep the group and channel
+/// of the original message. Use [IntoBytes](crate::IntoBytes) to send them
+/// over a MIDI 1.0 byte stream.
+///
+/// ```rust
+/// use midi2::{
+///     prelude::*,
+///     channel_voice1::ChannelVoice1,
+///     channel_voice2::{ChannelVoice2, ProgramChange},
+/// };
+///
+/// let translator = Midi2ToMidi1Translator::new();
+///
+/// let mut program_change = ProgramChange::<[u32; 4]>::new();
+/// program_change.set_program(u7::new(0x2A));
+/// program_change.set_bank(Some(u14::new(0x81)));
+///
+/// let bytes: std::vec::Vec<u8> = translator
+///     .translate(&ChannelVoice2::from(program_change))
+///     .expect("Program change has a MIDI 1.0 equivalent")
+///     .flat_map(|message| {
+///         let message: ChannelVoice1<std::vec::Vec<u8>> = message.into_bytes();
+///         message.data().to_vec()
+///     })
+///     .collect();
+///
+/// assert_eq!(bytes, [0xB0, 0x00, 0x01, 0xB0, 0x20, 0x01, 0xC0, 0x2A]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Midi2ToMidi1Translator {
+    policy: UntranslatablePolicy,
+}
+
+/// Determines how a [Midi2ToMidi1Translator] handles messages
+/// which have no MIDI 1.0 equivalent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UntranslatablePolicy {
+    /// The message is silently dropped and translates to an empty sequence.
+    #[default]
+    Drop,
+    /// The translation fails with an [InvalidData] error.
+    Reject,
+}
+
+/// The sequence of MIDI 1.0 messages produced by translating
+/// a single MIDI 2.0 message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Midi1Messages {
+    messages: [ChannelVoice1<[u32; 4]>; MAX_MESSAGES],
+    group: u4,
+    channel: u4,
+    size: usize,
+    index: usize,
+}
+
+const MAX_MESSAGES: usize = 4;
+
+const BANK_SELECT_MSB: u8 = 0;
+const DATA_ENTRY_MSB: u8 = 6;
+const BANK_SELECT_LSB: u8 = 32;
+const DATA_ENTRY_LSB: u8 = 38;
+const NRPN_LSB: u8 = 98;
+const NRPN_MSB: u8 = 99;
+const RPN_LSB: u8 = 100;
+const RPN_MSB: u8 = 101;
+
+const ERR_NO_MIDI1_EQUIVALENT: &str = "Message has no MIDI 1.0 equivalent";
+
+impl Midi2ToMidi1Translator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn policy(&self) -> UntranslatablePolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: UntranslatablePolicy) {
+        self.policy = policy;
+    }
+
+    /// Translate a MIDI 2.0 message into a sequence of MIDI 1.0 messages.
+    ///
+    /// # Fails
+    ///
+    /// When the message has no MIDI 1.0 equivalent and the policy
+    /// is [UntranslatablePolicy::Reject].
+    pub fn translate<B: Ump>(
+        &self,
+        message: &ChannelVoice2<B>,
+    ) -> Result<Midi1Messages, InvalidData> {
+        use ChannelVoice2 as M;
+
+        let mut messages = Midi1Messages::new(message.group(), message.channel());
+        match message {
+            M::NoteOn(m) => {
+                let mut translated = channel_voice1::NoteOn::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                // a zero velocity would turn the note on into a note off
+                let velocity = scale::u16_to_u7(m.velocity());
+                translated.set_velocity(velocity.max(u7::new(0x1)));
+                messages.push(translated.into());
+            }
+            M::NoteOff(m) => {
+                let mut translated = channel_voice1::NoteOff::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                translated.set_velocity(scale::u16_to_u7(m.velocity()));
+                messages.push(translated.into());
+            }
+            M::KeyPressure(m) => {
+                let mut translated = channel_voice1::KeyPressure::<[u32; 4]>::new();
+                translated.set_note_number(m.note_number());
+                translated.set_pressure(scale::u32_to_u7(m.key_pressure_data()));
+                messages.push(translated.into());
+            }
+            M::ChannelPressure(m) => {
+                let mut translated = channel_voice1::ChannelPressure::<[u32; 4]>::new();
+                translated.set_pressure(scale::u32_to_u7(m.channel_pressure_data()));
+                messages.push(translated.into());
+            }
+            M::ChannelPitchBend(m) => {
+                let mut translated = channel_voice1::PitchBend::<[u32; 4]>::new();
+                translated.set_bend(scale::u32_to_u14(m.pitch_bend_data()));
+                messages.push(translated.into());
+            }
+            M::ControlChange(m) => {
+                messages.push_control_change(
+                    u8::from(m.control()),
+                    scale::u32_to_u7(m.control_change_data()).into(),
+                );
+            }
+            M::ProgramChange(m) => {
+                if let Some(bank) = m.bank() {
+                    let bank = u16::from(bank);
+                    messages.push_control_change(BANK_SELECT_MSB, (bank >> 7) as u8);
+                    messages.push_control_change(BANK_SELECT_LSB, (bank & 0x7F) as u8);
+                }
+                let mut translated = channel_voice1::ProgramChange::<[u32; 4]>::new();
+                translated.set_program(m.program());
+                messages.push(translated.into());
+            }
+            M::RegisteredController(m) => {
+                messages.push_control_change(RPN_MSB, m.bank().into());
+                messages.push_control_change(RPN_LSB, m.index().into());
+                messages.push_data_entry(m.controller_data());
+            }
+            M::AssignableController(m) => {
+                messages.push_control_change(NRPN_MSB, m.bank().into());
+                messages.push_control_change(NRPN_LSB, m.index().into());
+                messages.push_data_entry(m.controller_data());
+            }
+            M::AssignablePerNoteController(_)
+            | M::PerNoteManagement(_)
+            | M::PerNotePitchBend(_)
+            | M::RegisteredPerNoteController(_)
+            | M::RelativeAssignableController(_)
+            | M::RelativeRegisteredController(_) => match self.policy {
+                UntranslatablePolicy::Drop => {}
+                UntranslatablePolicy::Reject => {
+                    return Err(InvalidData(ERR_NO_MIDI1_EQUIVALENT));
+                }
+            },
+        }
+        Ok(messages)
+    }
+}
+
+impl Midi1Messages {
+    fn new(group: u4, channel: u4) -> Self {
+        Midi1Messages {
+            messages: [channel_voice1::NoteOff::<[u32; 4]>::new().into(); MAX_MESSAGES],
+            group,
+            channel,
+            size: 0,
+            index: 0,
+        }
+    }
+
+    fn push(&mut self, mut message: ChannelVoice1<[u32; 4]>) {
+        message.set_group(self.group);
+        message.set_channel(self.channel);
+        self.messages[self.size] = message;
+        self.size += 1;
+    }
+
+    fn push_control_change(&mut self, control: u8, data: u8) {
+        let mut message = channel_voice1::ControlChange::<[u32; 4]>::new();
+        message.set_control(u7::new(control & 0x7F));
+        message.set_control_data(u7::new(data & 0x7F));
+        self.push(message.into());
+    }
+
+    fn push_data_entry(&mut self, data: u32) {
+        let data = u16::from(scale::u32_to_u14(data));
+        self.push_control_change(DATA_ENTRY_MSB, (data >> 7) as u8);
+        self.push_control_change(DATA_ENTRY_LSB, (data & 0x7F) as u8);
+    }
+}
+
+impl core::iter::Iterator for Midi1Messages {
+    type Item = ChannelVoice1<[u32; 4]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.size {
+            return None;
+        }
+        let message = self.messages[self.index];
+        self.index += 1;
+        Some(message)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.size - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl core::iter::ExactSizeIterator for Midi1Messages {}
+
+impl core::iter::FusedIterator for Midi1Messages {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ux::u14, Data};
+    use pretty_assertions::assert_eq;
+
+    fn translate(
+        translator: &Midi2ToMidi1Translator,
+        data: &[u32],
+    ) -> Result<std::vec::Vec<u32>, InvalidData> {
+        let message = ChannelVoice2::try_from(data).unwrap();
+        Ok(translator
+            .translate(&message)?
+            .map(|m| m.data()[0])
+            .collect())
+    }
+
+    #[test]
+    fn note_on() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x4391_3C00, 0xFFFF_0000]),
+            Ok(std::vec![0x2391_3C7F]),
+        );
+    }
+
+    #[test]
+    fn note_on_minimum_velocity() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x4391_3C00, 0x0100_0000]),
+            Ok(std::vec![0x2391_3C01]),
+        );
+    }
+
+    #[test]
+    fn note_off() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x4A8F_3C00, 0x8208_0000]),
+            Ok(std::vec![0x2A8F_3C41]),
+        );
+    }
+
+    #[test]
+    fn key_pressure() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x40A2_3C00, 0xFFFF_FFFF]),
+            Ok(std::vec![0x20A2_3C7F]),
+        );
+    }
+
+    #[test]
+    fn channel_pressure() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x40D2_0000, 0x8208_2082]),
+            Ok(std::vec![0x20D2_4100]),
+        );
+    }
+
+    #[test]
+    fn pitch_bend() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x40E0_0000, 0x8000_0000]),
+            Ok(std::vec![0x20E0_0040]),
+        );
+    }
+
+    #[test]
+    fn control_change() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x45B3_0700, 0x8000_0000]),
+            Ok(std::vec![0x25B3_0740]),
+        );
+    }
+
+    #[test]
+    fn program_change_without_bank() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x40C0_0000, 0x2A00_0000]),
+            Ok(std::vec![0x20C0_2A00]),
+        );
+    }
+
+    #[test]
+    fn program_change_with_bank() {
+        let translator = Midi2ToMidi1Translator::new();
+        let mut message = crate::channel_voice2::ProgramChange::<[u32; 4]>::new();
+        message.set_group(u4::new(0x3));
+        message.set_channel(u4::new(0x5));
+        message.set_program(u7::new(0x2A));
+        message.set_bank(Some(u14::new(0x3 << 7 | 0x5)));
+        assert_eq!(
+            translate(&translator, message.data()),
+            Ok(std::vec![0x23B5_0003, 0x23B5_2005, 0x23C5_2A00]),
+        );
+    }
+
+    #[test]
+    fn registered_controller() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x4024_0000, 0x0400_0000]),
+            Ok(std::vec![
+                0x20B4_6500,
+                0x20B4_6400,
+                0x20B4_0602,
+                0x20B4_2600
+            ]),
+        );
+    }
+
+    #[test]
+    fn assignable_controller() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x4034_0102, 0x81FC_0FE0]),
+            Ok(std::vec![
+                0x20B4_6301,
+                0x20B4_6202,
+                0x20B4_0640,
+                0x20B4_267F
+            ]),
+        );
+    }
+
+    #[test]
+    fn per_note_dropped_by_default() {
+        let translator = Midi2ToMidi1Translator::new();
+        assert_eq!(
+            translate(&translator, &[0x4069_3C00, 0x8000_0000]),
+            Ok(std::vec![]),
+        );
+    }
+
+    #[test]
+    fn per_note_rejected() {
+        let mut translator = Midi2ToMidi1Translator::new();
+        translator.set_policy(UntranslatablePolicy::Reject);
+        assert_eq!(
+            translate(&translator, &[0x4069_3C00, 0x8000_0000]),
+            Err(InvalidData(ERR_NO_MIDI1_EQUIVALENT)),
+        );
+    }
+
+    #[test]
+    fn size_hint() {
+        let translator = Midi2ToMidi1Translator::new();
+        let message = ChannelVoice2::try_from(&[0x4024_0000, 0x0400_0000][..]).unwrap();
+        let mut messages = translator.translate(&message).unwrap();
+        assert_eq!(messages.len(), 4);
+        messages.next();
+        assert_eq!(messages.len(), 3);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut up = crate::Midi1ToMidi2Translator::new();
+        let down = Midi2ToMidi1Translator::new();
+        for data in [
+            0x2391_3C7F,
+            0x2A8F_3C41,
+            0x20A2_3C11,
+            0x20D2_4100,
+            0x20E0_1234,
+            0x25B3_0740,
+            0x20C0_2A00,
+        ] {
+            let buffer = [data];
+            let message = ChannelVoice1::try_from(&buffer[..]).unwrap();
+            let translated = up.translate(&message).unwrap();
+            assert_eq!(translate(&down, translated.data()), Ok(std::vec![data]),);
+        }
+    }
+}
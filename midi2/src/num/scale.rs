@@ -0,0 +1,209 @@
+//! Value scaling between MIDI 1.0 and MIDI 2.0 resolutions.
+//!
+//! Upscaling uses the min-center-max algorithm from the MIDI 2.0 UMP
+//! translation guidelines. The minimum, center and maximum values of the
+//! source range map onto the minimum, center and maximum values of the
+//! destination range, so a centered pitch bend stays centered.
+//!
+//! Downscaling discards the least significant bits. Upscaling and then
+//! downscaling a value always results in the original value.
+//!
+//! ```rust
+//! use midi2::num::{scale, u7, u14};
+//!
+//! assert_eq!(scale::u7_to_u16(u7::new(0x0)), 0x0);
+//! assert_eq!(scale::u7_to_u16(u7::new(0x40)), 0x8000);
+//! assert_eq!(scale::u7_to_u16(u7::new(0x7F)), 0xFFFF);
+//!
+//! // pitch bend center
+//! assert_eq!(scale::u14_to_u32(u14::new(0x2000)), 0x8000_0000);
+//! assert_eq!(scale::u32_to_u14(0x8000_0000), u14::new(0x2000));
+//! ```
+
+use ux::{u14, u7};
+
+/// Upscale a 7 bit value to 14 bits.
+pub fn u7_to_u14(value: u7) -> u14 {
+    u14::new(scale_up(value.into(), 7, 14) as u16)
+}
+
+/// Upscale a 7 bit value to 16 bits.
+///
+/// Used for MIDI 2.0 note velocities.
+pub fn u7_to_u16(value: u7) -> u16 {
+    scale_up(value.into(), 7, 16) as u16
+}
+
+/// Upscale a 7 bit value to 32 bits.
+///
+/// Used for MIDI 2.0 controller and pressure values.
+pub fn u7_to_u32(value: u7) -> u32 {
+    scale_up(value.into(), 7, 32)
+}
+
+/// Upscale a 14 bit value to 16 bits.
+pub fn u14_to_u16(value: u14) -> u16 {
+    scale_up(value.into(), 14, 16) as u16
+}
+
+/// Upscale a 14 bit value to 32 bits.
+///
+/// Used for MIDI 2.0 pitch bend and parameter number values.
+/// The MIDI 1.0 pitch bend center `0x2000` maps onto the
+/// MIDI 2.0 pitch bend center `0x8000_0000`.
+pub fn u14_to_u32(value: u14) -> u32 {
+    scale_up(value.into(), 14, 32)
+}
+
+/// Upscale a 16 bit value to 32 bits.
+pub fn u16_to_u32(value: u16) -> u32 {
+    scale_up(value.into(), 16, 32)
+}
+
+/// Downscale a 14 bit value to 7 bits.
+pub fn u14_to_u7(value: u14) -> u7 {
+    u7::new(scale_down(value.into(), 14, 7) as u8)
+}
+
+/// Downscale a 16 bit value to 7 bits.
+pub fn u16_to_u7(value: u16) -> u7 {
+    u7::new(scale_down(value.into(), 16, 7) as u8)
+}
+
+/// Downscale a 16 bit value to 14 bits.
+pub fn u16_to_u14(value: u16) -> u14 {
+    u14::new(scale_down(value.into(), 16, 14) as u16)
+}
+
+/// Downscale a 32 bit value to 7 bits.
+pub fn u32_to_u7(value: u32) -> u7 {
+    u7::new(scale_down(value, 32, 7) as u8)
+}
+
+/// Downscale a 32 bit value to 14 bits.
+pub fn u32_to_u14(value: u32) -> u14 {
+    u14::new(scale_down(value, 32, 14) as u16)
+}
+
+/// Downscale a 32 bit value to 16 bits.
+pub fn u32_to_u16(value: u32) -> u16 {
+    scale_down(value, 32, 16) as u16
+}
+
+fn scale_up(value: u32, src_bits: u8, dst_bits: u8) -> u32 {
+    debug_assert!(0 < src_bits && src_bits <= dst_bits && dst_bits <= 32);
+
+    let scale_bits = dst_bits - src_bits;
+    let mut scaled = value << scale_bits;
+    let center = 1_u32 << (src_bits - 1);
+    if value <= center {
+        return scaled;
+    }
+
+    // repeat the bits below the center bit
+    // to fill the lower bits of the scaled value
+    let repeat_bits = src_bits - 1;
+    let repeat_mask = (1_u32 << repeat_bits) - 1;
+    let mut repeat = value & repeat_mask;
+    if scale_bits > repeat_bits {
+        repeat <<= scale_bits - repeat_bits;
+    } else {
+        repeat >>= repeat_bits - scale_bits;
+    }
+    while repeat != 0 {
+        scaled |= repeat;
+        repeat >>= repeat_bits;
+    }
+    scaled
+}
+
+fn scale_down(value: u32, src_bits: u8, dst_bits: u8) -> u32 {
+    debug_assert!(0 < dst_bits && dst_bits <= src_bits && src_bits <= 32);
+    value >> (src_bits - dst_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn u7_to_u14_min_center_max() {
+        assert_eq!(u7_to_u14(u7::new(0x0)), u14::new(0x0));
+        assert_eq!(u7_to_u14(u7::new(0x40)), u14::new(0x2000));
+        assert_eq!(u7_to_u14(u7::new(0x7F)), u14::new(0x3FFF));
+    }
+
+    #[test]
+    fn u7_to_u16_values() {
+        assert_eq!(u7_to_u16(u7::new(0x0)), 0x0);
+        assert_eq!(u7_to_u16(u7::new(0x1)), 0x200);
+        assert_eq!(u7_to_u16(u7::new(0x40)), 0x8000);
+        assert_eq!(u7_to_u16(u7::new(0x41)), 0x8208);
+        assert_eq!(u7_to_u16(u7::new(0x7F)), 0xFFFF);
+    }
+
+    #[test]
+    fn u7_to_u32_values() {
+        assert_eq!(u7_to_u32(u7::new(0x0)), 0x0);
+        assert_eq!(u7_to_u32(u7::new(0x40)), 0x8000_0000);
+        assert_eq!(u7_to_u32(u7::new(0x41)), 0x8208_2082);
+        assert_eq!(u7_to_u32(u7::new(0x7F)), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn u14_to_u16_min_center_max() {
+        assert_eq!(u14_to_u16(u14::new(0x0)), 0x0);
+        assert_eq!(u14_to_u16(u14::new(0x2000)), 0x8000);
+        assert_eq!(u14_to_u16(u14::new(0x3FFF)), 0xFFFF);
+    }
+
+    #[test]
+    fn u16_to_u14_min_center_max() {
+        assert_eq!(u16_to_u14(0x0), u14::new(0x0));
+        assert_eq!(u16_to_u14(0x8000), u14::new(0x2000));
+        assert_eq!(u16_to_u14(0xFFFF), u14::new(0x3FFF));
+    }
+
+    #[test]
+    fn u14_to_u32_values() {
+        assert_eq!(u14_to_u32(u14::new(0x0)), 0x0);
+        assert_eq!(u14_to_u32(u14::new(0x2000)), 0x8000_0000);
+        assert_eq!(u14_to_u32(u14::new(0x207F)), 0x81FC_0FE0);
+        assert_eq!(u14_to_u32(u14::new(0x3FFF)), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn u16_to_u32_min_center_max() {
+        assert_eq!(u16_to_u32(0x0), 0x0);
+        assert_eq!(u16_to_u32(0x8000), 0x8000_0000);
+        assert_eq!(u16_to_u32(0xFFFF), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn downscale() {
+        assert_eq!(u14_to_u7(u14::new(0x3FFF)), u7::new(0x7F));
+        assert_eq!(u16_to_u7(0x8208), u7::new(0x41));
+        assert_eq!(u32_to_u7(0x8000_0000), u7::new(0x40));
+        assert_eq!(u32_to_u14(0xFFFF_FFFF), u14::new(0x3FFF));
+        assert_eq!(u32_to_u16(0x8000_0000), 0x8000);
+    }
+
+    #[test]
+    fn round_trip() {
+        for v in 0..0x80 {
+            let v = u7::new(v);
+            assert_eq!(u16_to_u7(u7_to_u16(v)), v);
+            assert_eq!(u32_to_u7(u7_to_u32(v)), v);
+            assert_eq!(u14_to_u7(u7_to_u14(v)), v);
+        }
+        for v in 0..0x4000 {
+            let v = u14::new(v);
+            assert_eq!(u32_to_u14(u14_to_u32(v)), v);
+            assert_eq!(u16_to_u14(u14_to_u16(v)), v);
+        }
+        for v in (0..=0xFFFF).step_by(0xF) {
+            assert_eq!(u32_to_u16(u16_to_u32(v)), v);
+        }
+    }
+}
@@ -72,6 +72,20 @@ impl<'a> core::convert::TryFrom<&'a [u32]> for Packet {
     }
 }
 
+/// The number of 32 bit words in a packet of the given UMP message type.
+///
+/// Reserved message types are sized according to the specification
+/// so that they can be skipped over.
+pub(crate) fn size_from_ump_message_type(ump_message_type: u8) -> usize {
+    match ump_message_type {
+        0x0 | 0x1 | 0x2 | 0x6 | 0x7 => 1,
+        0x3 | 0x4 | 0x8 | 0x9 | 0xA => 2,
+        0xB | 0xC => 3,
+        0x5 | 0xD | 0xE | 0xF => 4,
+        _ => unreachable!(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +127,16 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn size_from_reserved_ump_message_types() {
+        assert_eq!(size_from_ump_message_type(0x6), 1);
+        assert_eq!(size_from_ump_message_type(0x7), 1);
+        assert_eq!(size_from_ump_message_type(0x8), 2);
+        assert_eq!(size_from_ump_message_type(0x9), 2);
+        assert_eq!(size_from_ump_message_type(0xA), 2);
+        assert_eq!(size_from_ump_message_type(0xB), 3);
+        assert_eq!(size_from_ump_message_type(0xC), 3);
+        assert_eq!(size_from_ump_message_type(0xE), 4);
+    }
 }
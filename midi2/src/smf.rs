@@ -0,0 +1,1124 @@
+//! Read and write Standard MIDI Files.
+//!
+//! Standard MIDI Files (SMF 1.0) of formats 0, 1 and 2 are supported.
+//!
+//! The events of each track are represented with the message types of this crate.
+//! Channel voice, system exclusive and escaped system common events are represented as
+//! [BytesMessage]s.
+//! The tempo, time signature, key signature, lyric and copyright meta events
+//! are represented with their [flex_data](crate::flex_data) equivalents
+//! ([SetTempo], [SetTimeSignature], [SetKeySignature], [Lyrics] and [CopyrightNotice])
+//! so that they can be passed straight into a UMP pipeline.
+//! All other events, including time signatures with a metronome click other than
+//! every 24 MIDI clocks, are kept in their raw form so that no information is lost.
+//!
+//! ```rust
+//! use midi2::{
+//!     prelude::*,
+//!     channel_voice1::{ChannelVoice1, NoteOn},
+//!     flex_data::{FlexData, SetTempo},
+//!     smf::{Event, Format, Smf, Timing, Track, TrackEvent},
+//! };
+//!
+//! let mut tempo = SetTempo::<Vec<u32>>::new();
+//! tempo.set_number_of_10_nanosecond_units_per_quarter_note(50_000_000);
+//!
+//! let mut note_on = NoteOn::<Vec<u8>>::new();
+//! note_on.set_note_number(u7::new(0x3C));
+//! note_on.set_velocity(u7::new(0x40));
+//!
+//! let smf = Smf {
+//!     format: Format::SingleTrack,
+//!     timing: Timing::TicksPerQuarterNote(480),
+//!     tracks: vec![Track {
+//!         events: vec![
+//!             TrackEvent { delta: 0, event: Event::FlexData(tempo.into()) },
+//!             TrackEvent {
+//!                 delta: 0,
+//!                 event: Event::Midi(BytesMessage::ChannelVoice1(note_on.into())),
+//!             },
+//!         ],
+//!         end_of_track_delta: 480,
+//!     }],
+//! };
+//!
+//! let mut file = Vec::new();
+//! smf.write(&mut file).unwrap();
+//!
+//! assert_eq!(Smf::try_from(&file[..]), Ok(smf));
+//! ```
+
+use crate::{
+    error::InvalidData,
+    flex_data::{
+        CopyrightNotice, FlexData, Lyrics, SetKeySignature, SetKeySignatureSharpsFlats, SetTempo,
+        SetTimeSignature, Tonic,
+    },
+    system_common::SystemCommon,
+    traits::RebufferInto,
+    ux::u3,
+    BytesMessage, Data,
+};
+
+const ERR_MISSING_HEADER_CHUNK: &str = "Missing MThd header chunk";
+const ERR_TRUNCATED_DATA: &str = "SMF data ends part way through a chunk";
+const ERR_TRUNCATED_EVENT: &str = "Track chunk ends part way through an event";
+const ERR_UNKNOWN_FORMAT: &str = "Unknown SMF format";
+const ERR_INVALID_VARIABLE_LENGTH_QUANTITY: &str = "Invalid variable length quantity";
+const ERR_MISSING_RUNNING_STATUS: &str = "Data byte without a running status";
+const ERR_UNEXPECTED_STATUS: &str = "Unexpected status byte in track";
+const ERR_MISSING_END_OF_TRACK: &str = "Missing End of Track meta event";
+const ERR_SINGLE_TRACK_FORMAT: &str = "Format 0 files contain exactly one track";
+const ERR_TICKS_PER_QUARTER_NOTE_OUT_OF_RANGE: &str = "Ticks per quarter note out of range";
+const ERR_VARIABLE_LENGTH_QUANTITY_OUT_OF_RANGE: &str =
+    "Value too large for a variable length quantity";
+const ERR_NO_SMF_EQUIVALENT: &str = "Message has no SMF equivalent";
+
+const MAX_VARIABLE_LENGTH_QUANTITY: u32 = 0x0FFF_FFFF;
+
+const META_COPYRIGHT_NOTICE: u8 = 0x02;
+const META_LYRIC: u8 = 0x05;
+const META_END_OF_TRACK: u8 = 0x2F;
+const META_SET_TEMPO: u8 = 0x51;
+const META_TIME_SIGNATURE: u8 = 0x58;
+const META_KEY_SIGNATURE: u8 = 0x59;
+
+/// A Standard MIDI File.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Smf {
+    pub format: Format,
+    pub timing: Timing,
+    pub tracks: std::vec::Vec<Track>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Format 0: a single multi-channel track.
+    SingleTrack,
+    /// Format 1: simultaneous tracks.
+    MultiTrack,
+    /// Format 2: independent single track patterns.
+    Sequential,
+}
+
+/// The meaning of the delta times of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timing {
+    /// Ticks per quarter note, in the range `1..=0x7FFF`.
+    TicksPerQuarterNote(u16),
+    /// Ticks per frame of SMPTE time code.
+    Smpte {
+        /// One of 24, 25, 29 (30 drop frame) or 30.
+        frames_per_second: u8,
+        ticks_per_frame: u8,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Track {
+    pub events: std::vec::Vec<TrackEvent>,
+    /// The delta time of the End of Track meta event.
+    pub end_of_track_delta: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackEvent {
+    /// Ticks since the previous event of the track.
+    pub delta: u32,
+    pub event: Event,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A channel voice message, a complete system exclusive message
+    /// or a system common / real time message in an escape event.
+    Midi(BytesMessage<std::vec::Vec<u8>>),
+    /// A meta event with a flex data equivalent.
+    FlexData(FlexData<std::vec::Vec<u32>>),
+    /// A meta event with no flex data equivalent.
+    Meta { kind: u8, data: std::vec::Vec<u8> },
+    /// A system exclusive event which is not a complete message,
+    /// e.g. the first part of a divided system exclusive message.
+    /// The data follows the leading `0xF0`.
+    Sysex(std::vec::Vec<u8>),
+    /// An escape event containing arbitrary bytes,
+    /// e.g. the continuation of a divided system exclusive message.
+    Escape(std::vec::Vec<u8>),
+}
+
+impl<'a> core::convert::TryFrom<&'a [u8]> for Smf {
+    type Error = InvalidData;
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let mut reader = Reader { data };
+
+        let (id, header) = reader
+            .chunk()
+            .map_err(|_| InvalidData(ERR_MISSING_HEADER_CHUNK))?;
+        if id != b"MThd" || header.len() < 6 {
+            return Err(InvalidData(ERR_MISSING_HEADER_CHUNK));
+        }
+        let format = match u16::from_be_bytes([header[0], header[1]]) {
+            0 => Format::SingleTrack,
+            1 => Format::MultiTrack,
+            2 => Format::Sequential,
+            _ => return Err(InvalidData(ERR_UNKNOWN_FORMAT)),
+        };
+        let timing = match header[4] {
+            v @ 0x00..=0x7F => Timing::TicksPerQuarterNote(u16::from_be_bytes([v, header[5]])),
+            v => Timing::Smpte {
+                frames_per_second: (v as i8).unsigned_abs(),
+                ticks_per_frame: header[5],
+            },
+        };
+
+        let mut tracks = std::vec::Vec::new();
+        while !reader.data.is_empty() {
+            let (id, data) = reader.chunk()?;
+            // alien chunks are ignored
+            if id == b"MTrk" {
+                tracks.push(read_track(data)?);
+            }
+        }
+
+        Ok(Smf {
+            format,
+            timing,
+            tracks,
+        })
+    }
+}
+
+impl Smf {
+    /// Read a whole file from the reader.
+    ///
+    /// Invalid file data is reported as an error of kind
+    /// [InvalidData](std::io::ErrorKind::InvalidData).
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Self> {
+        let mut data = std::vec::Vec::new();
+        reader.read_to_end(&mut data)?;
+        Smf::try_from(&data[..])
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the file into the writer.
+    ///
+    /// The tempo of [SetTempo] events is rounded to the nearest microsecond.
+    /// [SetTimeSignature] events are written with a metronome click every 24 MIDI clocks.
+    ///
+    /// # Fails
+    ///
+    /// With an error of kind [InvalidInput](std::io::ErrorKind::InvalidInput) when
+    /// - a format 0 file does not contain exactly one track,
+    /// - the timing, a delta time or an event length is out of range,
+    /// - a flex data event has no SMF equivalent.
+    pub fn write<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        if self.format == Format::SingleTrack && self.tracks.len() != 1 {
+            return Err(invalid_input(InvalidData(ERR_SINGLE_TRACK_FORMAT)));
+        }
+        let format: u16 = match self.format {
+            Format::SingleTrack => 0,
+            Format::MultiTrack => 1,
+            Format::Sequential => 2,
+        };
+        let division = match self.timing {
+            Timing::TicksPerQuarterNote(ticks) => {
+                if ticks == 0 || ticks > 0x7FFF {
+                    return Err(invalid_input(InvalidData(
+                        ERR_TICKS_PER_QUARTER_NOTE_OUT_OF_RANGE,
+                    )));
+                }
+                ticks.to_be_bytes()
+            }
+            Timing::Smpte {
+                frames_per_second,
+                ticks_per_frame,
+            } => [
+                (frames_per_second as i8).wrapping_neg() as u8,
+                ticks_per_frame,
+            ],
+        };
+
+        writer.write_all(b"MThd")?;
+        writer.write_all(&6_u32.to_be_bytes())?;
+        writer.write_all(&format.to_be_bytes())?;
+        writer.write_all(&(self.tracks.len() as u16).to_be_bytes())?;
+        writer.write_all(&division)?;
+
+        for track in self.tracks.iter() {
+            let data = write_track(track).map_err(invalid_input)?;
+            writer.write_all(b"MTrk")?;
+            writer.write_all(&(data.len() as u32).to_be_bytes())?;
+            writer.write_all(&data)?;
+        }
+
+        writer.flush()
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, size: usize, error: &'static str) -> Result<&'a [u8], InvalidData> {
+        if self.data.len() < size {
+            return Err(InvalidData(error));
+        }
+        let (taken, rest) = self.data.split_at(size);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, InvalidData> {
+        Ok(self.take(1, ERR_TRUNCATED_EVENT)?[0])
+    }
+
+    fn chunk(&mut self) -> Result<(&'a [u8], &'a [u8]), InvalidData> {
+        let header = self.take(8, ERR_TRUNCATED_DATA)?;
+        let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        Ok((&header[..4], self.take(size as usize, ERR_TRUNCATED_DATA)?))
+    }
+
+    fn variable_length_quantity(&mut self) -> Result<u32, InvalidData> {
+        let mut value = 0_u32;
+        for _ in 0..4 {
+            let byte = self.byte()?;
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(InvalidData(ERR_INVALID_VARIABLE_LENGTH_QUANTITY))
+    }
+
+    fn variable_length_data(&mut self) -> Result<&'a [u8], InvalidData> {
+        let size = self.variable_length_quantity()?;
+        self.take(size as usize, ERR_TRUNCATED_EVENT)
+    }
+}
+
+fn read_track(data: &[u8]) -> Result<Track, InvalidData> {
+    let mut reader = Reader { data };
+    let mut events = std::vec::Vec::new();
+    let mut running_status = None;
+
+    loop {
+        if reader.data.is_empty() {
+            return Err(InvalidData(ERR_MISSING_END_OF_TRACK));
+        }
+        let delta = reader.variable_length_quantity()?;
+        let status = match reader.data.first() {
+            Some(0x00..=0x7F) => running_status.ok_or(InvalidData(ERR_MISSING_RUNNING_STATUS))?,
+            _ => reader.byte()?,
+        };
+        let event = match status {
+            0xFF => {
+                running_status = None;
+                let kind = reader.byte()?;
+                let data = reader.variable_length_data()?;
+                if kind == META_END_OF_TRACK {
+                    return Ok(Track {
+                        events,
+                        end_of_track_delta: delta,
+                    });
+                }
+                meta_event(kind, data)
+            }
+            0xF0 => {
+                running_status = None;
+                sysex_event(reader.variable_length_data()?)
+            }
+            0xF7 => {
+                running_status = None;
+                escape_event(reader.variable_length_data()?)
+            }
+            0x80..=0xEF => {
+                running_status = Some(status);
+                channel_event(status, &mut reader)?
+            }
+            _ => return Err(InvalidData(ERR_UNEXPECTED_STATUS)),
+        };
+        events.push(TrackEvent { delta, event });
+    }
+}
+
+fn channel_event(status: u8, reader: &mut Reader) -> Result<Event, InvalidData> {
+    let size = match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    };
+    let data = reader.take(size, ERR_TRUNCATED_EVENT)?;
+    if data.iter().any(|b| b & 0x80 != 0) {
+        return Err(InvalidData(ERR_UNEXPECTED_STATUS));
+    }
+    let mut buffer = [status, 0x0, 0x0];
+    buffer[1..=size].copy_from_slice(data);
+    let message = BytesMessage::try_from(&buffer[..=size])?;
+    Ok(Event::Midi(message.rebuffer_into()))
+}
+
+fn sysex_event(data: &[u8]) -> Event {
+    let mut bytes = std::vec![0xF0];
+    bytes.extend_from_slice(data);
+    match crate::sysex7::Sysex7::try_from(&bytes[..]) {
+        Ok(message) if data.last() == Some(&0xF7) => {
+            Event::Midi(BytesMessage::Sysex7(message.rebuffer_into()))
+        }
+        _ => Event::Sysex(data.to_vec()),
+    }
+}
+
+fn escape_event(data: &[u8]) -> Event {
+    match data.first() {
+        Some(0xF1..=0xF6 | 0xF8..=0xFF) => match SystemCommon::try_from(data) {
+            Ok(message) if message.data().len() == data.len() => {
+                Event::Midi(BytesMessage::SystemCommon(message.rebuffer_into()))
+            }
+            _ => Event::Escape(data.to_vec()),
+        },
+        _ => Event::Escape(data.to_vec()),
+    }
+}
+
+// the tonics of the major and minor keys
+// indexed by the number of sharps (positive) or flats (negative) plus seven
+const MAJOR_TONICS: [Tonic; 15] = [
+    Tonic::C,
+    Tonic::G,
+    Tonic::D,
+    Tonic::A,
+    Tonic::E,
+    Tonic::B,
+    Tonic::F,
+    Tonic::C,
+    Tonic::G,
+    Tonic::D,
+    Tonic::A,
+    Tonic::E,
+    Tonic::B,
+    Tonic::F,
+    Tonic::C,
+];
+const MINOR_TONICS: [Tonic; 15] = [
+    Tonic::A,
+    Tonic::E,
+    Tonic::B,
+    Tonic::F,
+    Tonic::C,
+    Tonic::G,
+    Tonic::D,
+    Tonic::A,
+    Tonic::E,
+    Tonic::B,
+    Tonic::F,
+    Tonic::C,
+    Tonic::G,
+    Tonic::D,
+    Tonic::A,
+];
+
+// time signatures with other metronome clicks
+// are kept as raw meta events
+const MIDI_CLOCKS_PER_METRONOME_CLICK: u8 = 24;
+
+fn meta_event(kind: u8, data: &[u8]) -> Event {
+    match (kind, data) {
+        (META_SET_TEMPO, &[a, b, c]) => {
+            let mut message = SetTempo::<std::vec::Vec<u32>>::new();
+            message.set_number_of_10_nanosecond_units_per_quarter_note(
+                u32::from_be_bytes([0x0, a, b, c]) * 100,
+            );
+            Event::FlexData(message.into())
+        }
+        (
+            META_TIME_SIGNATURE,
+            &[numerator, denominator, MIDI_CLOCKS_PER_METRONOME_CLICK, number_of_32nd_notes],
+        ) => {
+            let mut message = SetTimeSignature::<std::vec::Vec<u32>>::new();
+            message.set_numerator(numerator);
+            message.set_denominator(denominator);
+            message.set_number_of_32nd_notes(number_of_32nd_notes);
+            Event::FlexData(message.into())
+        }
+        (META_KEY_SIGNATURE, &[sharps_flats, mode @ (0 | 1)])
+            if (-7..=7).contains(&(sharps_flats as i8)) =>
+        {
+            let sharps_flats = sharps_flats as i8;
+            let index = (sharps_flats + 7) as usize;
+            let mut message = SetKeySignature::<std::vec::Vec<u32>>::new();
+            message.set_sharps_flats(if sharps_flats < 0 {
+                SetKeySignatureSharpsFlats::Flats(u3::new(sharps_flats.unsigned_abs()))
+            } else {
+                SetKeySignatureSharpsFlats::Sharps(u3::new(sharps_flats as u8))
+            });
+            message.set_tonic(if mode == 0 {
+                MAJOR_TONICS[index]
+            } else {
+                MINOR_TONICS[index]
+            });
+            Event::FlexData(message.into())
+        }
+        (META_LYRIC | META_COPYRIGHT_NOTICE, _) => match core::str::from_utf8(data) {
+            // the flex data text can't represent null characters
+            Ok(text) if !text.contains('\0') => {
+                if kind == META_LYRIC {
+                    let mut message = Lyrics::<std::vec::Vec<u32>>::new();
+                    message.set_text(text);
+                    Event::FlexData(message.into())
+                } else {
+                    let mut message = CopyrightNotice::<std::vec::Vec<u32>>::new();
+                    message.set_text(text);
+                    Event::FlexData(message.into())
+                }
+            }
+            _ => Event::Meta {
+                kind,
+                data: data.to_vec(),
+            },
+        },
+        _ => Event::Meta {
+            kind,
+            data: data.to_vec(),
+        },
+    }
+}
+
+fn write_track(track: &Track) -> Result<std::vec::Vec<u8>, InvalidData> {
+    let mut data = std::vec::Vec::new();
+    for TrackEvent { delta, event } in track.events.iter() {
+        write_variable_length_quantity(&mut data, *delta)?;
+        write_event(&mut data, event)?;
+    }
+    write_variable_length_quantity(&mut data, track.end_of_track_delta)?;
+    write_meta(&mut data, META_END_OF_TRACK, &[])?;
+    Ok(data)
+}
+
+fn write_event(data: &mut std::vec::Vec<u8>, event: &Event) -> Result<(), InvalidData> {
+    match event {
+        Event::Midi(BytesMessage::ChannelVoice1(message)) => {
+            data.extend_from_slice(message.data());
+            Ok(())
+        }
+        Event::Midi(BytesMessage::Sysex7(message)) => {
+            write_variable_length_data(data, 0xF0, &message.data()[1..])
+        }
+        Event::Midi(BytesMessage::SystemCommon(message)) => {
+            write_variable_length_data(data, 0xF7, message.data())
+        }
+        Event::FlexData(message) => write_flex_data(data, message),
+        Event::Meta {
+            kind,
+            data: payload,
+        } => write_meta(data, *kind, payload),
+        Event::Sysex(payload) => write_variable_length_data(data, 0xF0, payload),
+        Event::Escape(payload) => write_variable_length_data(data, 0xF7, payload),
+    }
+}
+
+/// Whether the flex data message can be written as a meta event.
+pub(crate) fn has_smf_equivalent(message: &FlexData<std::vec::Vec<u32>>) -> bool {
+    write_flex_data(&mut std::vec::Vec::new(), message).is_ok()
+}
+
+fn write_flex_data(
+    data: &mut std::vec::Vec<u8>,
+    message: &FlexData<std::vec::Vec<u32>>,
+) -> Result<(), InvalidData> {
+    match message {
+        FlexData::SetTempo(message) => {
+            let microseconds =
+                ((u64::from(message.number_of_10_nanosecond_units_per_quarter_note()) + 50) / 100)
+                    .min(0xFF_FFFF) as u32;
+            write_meta(data, META_SET_TEMPO, &microseconds.to_be_bytes()[1..])
+        }
+        FlexData::SetTimeSignature(message) => write_meta(
+            data,
+            META_TIME_SIGNATURE,
+            &[
+                message.numerator(),
+                message.denominator(),
+                MIDI_CLOCKS_PER_METRONOME_CLICK,
+                message.number_of_32nd_notes(),
+            ],
+        ),
+        FlexData::SetKeySignature(message) => {
+            let sharps_flats = match message.sharps_flats() {
+                SetKeySignatureSharpsFlats::Sharps(v) => u8::from(v) as i8,
+                SetKeySignatureSharpsFlats::Flats(v) => -(u8::from(v) as i8),
+                SetKeySignatureSharpsFlats::NonStandard => {
+                    return Err(InvalidData(ERR_NO_SMF_EQUIVALENT))
+                }
+            };
+            let index = (sharps_flats + 7) as usize;
+            let mode = if MAJOR_TONICS[index] == message.tonic() {
+                0
+            } else if MINOR_TONICS[index] == message.tonic() {
+                1
+            } else {
+                return Err(InvalidData(ERR_NO_SMF_EQUIVALENT));
+            };
+            write_meta(data, META_KEY_SIGNATURE, &[sharps_flats as u8, mode])
+        }
+        FlexData::Lyrics(message) => write_meta(
+            data,
+            META_LYRIC,
+            &message.text_bytes().collect::<std::vec::Vec<u8>>(),
+        ),
+        FlexData::CopyrightNotice(message) => write_meta(
+            data,
+            META_COPYRIGHT_NOTICE,
+            &message.text_bytes().collect::<std::vec::Vec<u8>>(),
+        ),
+        _ => Err(InvalidData(ERR_NO_SMF_EQUIVALENT)),
+    }
+}
+
+fn write_meta(data: &mut std::vec::Vec<u8>, kind: u8, payload: &[u8]) -> Result<(), InvalidData> {
+    data.push(0xFF);
+    write_variable_length_data(data, kind, payload)
+}
+
+fn write_variable_length_data(
+    data: &mut std::vec::Vec<u8>,
+    prefix: u8,
+    payload: &[u8],
+) -> Result<(), InvalidData> {
+    data.push(prefix);
+    write_variable_length_quantity(
+        data,
+        u32::try_from(payload.len())
+            .map_err(|_| InvalidData(ERR_VARIABLE_LENGTH_QUANTITY_OUT_OF_RANGE))?,
+    )?;
+    data.extend_from_slice(payload);
+    Ok(())
+}
+
+fn write_variable_length_quantity(
+    data: &mut std::vec::Vec<u8>,
+    value: u32,
+) -> Result<(), InvalidData> {
+    if value > MAX_VARIABLE_LENGTH_QUANTITY {
+        return Err(InvalidData(ERR_VARIABLE_LENGTH_QUANTITY_OUT_OF_RANGE));
+    }
+    let mut started = false;
+    for shift in [21, 14, 7] {
+        let septet = ((value >> shift) & 0x7F) as u8;
+        if started || septet != 0 {
+            started = true;
+            data.push(septet | 0x80);
+        }
+    }
+    data.push((value & 0x7F) as u8);
+    Ok(())
+}
+
+fn invalid_input(error: InvalidData) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn file(format: u16, division: [u8; 2], tracks: &[&[u8]]) -> std::vec::Vec<u8> {
+        let mut data = b"MThd".to_vec();
+        data.extend_from_slice(&6_u32.to_be_bytes());
+        data.extend_from_slice(&format.to_be_bytes());
+        data.extend_from_slice(&(tracks.len() as u16).to_be_bytes());
+        data.extend_from_slice(&division);
+        for track in tracks {
+            data.extend_from_slice(b"MTrk");
+            data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+            data.extend_from_slice(track);
+        }
+        data
+    }
+
+    fn midi(bytes: &[u8]) -> Event {
+        Event::Midi(BytesMessage::try_from(bytes).unwrap().rebuffer_into())
+    }
+
+    fn event(delta: u32, event: Event) -> TrackEvent {
+        TrackEvent { delta, event }
+    }
+
+    fn single_track(data: &[u8]) -> Result<Smf, InvalidData> {
+        Smf::try_from(&file(0, [0x0, 0x60], &[data])[..])
+    }
+
+    fn events(data: &[u8]) -> std::vec::Vec<TrackEvent> {
+        single_track(data).unwrap().tracks.remove(0).events
+    }
+
+    fn write(smf: &Smf) -> std::io::Result<std::vec::Vec<u8>> {
+        let mut data = std::vec::Vec::new();
+        smf.write(&mut data)?;
+        Ok(data)
+    }
+
+    fn round_trip(data: &[u8]) {
+        let file = file(1, [0x0, 0x60], &[data]);
+        assert_eq!(write(&Smf::try_from(&file[..]).unwrap()).unwrap(), file);
+    }
+
+    #[test]
+    fn read_single_track() {
+        assert_eq!(
+            single_track(&[
+                0x00, 0x90, 0x3C, 0x40, // note on
+                0x60, 0x80, 0x3C, 0x00, // note off
+                0x00, 0xFF, 0x2F, 0x00, // end of track
+            ]),
+            Ok(Smf {
+                format: Format::SingleTrack,
+                timing: Timing::TicksPerQuarterNote(96),
+                tracks: std::vec![Track {
+                    events: std::vec![
+                        event(0, midi(&[0x90, 0x3C, 0x40])),
+                        event(0x60, midi(&[0x80, 0x3C, 0x00])),
+                    ],
+                    end_of_track_delta: 0,
+                }],
+            }),
+        );
+    }
+
+    #[test]
+    fn read_multi_track() {
+        let smf = Smf::try_from(
+            &file(
+                1,
+                [0x1, 0xE0],
+                &[
+                    &[0x00, 0xFF, 0x2F, 0x00],
+                    &[0x00, 0xC1, 0x05, 0x10, 0xFF, 0x2F, 0x00],
+                ],
+            )[..],
+        )
+        .unwrap();
+        assert_eq!(smf.format, Format::MultiTrack);
+        assert_eq!(smf.timing, Timing::TicksPerQuarterNote(480));
+        assert_eq!(
+            smf.tracks,
+            std::vec![
+                Track::default(),
+                Track {
+                    events: std::vec![event(0, midi(&[0xC1, 0x05]))],
+                    end_of_track_delta: 0x10,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn read_sequential() {
+        let smf = Smf::try_from(&file(2, [0x0, 0x60], &[&[0x00, 0xFF, 0x2F, 0x00]])[..]).unwrap();
+        assert_eq!(smf.format, Format::Sequential);
+    }
+
+    #[test]
+    fn read_smpte_timing() {
+        let smf = Smf::try_from(&file(0, [0xE7, 0x28], &[&[0x00, 0xFF, 0x2F, 0x00]])[..]).unwrap();
+        assert_eq!(
+            smf.timing,
+            Timing::Smpte {
+                frames_per_second: 25,
+                ticks_per_frame: 40,
+            },
+        );
+    }
+
+    #[test]
+    fn read_ignores_alien_chunks() {
+        let mut data = file(0, [0x0, 0x60], &[&[0x00, 0xFF, 0x2F, 0x00]]);
+        data.extend_from_slice(b"XFIH");
+        data.extend_from_slice(&2_u32.to_be_bytes());
+        data.extend_from_slice(&[0x1, 0x2]);
+        assert_eq!(
+            Smf::try_from(&data[..]).unwrap().tracks,
+            std::vec![Track::default()]
+        );
+    }
+
+    #[test]
+    fn read_running_status() {
+        assert_eq!(
+            events(&[
+                0x00, 0x90, 0x3C, 0x40, // note on
+                0x00, 0x3E, 0x40, // running status
+                0x00, 0xD0, 0x20, // channel pressure
+                0x00, 0x21, // running status
+                0x00, 0xFF, 0x2F, 0x00, // end of track
+            ]),
+            std::vec![
+                event(0, midi(&[0x90, 0x3C, 0x40])),
+                event(0, midi(&[0x90, 0x3E, 0x40])),
+                event(0, midi(&[0xD0, 0x20])),
+                event(0, midi(&[0xD0, 0x21])),
+            ],
+        );
+    }
+
+    #[test]
+    fn read_meta_event_cancels_running_status() {
+        assert_eq!(
+            single_track(&[
+                0x00, 0x90, 0x3C, 0x40, // note on
+                0x00, 0xFF, 0x01, 0x00, // empty text
+                0x00, 0x3E, 0x40, // running status
+                0x00, 0xFF, 0x2F, 0x00, // end of track
+            ]),
+            Err(InvalidData(ERR_MISSING_RUNNING_STATUS)),
+        );
+    }
+
+    #[test]
+    fn read_missing_running_status() {
+        assert_eq!(
+            single_track(&[0x00, 0x3C, 0x40, 0x00, 0xFF, 0x2F, 0x00]),
+            Err(InvalidData(ERR_MISSING_RUNNING_STATUS)),
+        );
+    }
+
+    #[test]
+    fn read_variable_length_delta() {
+        assert_eq!(
+            single_track(&[
+                0x81, 0x00, // delta 0x80
+                0xF7, 0x01, 0xFA, // escaped start
+                0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0x2F, 0x00, // end of track
+            ])
+            .unwrap()
+            .tracks,
+            std::vec![Track {
+                events: std::vec![event(0x80, midi(&[0xFA]))],
+                end_of_track_delta: 0x0FFF_FFFF,
+            }],
+        );
+    }
+
+    #[test]
+    fn read_invalid_variable_length_quantity() {
+        assert_eq!(
+            single_track(&[0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0xFF, 0x2F, 0x00]),
+            Err(InvalidData(ERR_INVALID_VARIABLE_LENGTH_QUANTITY)),
+        );
+    }
+
+    #[test]
+    fn read_tempo() {
+        let mut expected = SetTempo::<std::vec::Vec<u32>>::new();
+        expected.set_number_of_10_nanosecond_units_per_quarter_note(50_000_000);
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(0, Event::FlexData(expected.into()))],
+        );
+    }
+
+    #[test]
+    fn read_time_signature() {
+        let mut expected = SetTimeSignature::<std::vec::Vec<u32>>::new();
+        expected.set_numerator(6);
+        expected.set_denominator(3);
+        expected.set_number_of_32nd_notes(8);
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x58, 0x04, 0x06, 0x03, 0x18, 0x08, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(0, Event::FlexData(expected.into()))],
+        );
+    }
+
+    #[test]
+    fn read_time_signature_with_other_metronome_click() {
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x58, 0x04, 0x06, 0x03, 0x24, 0x08, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(
+                0,
+                Event::Meta {
+                    kind: 0x58,
+                    data: std::vec![0x06, 0x03, 0x24, 0x08],
+                }
+            )],
+        );
+    }
+
+    #[test]
+    fn read_key_signature() {
+        let mut expected = SetKeySignature::<std::vec::Vec<u32>>::new();
+        expected.set_sharps_flats(SetKeySignatureSharpsFlats::Flats(u3::new(3)));
+        expected.set_tonic(Tonic::C);
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x59, 0x02, 0xFD, 0x01, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(0, Event::FlexData(expected.into()))],
+        );
+    }
+
+    #[test]
+    fn read_invalid_key_signature() {
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x59, 0x02, 0x08, 0x00, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(
+                0,
+                Event::Meta {
+                    kind: 0x59,
+                    data: std::vec![0x08, 0x00],
+                }
+            )],
+        );
+    }
+
+    #[test]
+    fn read_lyric_and_copyright() {
+        let mut lyrics = Lyrics::<std::vec::Vec<u32>>::new();
+        lyrics.set_text("la");
+        let mut copyright = CopyrightNotice::<std::vec::Vec<u32>>::new();
+        copyright.set_text("(c)");
+        assert_eq!(
+            events(&[
+                0x00, 0xFF, 0x05, 0x02, b'l', b'a', // lyric
+                0x00, 0xFF, 0x02, 0x03, b'(', b'c', b')', // copyright
+                0x00, 0xFF, 0x2F, 0x00, // end of track
+            ]),
+            std::vec![
+                event(0, Event::FlexData(lyrics.into())),
+                event(0, Event::FlexData(copyright.into())),
+            ],
+        );
+    }
+
+    #[test]
+    fn read_lyric_which_is_not_utf8() {
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x05, 0x01, 0xE9, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(
+                0,
+                Event::Meta {
+                    kind: 0x05,
+                    data: std::vec![0xE9],
+                }
+            )],
+        );
+    }
+
+    #[test]
+    fn read_unmapped_meta_event() {
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x03, 0x01, b'A', 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(
+                0,
+                Event::Meta {
+                    kind: 0x03,
+                    data: std::vec![b'A'],
+                }
+            )],
+        );
+    }
+
+    #[test]
+    fn read_complete_sysex() {
+        assert_eq!(
+            events(&[0x00, 0xF0, 0x03, 0x7E, 0x01, 0xF7, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(0, midi(&[0xF0, 0x7E, 0x01, 0xF7]))],
+        );
+    }
+
+    #[test]
+    fn read_divided_sysex() {
+        assert_eq!(
+            events(&[
+                0x00, 0xF0, 0x02, 0x7E, 0x01, // first part
+                0x10, 0xF7, 0x02, 0x02, 0xF7, // continuation
+                0x00, 0xFF, 0x2F, 0x00, // end of track
+            ]),
+            std::vec![
+                event(0, Event::Sysex(std::vec![0x7E, 0x01])),
+                event(0x10, Event::Escape(std::vec![0x02, 0xF7])),
+            ],
+        );
+    }
+
+    #[test]
+    fn read_escaped_system_common() {
+        assert_eq!(
+            events(&[0x00, 0xF7, 0x02, 0xF3, 0x05, 0x00, 0xFF, 0x2F, 0x00]),
+            std::vec![event(0, midi(&[0xF3, 0x05]))],
+        );
+    }
+
+    #[test]
+    fn read_missing_header() {
+        assert_eq!(
+            Smf::try_from(&b"MTrk\x00\x00\x00\x00"[..]),
+            Err(InvalidData(ERR_MISSING_HEADER_CHUNK)),
+        );
+    }
+
+    #[test]
+    fn read_unknown_format() {
+        assert_eq!(
+            Smf::try_from(&file(3, [0x0, 0x60], &[])[..]),
+            Err(InvalidData(ERR_UNKNOWN_FORMAT)),
+        );
+    }
+
+    #[test]
+    fn read_truncated_chunk() {
+        let data = file(0, [0x0, 0x60], &[&[0x00, 0xFF, 0x2F, 0x00]]);
+        assert_eq!(
+            Smf::try_from(&data[..data.len() - 1]),
+            Err(InvalidData(ERR_TRUNCATED_DATA)),
+        );
+    }
+
+    #[test]
+    fn read_truncated_event() {
+        assert_eq!(
+            single_track(&[0x00, 0x90, 0x3C]),
+            Err(InvalidData(ERR_TRUNCATED_EVENT)),
+        );
+    }
+
+    #[test]
+    fn read_missing_end_of_track() {
+        assert_eq!(
+            single_track(&[0x00, 0x90, 0x3C, 0x40]),
+            Err(InvalidData(ERR_MISSING_END_OF_TRACK)),
+        );
+    }
+
+    #[test]
+    fn read_unexpected_status() {
+        assert_eq!(
+            single_track(&[0x00, 0x90, 0x3C, 0x90, 0x00, 0xFF, 0x2F, 0x00]),
+            Err(InvalidData(ERR_UNEXPECTED_STATUS)),
+        );
+    }
+
+    #[test]
+    fn round_trip_channel_events() {
+        round_trip(&[
+            0x00, 0x90, 0x3C, 0x40, // note on
+            0x83, 0x60, 0x80, 0x3C, 0x00, // note off
+            0x00, 0xB2, 0x07, 0x64, // control change
+            0x00, 0xE2, 0x00, 0x40, // pitch bend
+            0x00, 0xFF, 0x2F, 0x00, // end of track
+        ]);
+    }
+
+    #[test]
+    fn round_trip_meta_events() {
+        round_trip(&[
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // tempo
+            0x00, 0xFF, 0x58, 0x04, 0x04, 0x02, 0x18, 0x08, // time signature
+            0x00, 0xFF, 0x58, 0x04, 0x06, 0x03, 0x24, 0x08, // time signature
+            0x00, 0xFF, 0x59, 0x02, 0x02, 0x00, // key signature
+            0x00, 0xFF, 0x59, 0x02, 0xFE, 0x01, // key signature
+            0x00, 0xFF, 0x05, 0x02, b'l', b'a', // lyric
+            0x00, 0xFF, 0x02, 0x01, b'c', // copyright
+            0x00, 0xFF, 0x03, 0x01, b'A', // track name
+            0x00, 0xFF, 0x2F, 0x00, // end of track
+        ]);
+    }
+
+    #[test]
+    fn round_trip_sysex_events() {
+        round_trip(&[
+            0x00, 0xF0, 0x03, 0x7E, 0x01, 0xF7, // complete
+            0x00, 0xF0, 0x02, 0x7E, 0x01, // first part
+            0x10, 0xF7, 0x02, 0x02, 0xF7, // continuation
+            0x00, 0xF7, 0x02, 0xF3, 0x05, // escaped song select
+            0x00, 0xFF, 0x2F, 0x00, // end of track
+        ]);
+    }
+
+    #[test]
+    fn write_smpte_timing() {
+        let data = write(&Smf {
+            format: Format::SingleTrack,
+            timing: Timing::Smpte {
+                frames_per_second: 30,
+                ticks_per_frame: 80,
+            },
+            tracks: std::vec![Track::default()],
+        })
+        .unwrap();
+        assert_eq!(data, file(0, [0xE2, 0x50], &[&[0x00, 0xFF, 0x2F, 0x00]]));
+    }
+
+    #[test]
+    fn write_rounds_tempo() {
+        let mut tempo = SetTempo::<std::vec::Vec<u32>>::new();
+        tempo.set_number_of_10_nanosecond_units_per_quarter_note(50_000_050);
+        let data = write(&Smf {
+            format: Format::SingleTrack,
+            timing: Timing::TicksPerQuarterNote(96),
+            tracks: std::vec![Track {
+                events: std::vec![event(0, Event::FlexData(tempo.into()))],
+                end_of_track_delta: 0,
+            }],
+        })
+        .unwrap();
+        assert_eq!(
+            data,
+            file(
+                0,
+                [0x0, 0x60],
+                &[&[0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x21, 0x00, 0xFF, 0x2F, 0x00]],
+            ),
+        );
+    }
+
+    #[test]
+    fn write_single_track_format_with_two_tracks() {
+        let error = write(&Smf {
+            format: Format::SingleTrack,
+            timing: Timing::TicksPerQuarterNote(96),
+            tracks: std::vec![Track::default(), Track::default()],
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn write_ticks_per_quarter_note_out_of_range() {
+        let error = write(&Smf {
+            format: Format::SingleTrack,
+            timing: Timing::TicksPerQuarterNote(0x8000),
+            tracks: std::vec![Track::default()],
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn write_delta_out_of_range() {
+        let error = write(&Smf {
+            format: Format::SingleTrack,
+            timing: Timing::TicksPerQuarterNote(96),
+            tracks: std::vec![Track {
+                events: std::vec![],
+                end_of_track_delta: 0x1000_0000,
+            }],
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn write_flex_data_without_smf_equivalent() {
+        let mut key_signature = SetKeySignature::<std::vec::Vec<u32>>::new();
+        key_signature.set_sharps_flats(SetKeySignatureSharpsFlats::Sharps(u3::new(1)));
+        key_signature.set_tonic(Tonic::C);
+        let error = write(&Smf {
+            format: Format::SingleTrack,
+            timing: Timing::TicksPerQuarterNote(96),
+            tracks: std::vec![Track {
+                events: std::vec![event(0, Event::FlexData(key_signature.into()))],
+                end_of_track_delta: 0,
+            }],
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
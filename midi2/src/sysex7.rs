@@ -6,8 +6,12 @@ use crate::{
     ux::{self, u7},
 };
 
+mod assembler;
+mod fragmenter;
 mod packet;
 
+pub use assembler::Sysex7Assembler;
+pub use fragmenter::{FragmentLimit, Sysex7Fragmenter};
 pub use packet::Packet;
 
 pub(crate) const UMP_MESSAGE_TYPE: u8 = 0x3;
@@ -0,0 +1,416 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Ump},
+    detail::helpers::ERR_SYSEX_EXPECTED_BEGIN,
+    error::{BufferOverflow, Error, InvalidData},
+    sysex7::{packet::Status, Packet, Sysex7},
+    Grouped,
+};
+
+/// Reassembles [Sysex7] messages from individual [Packet]s.
+///
+/// On a real UMP stream the packets of a system exclusive message
+/// may be interleaved with packets of other messages, including
+/// system exclusive packets on other groups.
+/// The assembler keeps independent state for each of the 16 groups
+/// and produces a message once its final packet has been received.
+///
+/// The assembled messages are written into a backing buffer per group.
+/// With a resizable buffer (e.g. `Vec<u32>`) use [push](Sysex7Assembler::push).
+/// With a fixed size buffer (e.g. `[u32; N]`) use [try_push](Sysex7Assembler::try_push),
+/// which reports messages which are too large for the buffer.
+///
+/// ```rust
+/// use midi2::{prelude::*, sysex7::{Packet, Sysex7Assembler}};
+///
+/// let mut assembler = Sysex7Assembler::<[u32; 16]>::new();
+///
+/// let packets = [
+///     [0x3116_0001, 0x0203_0405],
+///     [0x3216_1011, 0x1213_1415],
+///     [0x3132_0607, 0x0000_0000],
+///     [0x3232_1617, 0x0000_0000],
+/// ];
+///
+/// let mut payloads = Vec::new();
+/// for packet in packets {
+///     let packet = Packet::try_from(&packet[..]).expect("Valid packet");
+///     if let Some(message) = assembler.try_push(&packet).expect("Valid stream") {
+///         payloads.push((message.group(), message.payload().map(u8::from).collect::<Vec<u8>>()));
+///     }
+/// }
+///
+/// assert_eq!(
+///     payloads,
+///     [
+///         (u4::new(0x1), std::vec![0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07]),
+///         (u4::new(0x2), std::vec![0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17]),
+///     ],
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sysex7Assembler<B: Ump + BufferMut> {
+    groups: [GroupState<B>; 16],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GroupState<B> {
+    buffer: B,
+    size: usize,
+    status: AssemblyStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssemblyStatus {
+    Idle,
+    Assembling,
+    // the message overflowed the buffer and the remaining
+    // packets are discarded
+    Discarding,
+}
+
+impl<B: Ump + BufferMut + BufferDefault> core::default::Default for Sysex7Assembler<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault> Sysex7Assembler<B> {
+    pub fn new() -> Self {
+        Sysex7Assembler {
+            groups: core::array::from_fn(|_| GroupState {
+                buffer: B::default(),
+                size: 0,
+                status: AssemblyStatus::Idle,
+            }),
+        }
+    }
+}
+
+impl<B: Ump + BufferMut> Sysex7Assembler<B> {
+    /// Drop any partially assembled messages on all groups.
+    pub fn reset(&mut self) {
+        for state in self.groups.iter_mut() {
+            state.size = 0;
+            state.status = AssemblyStatus::Idle;
+        }
+    }
+
+    /// Feed the next packet of the stream into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// A start or complete packet received while a message is being assembled
+    /// on the same group interrupts it and the partial message is dropped.
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when a continue or end packet is received
+    /// without a preceding start packet on its group.
+    pub fn push(&mut self, packet: &Packet) -> Result<Option<Sysex7<&[u32]>>, Error>
+    where
+        B: BufferResize,
+    {
+        self.push_impl(packet, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+
+    /// Feed the next packet of the stream into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// A start or complete packet received while a message is being assembled
+    /// on the same group interrupts it and the partial message is dropped.
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when a continue or end packet is received
+    /// without a preceding start packet on its group.
+    ///
+    /// With [BufferOverflow] when the message does not fit in the buffer.
+    /// The error is reported once and the remaining packets of the
+    /// message are discarded.
+    pub fn try_push(&mut self, packet: &Packet) -> Result<Option<Sysex7<&[u32]>>, Error>
+    where
+        B: BufferTryResize,
+    {
+        self.push_impl(packet, |buffer, size| buffer.try_resize(size))
+    }
+
+    fn push_impl<Resize>(
+        &mut self,
+        packet: &Packet,
+        try_resize: Resize,
+    ) -> Result<Option<Sysex7<&[u32]>>, Error>
+    where
+        Resize: Fn(&mut B, usize) -> Result<(), BufferOverflow>,
+    {
+        let state = &mut self.groups[usize::from(u8::from(packet.group()))];
+        let status = packet.status();
+
+        match (status, state.status) {
+            (Status::Complete | Status::Start, _) => {
+                state.size = 0;
+                state.status = AssemblyStatus::Assembling;
+            }
+            (Status::Continue | Status::End, AssemblyStatus::Assembling) => {}
+            (Status::Continue, AssemblyStatus::Discarding) => return Ok(None),
+            (Status::End, AssemblyStatus::Discarding) => {
+                state.status = AssemblyStatus::Idle;
+                return Ok(None);
+            }
+            (Status::Continue | Status::End, AssemblyStatus::Idle) => {
+                return Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN).into());
+            }
+        }
+
+        let size = state.size + packet.len();
+        if let Err(e) = try_resize(&mut state.buffer, size) {
+            state.size = 0;
+            state.status = match status {
+                Status::Complete | Status::End => AssemblyStatus::Idle,
+                Status::Start | Status::Continue => AssemblyStatus::Discarding,
+            };
+            return Err(e.into());
+        }
+        state.buffer.buffer_mut()[state.size..size].copy_from_slice(packet);
+        state.size = size;
+
+        match status {
+            Status::Complete | Status::End => {
+                state.status = AssemblyStatus::Idle;
+                Ok(Some(Sysex7::try_from(&state.buffer.buffer()[..size])?))
+            }
+            Status::Start | Status::Continue => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{traits::Sysex, ux::u4, Data};
+    use pretty_assertions::assert_eq;
+
+    fn packet(data: [u32; 2]) -> Packet {
+        Packet::try_from(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn complete_packet() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        let message = assembler
+            .try_push(&packet([0x3003_0102, 0x0300_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.data(), &[0x3003_0102, 0x0300_0000]);
+    }
+
+    #[test]
+    fn multi_packet_message() {
+        let mut assembler = Sysex7Assembler::<[u32; 8]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3026_0708, 0x090A_0B0C])),
+            Ok(None)
+        );
+        let message = assembler
+            .try_push(&packet([0x3031_0D00, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.data(),
+            &[
+                0x3016_0102,
+                0x0304_0506,
+                0x3026_0708,
+                0x090A_0B0C,
+                0x3031_0D00,
+                0x0000_0000
+            ]
+        );
+        assert_eq!(message.payload_size(), 13);
+    }
+
+    #[test]
+    fn interleaved_groups() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3516_1112, 0x1314_1516])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler
+                .try_push(&packet([0x3531_1700, 0x0000_0000]))
+                .unwrap()
+                .unwrap()
+                .group(),
+            u4::new(0x5),
+        );
+        assert_eq!(
+            assembler
+                .try_push(&packet([0x3031_0700, 0x0000_0000]))
+                .unwrap()
+                .unwrap()
+                .payload()
+                .map(u8::from)
+                .collect::<std::vec::Vec<u8>>(),
+            std::vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07],
+        );
+    }
+
+    #[test]
+    fn continue_without_start() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3026_0102, 0x0304_0506])),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn end_without_start() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3031_0100, 0x0000_0000])),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn end_after_completed_message() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert!(assembler
+            .try_push(&packet([0x3016_0102, 0x0304_0506]))
+            .is_ok());
+        assert!(assembler
+            .try_push(&packet([0x3031_0700, 0x0000_0000]))
+            .is_ok());
+        assert_eq!(
+            assembler.try_push(&packet([0x3031_0700, 0x0000_0000])),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn start_interrupts_message() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_1112, 0x1314_1516])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler
+                .try_push(&packet([0x3031_1700, 0x0000_0000]))
+                .unwrap()
+                .unwrap()
+                .data(),
+            &[0x3016_1112, 0x1314_1516, 0x3031_1700, 0x0000_0000],
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3026_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3026_0102, 0x0304_0506])),
+            Err(Error::BufferOverflow),
+        );
+        // the rest of the message is discarded
+        assert_eq!(
+            assembler.try_push(&packet([0x3026_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3031_0100, 0x0000_0000])),
+            Ok(None)
+        );
+        // the next message is assembled as normal
+        assert!(assembler
+            .try_push(&packet([0x3001_0100, 0x0000_0000]))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn overflow_is_per_group() {
+        let mut assembler = Sysex7Assembler::<[u32; 2]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3116_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3031_0100, 0x0000_0000])),
+            Err(Error::BufferOverflow),
+        );
+        assert_eq!(
+            assembler.try_push(&packet([0x3131_0100, 0x0000_0000])),
+            Err(Error::BufferOverflow),
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut assembler = Sysex7Assembler::<[u32; 4]>::new();
+        assert_eq!(
+            assembler.try_push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        assembler.reset();
+        assert_eq!(
+            assembler.try_push(&packet([0x3031_0100, 0x0000_0000])),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn vec_backed() {
+        let mut assembler = Sysex7Assembler::<std::vec::Vec<u32>>::new();
+        for _ in 0..100 {
+            assert_eq!(
+                assembler.push(&packet([0x3026_0102, 0x0304_0506])),
+                Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+            );
+        }
+        assert_eq!(
+            assembler.push(&packet([0x3016_0102, 0x0304_0506])),
+            Ok(None)
+        );
+        for _ in 0..100 {
+            assert_eq!(
+                assembler.push(&packet([0x3026_0102, 0x0304_0506])),
+                Ok(None)
+            );
+        }
+        let message = assembler
+            .push(&packet([0x3031_0100, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.data().len(), 204);
+        assert_eq!(message.payload_size(), 607);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    buffer::{Buffer, BufferDefault, BufferMut, BufferTryResize, UnitPrivate},
+    sysex7::Sysex7,
+    traits::Sysex,
+    ux::u7,
+};
+
+/// Limits the size of each message produced by a [Sysex7Fragmenter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentLimit {
+    /// The maximum size of each message as a MIDI 1.0 byte stream,
+    /// including the `0xF0` and `0xF7` framing bytes.
+    MaxBytes(usize),
+    /// The maximum number of UMP packets of each message.
+    /// Each packet carries up to six payload bytes.
+    MaxPackets(usize),
+}
+
+/// Splits a long system exclusive payload into a sequence of
+/// complete, independently sendable [Sysex7] messages.
+///
+/// Each message is backed by a fixed size array buffer, so no allocation
+/// takes place. The size of each message is bounded by both the size
+/// of the buffer and the provided [FragmentLimit].
+///
+/// ```rust
+/// use midi2::{prelude::*, sysex7::{FragmentLimit, Sysex7Fragmenter}};
+///
+/// let fragmenter = Sysex7Fragmenter::<_, [u32; 16]>::new(
+///     (0u8..20).map(u7::new),
+///     FragmentLimit::MaxPackets(2),
+/// );
+///
+/// let chunks: Vec<Vec<u8>> = fragmenter
+///     .map(|message| message.payload().map(u8::from).collect())
+///     .collect();
+///
+/// assert_eq!(
+///     chunks,
+///     [
+///         (0u8..12).collect::<Vec<u8>>(),
+///         (12u8..20).collect::<Vec<u8>>(),
+///     ],
+/// );
+/// ```
+///
+/// A header (e.g. the manufacturer id) can be repeated at the start of each message.
+///
+/// ```rust
+/// use midi2::{prelude::*, sysex7::{FragmentLimit, Sysex7Fragmenter}};
+///
+/// let header = [u7::new(0x43), u7::new(0x10)];
+/// let mut fragmenter = Sysex7Fragmenter::<_, [u8; 32]>::with_header(
+///     (0u8..6).map(u7::new),
+///     &header,
+///     FragmentLimit::MaxBytes(7),
+/// );
+///
+/// assert_eq!(
+///     fragmenter.next().unwrap().data(),
+///     &[0xF0, 0x43, 0x10, 0x00, 0x01, 0x02, 0xF7],
+/// );
+/// assert_eq!(
+///     fragmenter.next().unwrap().data(),
+///     &[0xF0, 0x43, 0x10, 0x03, 0x04, 0x05, 0xF7],
+/// );
+/// assert!(fragmenter.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sysex7Fragmenter<'a, I: core::iter::Iterator<Item = u7>, B> {
+    payload: core::iter::Peekable<I>,
+    header: &'a [u7],
+    chunk_size: usize,
+    _buffer: core::marker::PhantomData<B>,
+}
+
+impl<I, B> Sysex7Fragmenter<'static, I, B>
+where
+    I: core::iter::Iterator<Item = u7>,
+    B: Buffer + BufferMut + BufferDefault + BufferTryResize,
+{
+    /// # Panics
+    ///
+    /// If the limit or the buffer leave no space for any payload.
+    pub fn new<P>(payload: P, limit: FragmentLimit) -> Self
+    where
+        P: core::iter::IntoIterator<IntoIter = I>,
+    {
+        Sysex7Fragmenter::with_header(payload, &[], limit)
+    }
+}
+
+impl<'a, I, B> Sysex7Fragmenter<'a, I, B>
+where
+    I: core::iter::Iterator<Item = u7>,
+    B: Buffer + BufferMut + BufferDefault + BufferTryResize,
+{
+    /// Create a fragmenter which starts the payload
+    /// of every message with the provided header.
+    ///
+    /// # Panics
+    ///
+    /// If the limit or the buffer leave no space for any payload after the header.
+    pub fn with_header<P>(payload: P, header: &'a [u7], limit: FragmentLimit) -> Self
+    where
+        P: core::iter::IntoIterator<IntoIter = I>,
+    {
+        let max_payload_size = match limit {
+            FragmentLimit::MaxBytes(size) => size.saturating_sub(2),
+            FragmentLimit::MaxPackets(packets) => packets * 6,
+        }
+        .min(buffer_payload_capacity::<B>());
+        assert!(
+            header.len() < max_payload_size,
+            "No space for payload in fragmented messages"
+        );
+        Sysex7Fragmenter {
+            payload: payload.into_iter().peekable(),
+            header,
+            chunk_size: max_payload_size - header.len(),
+            _buffer: Default::default(),
+        }
+    }
+}
+
+impl<I, B> core::iter::Iterator for Sysex7Fragmenter<'_, I, B>
+where
+    I: core::iter::Iterator<Item = u7>,
+    B: Buffer + BufferMut + BufferDefault + BufferTryResize,
+{
+    type Item = Sysex7<B>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.payload.peek()?;
+        let mut message = Sysex7::<B>::try_new().expect("Buffer fits an empty message");
+        message
+            .try_set_payload(
+                self.header
+                    .iter()
+                    .copied()
+                    .chain(self.payload.by_ref().take(self.chunk_size)),
+            )
+            .expect("Chunk size fits the buffer");
+        Some(message)
+    }
+}
+
+impl<I, B> core::iter::FusedIterator for Sysex7Fragmenter<'_, I, B>
+where
+    I: core::iter::Iterator<Item = u7>,
+    B: Buffer + BufferMut + BufferDefault + BufferTryResize,
+{
+}
+
+fn buffer_payload_capacity<B: Buffer + BufferDefault>() -> usize {
+    let size = B::default().buffer().len();
+    match <B::Unit as UnitPrivate>::UNIT_ID {
+        crate::buffer::UNIT_ID_U8 => size.saturating_sub(2),
+        crate::buffer::UNIT_ID_U32 => (size / 2) * 6,
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn payload(size: u8) -> impl core::iter::Iterator<Item = u7> {
+        (0..size).map(u7::new)
+    }
+
+    #[test]
+    fn single_chunk() {
+        let mut fragmenter =
+            Sysex7Fragmenter::<_, [u32; 4]>::new(payload(4), FragmentLimit::MaxPackets(2));
+        assert_eq!(
+            fragmenter.next().unwrap().data(),
+            &[0x3004_0001, 0x0203_0000]
+        );
+        assert!(fragmenter.next().is_none());
+    }
+
+    #[test]
+    fn empty_payload() {
+        let mut fragmenter =
+            Sysex7Fragmenter::<_, [u32; 4]>::new(payload(0), FragmentLimit::MaxPackets(2));
+        assert!(fragmenter.next().is_none());
+    }
+
+    #[test]
+    fn max_packets() {
+        let fragmenter =
+            Sysex7Fragmenter::<_, [u32; 8]>::new(payload(15), FragmentLimit::MaxPackets(2));
+        assert_eq!(
+            fragmenter
+                .map(|m| m.data().to_vec())
+                .collect::<std::vec::Vec<_>>(),
+            [
+                std::vec![0x3016_0001, 0x0203_0405, 0x3036_0607, 0x0809_0A0B],
+                std::vec![0x3003_0C0D, 0x0E00_0000],
+            ],
+        );
+    }
+
+    #[test]
+    fn max_bytes() {
+        let fragmenter =
+            Sysex7Fragmenter::<_, [u8; 16]>::new(payload(7), FragmentLimit::MaxBytes(5));
+        assert_eq!(
+            fragmenter
+                .map(|m| m.data().to_vec())
+                .collect::<std::vec::Vec<_>>(),
+            [
+                std::vec![0xF0, 0x00, 0x01, 0x02, 0xF7],
+                std::vec![0xF0, 0x03, 0x04, 0x05, 0xF7],
+                std::vec![0xF0, 0x06, 0xF7],
+            ],
+        );
+    }
+
+    #[test]
+    fn limited_by_buffer_size() {
+        let fragmenter =
+            Sysex7Fragmenter::<_, [u8; 4]>::new(payload(4), FragmentLimit::MaxBytes(100));
+        assert_eq!(
+            fragmenter
+                .map(|m| m.data().to_vec())
+                .collect::<std::vec::Vec<_>>(),
+            [
+                std::vec![0xF0, 0x00, 0x01, 0xF7],
+                std::vec![0xF0, 0x02, 0x03, 0xF7],
+            ],
+        );
+    }
+
+    #[test]
+    fn header_in_each_chunk() {
+        let header = [u7::new(0x7D)];
+        let fragmenter = Sysex7Fragmenter::<_, [u32; 2]>::with_header(
+            payload(8),
+            &header,
+            FragmentLimit::MaxPackets(4),
+        );
+        assert_eq!(
+            fragmenter
+                .map(|m| m.data().to_vec())
+                .collect::<std::vec::Vec<_>>(),
+            [
+                std::vec![0x3006_7D00, 0x0102_0304],
+                std::vec![0x3004_7D05, 0x0607_0000],
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn header_fills_chunk() {
+        let header = [u7::new(0x7D), u7::new(0x00), u7::new(0x01)];
+        Sysex7Fragmenter::<_, [u8; 16]>::with_header(
+            payload(8),
+            &header,
+            FragmentLimit::MaxBytes(5),
+        );
+    }
+}
@@ -67,7 +67,7 @@ impl crate::Grouped<[u32; 2]> for Packet {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Status {
     Complete,
     Start,
@@ -4,8 +4,10 @@ use crate::{
     ux,
 };
 
+mod assembler;
 mod packet;
 
+pub use assembler::Sysex8Assembler;
 pub use packet::Packet;
 
 pub(crate) const UMP_MESSAGE_TYPE: u8 = 0x5;
@@ -177,7 +179,7 @@ impl<B: crate::buffer::Ump + crate::buffer::BufferMut> crate::detail::property::
             .chunks_exact_mut(4)
             .take_while(|packet| u8::from(packet[0].nibble(0)) == UMP_MESSAGE_TYPE)
         {
-            packet[0].set_octet(3, id);
+            packet[0].set_octet(2, id);
         }
     }
     fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
@@ -991,6 +993,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn set_stream_id() {
+        let mut message = Sysex8::<std::vec::Vec<u32>>::new();
+        message.set_payload(0..16);
+        message.set_stream_id(0xBB);
+        assert_eq!(
+            message,
+            Sysex8(std::vec![
+                0x501E_BB00,
+                0x0102_0304,
+                0x0506_0708,
+                0x090A_0B0C,
+                0x5034_BB0D,
+                0x0E0F_0000,
+                0x0000_0000,
+                0x0000_0000,
+            ]),
+        );
+    }
+
     #[test]
     fn payload() {
         assert_eq!(
@@ -0,0 +1,487 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Ump},
+    detail::helpers::ERR_SYSEX_EXPECTED_BEGIN,
+    error::{BufferOverflow, Error, InvalidData},
+    sysex8::{
+        packet::{PreviousDataValidity, Status},
+        Packet, Sysex8,
+    },
+    ux::u4,
+    Grouped,
+};
+
+/// Reassembles [Sysex8] messages from individual [Packet]s.
+///
+/// The packets of up to 256 system exclusive messages can be in flight
+/// on each group at once, distinguished by their stream id.
+/// The assembler demultiplexes the packets by `(group, stream_id)`
+/// and produces a message once its final packet has been received.
+///
+/// At most `STREAMS` messages are assembled concurrently, each one
+/// written into its own backing buffer.
+/// With a resizable buffer (e.g. `Vec<u32>`) use [push](Sysex8Assembler::push).
+/// With a fixed size buffer (e.g. `[u32; N]`) use [try_push](Sysex8Assembler::try_push),
+/// which reports messages which are too large for the buffer.
+///
+/// Streams which never receive their end packet can be expired
+/// with [set_stream_expiry](Sysex8Assembler::set_stream_expiry).
+///
+/// ```rust
+/// use midi2::{prelude::*, sysex8::{Packet, Sysex8Assembler}};
+///
+/// let mut assembler = Sysex8Assembler::<[u32; 8], 4>::new();
+///
+/// let packets = [
+///     [0x501E_0100, 0x0102_0304, 0x0506_0708, 0x090A_0B0C],
+///     [0x501E_0200, 0x1112_1314, 0x1516_1718, 0x191A_1B1C],
+///     [0x5032_0210, 0x0000_0000, 0x0000_0000, 0x0000_0000],
+///     [0x5032_010D, 0x0000_0000, 0x0000_0000, 0x0000_0000],
+/// ];
+///
+/// let mut messages = Vec::new();
+/// for packet in packets {
+///     let packet = Packet::try_from(&packet[..]).expect("Valid packet");
+///     if let Some(message) = assembler.try_push(&packet).expect("Valid stream") {
+///         messages.push((message.stream_id(), message.payload().count()));
+///     }
+/// }
+///
+/// assert_eq!(messages, [(0x02, 14), (0x01, 14)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sysex8Assembler<B: Ump + BufferMut, const STREAMS: usize> {
+    streams: [Stream<B>; STREAMS],
+    complete: B,
+    expiry: Option<usize>,
+    packet_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stream<B> {
+    buffer: B,
+    size: usize,
+    key: Option<(u4, u8)>,
+    // the message overflowed the buffer and the remaining
+    // packets are discarded
+    discarding: bool,
+    last_packet: usize,
+}
+
+const ERR_TOO_MANY_STREAMS: &str = "Too many concurrent streams";
+
+impl<B: Ump + BufferMut + BufferDefault, const STREAMS: usize> core::default::Default
+    for Sysex8Assembler<B, STREAMS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault, const STREAMS: usize> Sysex8Assembler<B, STREAMS> {
+    pub fn new() -> Self {
+        Sysex8Assembler {
+            streams: core::array::from_fn(|_| Stream {
+                buffer: B::default(),
+                size: 0,
+                key: None,
+                discarding: false,
+                last_packet: 0,
+            }),
+            complete: B::default(),
+            expiry: None,
+            packet_count: 0,
+        }
+    }
+}
+
+impl<B: Ump + BufferMut, const STREAMS: usize> Sysex8Assembler<B, STREAMS> {
+    pub fn stream_expiry(&self) -> Option<usize> {
+        self.expiry
+    }
+
+    /// Drop partially assembled messages which have not received a packet
+    /// within the given number of packets pushed into the assembler.
+    ///
+    /// `None` (the default) disables expiry.
+    pub fn set_stream_expiry(&mut self, packets: Option<usize>) {
+        self.expiry = packets;
+    }
+
+    /// Drop all partially assembled messages.
+    pub fn reset(&mut self) {
+        for stream in self.streams.iter_mut() {
+            stream.key = None;
+        }
+    }
+
+    /// Feed the next packet of the stream into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// A start packet received while a message with the same group and stream id
+    /// is being assembled interrupts it and the partial message is dropped.
+    /// An end packet signalling an unexpected end with invalid data
+    /// drops the partial message.
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when a continue or end packet is received
+    /// without a preceding start packet for its group and stream id,
+    /// or when a start packet is received while the maximum number of
+    /// streams are already being assembled.
+    pub fn push(&mut self, packet: &Packet) -> Result<Option<Sysex8<&[u32]>>, Error>
+    where
+        B: BufferResize,
+    {
+        self.push_impl(packet, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+
+    /// Feed the next packet of the stream into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// A start packet received while a message with the same group and stream id
+    /// is being assembled interrupts it and the partial message is dropped.
+    /// An end packet signalling an unexpected end with invalid data
+    /// drops the partial message.
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when a continue or end packet is received
+    /// without a preceding start packet for its group and stream id,
+    /// or when a start packet is received while the maximum number of
+    /// streams are already being assembled.
+    ///
+    /// With [BufferOverflow] when the message does not fit in the buffer.
+    /// The error is reported once and the remaining packets of the
+    /// message are discarded.
+    pub fn try_push(&mut self, packet: &Packet) -> Result<Option<Sysex8<&[u32]>>, Error>
+    where
+        B: BufferTryResize,
+    {
+        self.push_impl(packet, |buffer, size| buffer.try_resize(size))
+    }
+
+    fn push_impl<Resize>(
+        &mut self,
+        packet: &Packet,
+        try_resize: Resize,
+    ) -> Result<Option<Sysex8<&[u32]>>, Error>
+    where
+        Resize: Fn(&mut B, usize) -> Result<(), BufferOverflow>,
+    {
+        self.packet_count = self.packet_count.wrapping_add(1);
+        self.expire_streams();
+
+        let key = (packet.group(), packet.stream_id());
+        let status = packet.status();
+        let position = self.streams.iter().position(|s| s.key == Some(key));
+
+        let index = match (status, position) {
+            (Status::Complete, _) => {
+                if let Some(index) = position {
+                    self.streams[index].key = None;
+                }
+                try_resize(&mut self.complete, packet.len())?;
+                self.complete.buffer_mut()[..packet.len()].copy_from_slice(packet);
+                return Ok(Some(Sysex8::try_from(
+                    &self.complete.buffer()[..packet.len()],
+                )?));
+            }
+            (Status::Start, Some(index)) => index,
+            (Status::Start, None) => self
+                .streams
+                .iter()
+                .position(|s| s.key.is_none())
+                .ok_or(InvalidData(ERR_TOO_MANY_STREAMS))?,
+            (Status::UnexpectedEnd(PreviousDataValidity::Invalid), Some(index)) => {
+                self.streams[index].key = None;
+                return Ok(None);
+            }
+            (Status::Continue | Status::End | Status::UnexpectedEnd(_), Some(index)) => index,
+            (Status::Continue | Status::End | Status::UnexpectedEnd(_), None) => {
+                return Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN).into());
+            }
+        };
+
+        let packet_count = self.packet_count;
+        let stream = &mut self.streams[index];
+        let is_end = matches!(status, Status::End | Status::UnexpectedEnd(_));
+
+        if status == Status::Start {
+            stream.key = Some(key);
+            stream.size = 0;
+            stream.discarding = false;
+        }
+        stream.last_packet = packet_count;
+
+        if stream.discarding {
+            if is_end {
+                stream.key = None;
+            }
+            return Ok(None);
+        }
+
+        let size = stream.size + packet.len();
+        if let Err(e) = try_resize(&mut stream.buffer, size) {
+            if is_end {
+                stream.key = None;
+            } else {
+                stream.discarding = true;
+            }
+            return Err(e.into());
+        }
+        stream.buffer.buffer_mut()[stream.size..size].copy_from_slice(packet);
+        stream.size = size;
+
+        if is_end {
+            stream.key = None;
+            Ok(Some(Sysex8::try_from(&stream.buffer.buffer()[..size])?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn expire_streams(&mut self) {
+        let Some(expiry) = self.expiry else {
+            return;
+        };
+        for stream in self.streams.iter_mut() {
+            if stream.key.is_some() && self.packet_count.wrapping_sub(stream.last_packet) > expiry {
+                stream.key = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{traits::Sysex, Data};
+    use pretty_assertions::assert_eq;
+
+    fn packet(data: [u32; 4]) -> Packet {
+        Packet::try_from(&data[..]).unwrap()
+    }
+
+    const START_1: [u32; 4] = [0x501E_0100, 0x0102_0304, 0x0506_0708, 0x090A_0B0C];
+    const CONTINUE_1: [u32; 4] = [0x502E_010D, 0x0E0F_1011, 0x1213_1415, 0x1617_1819];
+    const END_1: [u32; 4] = [0x5032_011A, 0x0000_0000, 0x0000_0000, 0x0000_0000];
+    const START_2: [u32; 4] = [0x501E_0200, 0x0102_0304, 0x0506_0708, 0x090A_0B0C];
+    const END_2: [u32; 4] = [0x5032_021A, 0x0000_0000, 0x0000_0000, 0x0000_0000];
+
+    #[test]
+    fn complete_packet() {
+        let mut assembler = Sysex8Assembler::<[u32; 4], 1>::new();
+        let message = assembler
+            .try_push(&packet([0x5004_0101, 0x0203_0000, 0x0, 0x0]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.data(), &[0x5004_0101, 0x0203_0000, 0x0, 0x0]);
+    }
+
+    #[test]
+    fn complete_packet_with_no_free_streams() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert!(assembler
+            .try_push(&packet([0x5004_0201, 0x0203_0000, 0x0, 0x0]))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn multi_packet_message() {
+        let mut assembler = Sysex8Assembler::<[u32; 12], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(CONTINUE_1)), Ok(None));
+        let message = assembler.try_push(&packet(END_1)).unwrap().unwrap();
+        assert_eq!(message.stream_id(), 0x01);
+        assert_eq!(
+            message.payload().collect::<std::vec::Vec<u8>>(),
+            (0x0..=0x1A).collect::<std::vec::Vec<u8>>(),
+        );
+    }
+
+    #[test]
+    fn interleaved_streams() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 2>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(START_2)), Ok(None));
+        assert_eq!(
+            assembler
+                .try_push(&packet(END_2))
+                .unwrap()
+                .unwrap()
+                .stream_id(),
+            0x02,
+        );
+        assert_eq!(
+            assembler
+                .try_push(&packet(END_1))
+                .unwrap()
+                .unwrap()
+                .stream_id(),
+            0x01,
+        );
+    }
+
+    #[test]
+    fn same_stream_id_on_different_groups() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 2>::new();
+        let mut start = START_1;
+        start[0] |= 0x0A00_0000;
+        let mut end = END_1;
+        end[0] |= 0x0A00_0000;
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(start)), Ok(None));
+        let message = assembler.try_push(&packet(end)).unwrap().unwrap();
+        assert_eq!(message.group(), u4::new(0xA));
+        assert_eq!(message.data()[0], 0x5A1E_0100);
+        let message = assembler.try_push(&packet(END_1)).unwrap().unwrap();
+        assert_eq!(message.group(), u4::new(0x0));
+    }
+
+    #[test]
+    fn continue_without_start() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 2>::new();
+        assert_eq!(
+            assembler.try_push(&packet(CONTINUE_1)),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn end_without_start() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 2>::new();
+        assert_eq!(assembler.try_push(&packet(START_2)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(END_1)),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn too_many_streams() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(START_2)),
+            Err(Error::InvalidData(InvalidData(ERR_TOO_MANY_STREAMS))),
+        );
+        // completing a stream frees it up
+        assert!(assembler.try_push(&packet(END_1)).unwrap().is_some());
+        assert_eq!(assembler.try_push(&packet(START_2)), Ok(None));
+    }
+
+    #[test]
+    fn start_interrupts_stream() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(
+            assembler
+                .try_push(&packet(END_1))
+                .unwrap()
+                .unwrap()
+                .payload_size(),
+            14,
+        );
+    }
+
+    #[test]
+    fn unexpected_end_invalid_data() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet([0x503F_0100, 0x0, 0x0, 0x0])),
+            Ok(None),
+        );
+        assert_eq!(
+            assembler.try_push(&packet(END_1)),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn unexpected_end_valid_data() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(
+            assembler
+                .try_push(&packet([0x5031_0100, 0x0, 0x0, 0x0]))
+                .unwrap()
+                .unwrap()
+                .payload_size(),
+            13,
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let mut assembler = Sysex8Assembler::<[u32; 4], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(CONTINUE_1)),
+            Err(Error::BufferOverflow),
+        );
+        assert_eq!(assembler.try_push(&packet(CONTINUE_1)), Ok(None));
+        assert_eq!(assembler.try_push(&packet(END_1)), Ok(None));
+        // the stream is free again
+        assert_eq!(assembler.try_push(&packet(START_2)), Ok(None));
+    }
+
+    #[test]
+    fn expiry() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assembler.set_stream_expiry(Some(2));
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(START_2)),
+            Err(Error::InvalidData(InvalidData(ERR_TOO_MANY_STREAMS))),
+        );
+        assert_eq!(
+            assembler.try_push(&packet(START_2)),
+            Err(Error::InvalidData(InvalidData(ERR_TOO_MANY_STREAMS))),
+        );
+        // stream 1 has now been idle for more than two packets
+        assert_eq!(assembler.try_push(&packet(START_2)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&packet(END_1)),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+
+    #[test]
+    fn activity_prevents_expiry() {
+        let mut assembler = Sysex8Assembler::<std::vec::Vec<u32>, 1>::new();
+        assembler.set_stream_expiry(Some(1));
+        assert_eq!(assembler.push(&packet(START_1)), Ok(None));
+        for _ in 0..10 {
+            assert_eq!(assembler.push(&packet(CONTINUE_1)), Ok(None));
+        }
+        assert_eq!(
+            assembler
+                .push(&packet(END_1))
+                .unwrap()
+                .unwrap()
+                .data()
+                .len(),
+            48,
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut assembler = Sysex8Assembler::<[u32; 8], 1>::new();
+        assert_eq!(assembler.try_push(&packet(START_1)), Ok(None));
+        assembler.reset();
+        assert_eq!(
+            assembler.try_push(&packet(END_1)),
+            Err(Error::InvalidData(InvalidData(ERR_SYSEX_EXPECTED_BEGIN))),
+        );
+    }
+}
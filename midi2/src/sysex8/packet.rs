@@ -63,13 +63,13 @@ impl crate::Grouped<[u32; 4]> for Packet {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum PreviousDataValidity {
     Valid,
     Invalid,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Status {
     Complete,
     Start,
@@ -20,7 +20,7 @@ mod tune_request {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct TuneRequest {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -365,6 +365,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tune_request_bytes_data() {
+        use crate::Data;
+        assert_eq!(
+            TuneRequest::try_from(&[0xF6_u8][..]).unwrap().data(),
+            &[0xF6_u8][..]
+        );
+    }
+
+    #[test]
+    fn time_code_bytes_data() {
+        use crate::Data;
+        assert_eq!(
+            TimeCode::try_from(&[0xF1_u8, 0x5F][..]).unwrap().data(),
+            &[0xF1_u8, 0x5F][..]
+        );
+    }
+
+    #[test]
+    fn song_position_pointer_bytes_data() {
+        use crate::Data;
+        assert_eq!(
+            SongPositionPointer::try_from(&[0xF2_u8, 0x7D, 0x6C][..])
+                .unwrap()
+                .data(),
+            &[0xF2_u8, 0x7D, 0x6C][..]
+        );
+    }
+
+    #[test]
+    fn song_position_pointer_bytes_too_short() {
+        assert!(SongPositionPointer::try_from(&[0xF2_u8, 0x7D][..]).is_err());
+    }
+
     #[test]
     fn from_byte_data() {
         assert_eq!(
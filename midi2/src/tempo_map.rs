@@ -0,0 +1,626 @@
+//! Convert between ticks, time and musical position.
+//!
+//! A [TempoMap] records the tempo and time signature changes of a sequence
+//! of UMP messages. Ticks are counted with [DeltaClockstamp] messages
+//! at the resolution declared with [DeltaClockstampTpq], while [SetTempo]
+//! and [SetTimeSignature] messages add changes at the current tick.
+//!
+//! ```rust
+//! use midi2::{
+//!     prelude::*,
+//!     flex_data::{FlexData, SetTempo},
+//!     tempo_map::{Position, TempoMap},
+//!     utility::{DeltaClockstamp, DeltaClockstampTpq, Utility},
+//! };
+//! use core::time::Duration;
+//!
+//! let mut tpq = DeltaClockstampTpq::<[u32; 4]>::new();
+//! tpq.set_time_data(96);
+//! let mut delta = DeltaClockstamp::<[u32; 4]>::new();
+//! delta.set_time_data(u20::new(192));
+//! let mut tempo = SetTempo::<[u32; 4]>::new();
+//! // 60 beats per minute
+//! tempo.set_number_of_10_nanosecond_units_per_quarter_note(100_000_000);
+//!
+//! let mut map = TempoMap::new(480);
+//! for message in [
+//!     UmpMessage::from(Utility::from(tpq)),
+//!     UmpMessage::from(Utility::from(delta)),
+//!     UmpMessage::from(FlexData::from(tempo)),
+//! ] {
+//!     map.push(&message).unwrap();
+//! }
+//! assert_eq!(map.tick(), 192);
+//!
+//! // two quarter notes at the default 120 beats per minute
+//! // followed by one quarter note at 60 beats per minute
+//! assert_eq!(map.tick_to_duration(288), Duration::from_secs(2));
+//! assert_eq!(map.duration_to_tick(Duration::from_secs(2)), 288);
+//!
+//! // the second beat of the second bar in the default 4/4 time signature
+//! assert_eq!(
+//!     map.tick_to_position(480),
+//!     Position { bar: 1, beat: 1, tick: 0 },
+//! );
+//! ```
+
+use crate::{
+    buffer::Ump,
+    error::InvalidData,
+    flex_data::{FlexData, SetTempo, SetTimeSignature},
+    utility::{DeltaClockstamp, DeltaClockstampTpq, Utility},
+    UmpMessage,
+};
+
+const ERR_ZERO_TICKS_PER_QUARTER_NOTE: &str = "Ticks per quarter note must be non zero";
+const ERR_ZERO_TEMPO: &str = "Tempo must be non zero";
+const ERR_INVALID_TIME_SIGNATURE: &str =
+    "Time signature numerator must be non zero and denominator at most 31";
+
+/// The tempo in effect until the first tempo change: 120 beats per minute.
+pub const DEFAULT_TEMPO: u32 = 50_000_000;
+
+/// The time signature in effect until the first time signature change: 4/4.
+pub const DEFAULT_TIME_SIGNATURE: TimeSignature = TimeSignature {
+    numerator: 4,
+    denominator: 2,
+};
+
+const MAX_DENOMINATOR: u8 = 31;
+
+/// A time signature with the denominator
+/// expressed as a negative power of two, as in [SetTimeSignature].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+/// A musical position.
+///
+/// All fields are zero based.
+/// The beat is counted in units of the time signature denominator
+/// and the tick is the offset into the beat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub bar: u64,
+    pub beat: u64,
+    pub tick: u64,
+}
+
+/// Records tempo and time signature changes
+/// and converts between ticks, time and musical position.
+///
+/// See the [module documentation](crate::tempo_map) for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TempoMap {
+    ticks_per_quarter_note: u16,
+    tick: u64,
+    tempos: std::vec::Vec<TempoChange>,
+    time_signatures: std::vec::Vec<TimeSignatureChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TempoChange {
+    tick: u64,
+    tempo: u32,
+    // the time at the change in units of 10 nanoseconds
+    // multiplied by the ticks per quarter note,
+    // which keeps it exact and independent of the resolution
+    time: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeSignatureChange {
+    tick: u64,
+    time_signature: TimeSignature,
+    bar: u64,
+}
+
+impl TempoMap {
+    /// Create a map with the default tempo and time signature.
+    ///
+    /// # Panics
+    ///
+    /// If `ticks_per_quarter_note` is zero.
+    pub fn new(ticks_per_quarter_note: u16) -> Self {
+        assert!(
+            ticks_per_quarter_note != 0,
+            "{ERR_ZERO_TICKS_PER_QUARTER_NOTE}"
+        );
+        TempoMap {
+            ticks_per_quarter_note,
+            tick: 0,
+            tempos: std::vec![TempoChange {
+                tick: 0,
+                tempo: DEFAULT_TEMPO,
+                time: 0,
+            }],
+            time_signatures: std::vec![TimeSignatureChange {
+                tick: 0,
+                time_signature: DEFAULT_TIME_SIGNATURE,
+                bar: 0,
+            }],
+        }
+    }
+
+    pub fn ticks_per_quarter_note(&self) -> u16 {
+        self.ticks_per_quarter_note
+    }
+
+    /// The current tick of the pushed message stream.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Process the next message of the stream and
+    /// return the absolute tick of the message.
+    ///
+    /// - [DeltaClockstamp] messages advance the current tick.
+    /// - [DeltaClockstampTpq] messages set the resolution of the whole map.
+    /// - [SetTempo] and [SetTimeSignature] messages
+    ///   change the tempo or time signature at the current tick.
+    ///
+    /// All other messages are ignored.
+    ///
+    /// # Fails
+    ///
+    /// If the message declares a zero resolution or tempo, or an invalid time signature.
+    pub fn push<B: Ump>(&mut self, message: &UmpMessage<B>) -> Result<u64, InvalidData> {
+        match message {
+            UmpMessage::Utility(Utility::DeltaClockstamp(m)) => self.push_delta_clockstamp(m),
+            UmpMessage::Utility(Utility::DeltaClockstampTpq(m)) => {
+                self.push_delta_clockstamp_tpq(m)?
+            }
+            UmpMessage::FlexData(FlexData::SetTempo(m)) => self.push_set_tempo(m)?,
+            UmpMessage::FlexData(FlexData::SetTimeSignature(m)) => {
+                self.push_set_time_signature(m)?
+            }
+            _ => {}
+        }
+        Ok(self.tick)
+    }
+
+    /// Change the tempo from the given tick onwards.
+    ///
+    /// The tempo is given in units of 10 nanoseconds per quarter note,
+    /// as in [SetTempo]. A later change at the same tick replaces the earlier one.
+    ///
+    /// # Fails
+    ///
+    /// If the tempo is zero.
+    pub fn set_tempo(&mut self, tick: u64, tempo: u32) -> Result<(), InvalidData> {
+        if tempo == 0 {
+            return Err(InvalidData(ERR_ZERO_TEMPO));
+        }
+        let index = self.tempos.partition_point(|change| change.tick < tick);
+        let change = TempoChange {
+            tick,
+            tempo,
+            time: 0,
+        };
+        match self.tempos.get_mut(index) {
+            Some(existing) if existing.tick == tick => *existing = change,
+            _ => self.tempos.insert(index, change),
+        }
+        self.update_times(index);
+        Ok(())
+    }
+
+    /// Change the time signature from the given tick onwards.
+    ///
+    /// A new bar starts at every change.
+    /// A later change at the same tick replaces the earlier one.
+    ///
+    /// # Fails
+    ///
+    /// If the numerator is zero or the denominator is greater than 31.
+    pub fn set_time_signature(
+        &mut self,
+        tick: u64,
+        time_signature: TimeSignature,
+    ) -> Result<(), InvalidData> {
+        if time_signature.numerator == 0 || time_signature.denominator > MAX_DENOMINATOR {
+            return Err(InvalidData(ERR_INVALID_TIME_SIGNATURE));
+        }
+        let index = self
+            .time_signatures
+            .partition_point(|change| change.tick < tick);
+        let change = TimeSignatureChange {
+            tick,
+            time_signature,
+            bar: 0,
+        };
+        match self.time_signatures.get_mut(index) {
+            Some(existing) if existing.tick == tick => *existing = change,
+            _ => self.time_signatures.insert(index, change),
+        }
+        self.update_bars();
+        Ok(())
+    }
+
+    /// The tempo at the given tick in units of 10 nanoseconds per quarter note.
+    pub fn tempo_at(&self, tick: u64) -> u32 {
+        self.tempo_change_at(tick).tempo
+    }
+
+    pub fn time_signature_at(&self, tick: u64) -> TimeSignature {
+        self.time_signature_change_at(tick).time_signature
+    }
+
+    /// The time elapsed from tick zero to the given tick,
+    /// rounded up to the nanosecond so that
+    /// [duration_to_tick](TempoMap::duration_to_tick) gives back the same tick.
+    pub fn tick_to_duration(&self, tick: u64) -> core::time::Duration {
+        let change = self.tempo_change_at(tick);
+        let time = change.time + u128::from(tick - change.tick) * u128::from(change.tempo);
+        let nanos = (time * 10).div_ceil(u128::from(self.ticks_per_quarter_note));
+        core::time::Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+
+    /// The last tick at or before the given time.
+    pub fn duration_to_tick(&self, duration: core::time::Duration) -> u64 {
+        let time = duration.as_nanos() * u128::from(self.ticks_per_quarter_note) / 10;
+        let index = self.tempos.partition_point(|change| change.time <= time) - 1;
+        let change = &self.tempos[index];
+        let ticks = (time - change.time) / u128::from(change.tempo);
+        change.tick + ticks.min(u128::from(u64::MAX - change.tick)) as u64
+    }
+
+    /// The musical position of the given tick.
+    ///
+    /// When a beat isn't a whole number of ticks
+    /// the tick offset into the beat is rounded down.
+    pub fn tick_to_position(&self, tick: u64) -> Position {
+        let change = self.time_signature_change_at(tick);
+        let numerator = u128::from(change.time_signature.numerator);
+        // ticks are scaled by the power of two denominator
+        // so that a beat is a whole number of scaled ticks
+        let scale = 1_u128 << change.time_signature.denominator;
+        let beat_length = self.scaled_ticks_per_beat();
+
+        let scaled = u128::from(tick - change.tick) * scale;
+        let beats = scaled / beat_length;
+        Position {
+            bar: change.bar + (beats / numerator) as u64,
+            beat: (beats % numerator) as u64,
+            tick: (scaled % beat_length / scale) as u64,
+        }
+    }
+
+    /// The tick of the given musical position.
+    ///
+    /// Beats and ticks beyond the end of the bar carry over into the next bars.
+    pub fn position_to_tick(&self, position: Position) -> u64 {
+        let index = self
+            .time_signatures
+            .partition_point(|change| change.bar <= position.bar)
+            - 1;
+        let change = &self.time_signatures[index];
+        let numerator = u128::from(change.time_signature.numerator);
+        let scale = 1_u128 << change.time_signature.denominator;
+
+        let beats = u128::from(position.bar - change.bar) * numerator + u128::from(position.beat);
+        let ticks = beats * self.scaled_ticks_per_beat() / scale + u128::from(position.tick);
+        change.tick + ticks.min(u128::from(u64::MAX - change.tick)) as u64
+    }
+
+    fn push_delta_clockstamp<B: Ump>(&mut self, message: &DeltaClockstamp<B>) {
+        self.tick += u64::from(u32::from(message.time_data()));
+    }
+
+    fn push_delta_clockstamp_tpq<B: Ump>(
+        &mut self,
+        message: &DeltaClockstampTpq<B>,
+    ) -> Result<(), InvalidData> {
+        if message.time_data() == 0 {
+            return Err(InvalidData(ERR_ZERO_TICKS_PER_QUARTER_NOTE));
+        }
+        self.ticks_per_quarter_note = message.time_data();
+        self.update_bars();
+        Ok(())
+    }
+
+    fn push_set_tempo<B: Ump>(&mut self, message: &SetTempo<B>) -> Result<(), InvalidData> {
+        self.set_tempo(
+            self.tick,
+            message.number_of_10_nanosecond_units_per_quarter_note(),
+        )
+    }
+
+    fn push_set_time_signature<B: Ump>(
+        &mut self,
+        message: &SetTimeSignature<B>,
+    ) -> Result<(), InvalidData> {
+        self.set_time_signature(
+            self.tick,
+            TimeSignature {
+                numerator: message.numerator(),
+                denominator: message.denominator(),
+            },
+        )
+    }
+
+    fn tempo_change_at(&self, tick: u64) -> &TempoChange {
+        let index = self.tempos.partition_point(|change| change.tick <= tick);
+        &self.tempos[index.max(1) - 1]
+    }
+
+    fn time_signature_change_at(&self, tick: u64) -> &TimeSignatureChange {
+        let index = self
+            .time_signatures
+            .partition_point(|change| change.tick <= tick);
+        &self.time_signatures[index.max(1) - 1]
+    }
+
+    // the length of a beat scaled by two to the power of the denominator,
+    // i.e. the length of a whole note
+    fn scaled_ticks_per_beat(&self) -> u128 {
+        4 * u128::from(self.ticks_per_quarter_note)
+    }
+
+    fn update_times(&mut self, from: usize) {
+        for index in from.max(1)..self.tempos.len() {
+            let previous = self.tempos[index - 1];
+            self.tempos[index].time = previous.time
+                + u128::from(self.tempos[index].tick - previous.tick) * u128::from(previous.tempo);
+        }
+    }
+
+    // bars depend on the resolution, so are recomputed
+    // when either the resolution or the time signatures change.
+    // a partial bar before a change counts as a whole bar.
+    fn update_bars(&mut self) {
+        let beat_length = self.scaled_ticks_per_beat();
+        for index in 1..self.time_signatures.len() {
+            let previous = self.time_signatures[index - 1];
+            let scale = 1_u128 << previous.time_signature.denominator;
+            let bar_length = beat_length * u128::from(previous.time_signature.numerator);
+            let scaled = u128::from(self.time_signatures[index].tick - previous.tick) * scale;
+            self.time_signatures[index].bar = previous.bar + scaled.div_ceil(bar_length) as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ux::u20;
+    use core::time::Duration;
+    use pretty_assertions::assert_eq;
+
+    fn delta_clockstamp(ticks: u32) -> UmpMessage<[u32; 4]> {
+        let mut message = DeltaClockstamp::<[u32; 4]>::new();
+        message.set_time_data(u20::new(ticks));
+        Utility::from(message).into()
+    }
+
+    fn delta_clockstamp_tpq(ticks_per_quarter_note: u16) -> UmpMessage<[u32; 4]> {
+        let mut message = DeltaClockstampTpq::<[u32; 4]>::new();
+        message.set_time_data(ticks_per_quarter_note);
+        Utility::from(message).into()
+    }
+
+    fn set_tempo(tempo: u32) -> UmpMessage<[u32; 4]> {
+        let mut message = SetTempo::<[u32; 4]>::new();
+        message.set_number_of_10_nanosecond_units_per_quarter_note(tempo);
+        FlexData::from(message).into()
+    }
+
+    fn set_time_signature(numerator: u8, denominator: u8) -> UmpMessage<[u32; 4]> {
+        let mut message = SetTimeSignature::<[u32; 4]>::new();
+        message.set_numerator(numerator);
+        message.set_denominator(denominator);
+        FlexData::from(message).into()
+    }
+
+    fn time_signature(numerator: u8, denominator: u8) -> TimeSignature {
+        TimeSignature {
+            numerator,
+            denominator,
+        }
+    }
+
+    fn position(bar: u64, beat: u64, tick: u64) -> Position {
+        Position { bar, beat, tick }
+    }
+
+    #[test]
+    fn defaults() {
+        let map = TempoMap::new(96);
+        assert_eq!(map.tick(), 0);
+        assert_eq!(map.tempo_at(1000), DEFAULT_TEMPO);
+        assert_eq!(map.time_signature_at(1000), DEFAULT_TIME_SIGNATURE);
+        assert_eq!(map.tick_to_duration(96), Duration::from_millis(500));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_ticks_per_quarter_note() {
+        TempoMap::new(0);
+    }
+
+    #[test]
+    fn push_returns_tick() {
+        let mut map = TempoMap::new(96);
+        assert_eq!(map.push(&delta_clockstamp(10)), Ok(10));
+        assert_eq!(map.push(&delta_clockstamp(0xFFFFF)), Ok(0x100009));
+        assert_eq!(map.push(&set_tempo(DEFAULT_TEMPO)), Ok(0x100009));
+        assert_eq!(map.tick(), 0x100009);
+    }
+
+    #[test]
+    fn push_ignores_other_messages() {
+        let mut map = TempoMap::new(96);
+        // utility no-op
+        let message = UmpMessage::try_from(&[0x0000_0000][..]).unwrap();
+        assert_eq!(map.push(&message), Ok(0));
+        assert_eq!(map, TempoMap::new(96));
+    }
+
+    #[test]
+    fn push_tempo_changes() {
+        let mut map = TempoMap::new(96);
+        map.push(&delta_clockstamp(96)).unwrap();
+        map.push(&set_tempo(25_000_000)).unwrap();
+        map.push(&delta_clockstamp(192)).unwrap();
+        map.push(&set_tempo(100_000_000)).unwrap();
+
+        assert_eq!(map.tempo_at(95), 50_000_000);
+        assert_eq!(map.tempo_at(96), 25_000_000);
+        assert_eq!(map.tempo_at(288), 100_000_000);
+        assert_eq!(map.tick_to_duration(96), Duration::from_millis(500));
+        assert_eq!(map.tick_to_duration(288), Duration::from_millis(1000));
+        assert_eq!(map.tick_to_duration(384), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn duration_to_tick() {
+        let mut map = TempoMap::new(96);
+        map.set_tempo(96, 25_000_000).unwrap();
+        map.set_tempo(288, 100_000_000).unwrap();
+
+        assert_eq!(map.duration_to_tick(Duration::ZERO), 0);
+        assert_eq!(map.duration_to_tick(Duration::from_millis(500)), 96);
+        assert_eq!(map.duration_to_tick(Duration::from_millis(750)), 192);
+        assert_eq!(map.duration_to_tick(Duration::from_millis(2000)), 384);
+        // rounded down to the last tick
+        assert_eq!(map.duration_to_tick(Duration::from_millis(2010)), 384);
+        for tick in [0, 1, 95, 96, 97, 287, 288, 289, 1000] {
+            assert_eq!(map.duration_to_tick(map.tick_to_duration(tick)), tick);
+        }
+    }
+
+    #[test]
+    fn tick_to_duration_rounds_up() {
+        let map = TempoMap::new(3);
+        assert_eq!(map.tick_to_duration(1), Duration::from_nanos(166_666_667));
+    }
+
+    #[test]
+    fn set_tempo_out_of_order() {
+        let mut map = TempoMap::new(96);
+        map.set_tempo(288, 100_000_000).unwrap();
+        map.set_tempo(96, 25_000_000).unwrap();
+        assert_eq!(map.tick_to_duration(384), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn set_tempo_replaces_change_at_same_tick() {
+        let mut map = TempoMap::new(96);
+        map.set_tempo(0, 25_000_000).unwrap();
+        map.set_tempo(96, 25_000_000).unwrap();
+        map.set_tempo(96, 100_000_000).unwrap();
+        assert_eq!(map.tempo_at(0), 25_000_000);
+        assert_eq!(map.tick_to_duration(192), Duration::from_millis(1250));
+    }
+
+    #[test]
+    fn zero_tempo() {
+        let mut map = TempoMap::new(96);
+        assert_eq!(map.push(&set_tempo(0)), Err(InvalidData(ERR_ZERO_TEMPO)),);
+    }
+
+    #[test]
+    fn delta_clockstamp_tpq_sets_resolution() {
+        let mut map = TempoMap::new(96);
+        map.push(&delta_clockstamp_tpq(480)).unwrap();
+        assert_eq!(map.ticks_per_quarter_note(), 480);
+        assert_eq!(map.tick_to_duration(480), Duration::from_millis(500));
+        assert_eq!(map.tick_to_position(480 * 5), position(1, 1, 0));
+    }
+
+    #[test]
+    fn zero_delta_clockstamp_tpq() {
+        let mut map = TempoMap::new(96);
+        assert_eq!(
+            map.push(&delta_clockstamp_tpq(0)),
+            Err(InvalidData(ERR_ZERO_TICKS_PER_QUARTER_NOTE)),
+        );
+    }
+
+    #[test]
+    fn tick_to_position() {
+        let map = TempoMap::new(96);
+        assert_eq!(map.tick_to_position(0), position(0, 0, 0));
+        assert_eq!(map.tick_to_position(95), position(0, 0, 95));
+        assert_eq!(map.tick_to_position(96), position(0, 1, 0));
+        assert_eq!(map.tick_to_position(96 * 4 + 10), position(1, 0, 10));
+    }
+
+    #[test]
+    fn time_signature_changes() {
+        let mut map = TempoMap::new(96);
+        // one bar of 4/4 then 6/8
+        map.push(&delta_clockstamp(384)).unwrap();
+        map.push(&set_time_signature(6, 3)).unwrap();
+
+        assert_eq!(map.time_signature_at(383), time_signature(4, 2));
+        assert_eq!(map.time_signature_at(384), time_signature(6, 3));
+        assert_eq!(map.tick_to_position(384), position(1, 0, 0));
+        assert_eq!(map.tick_to_position(384 + 48), position(1, 1, 0));
+        assert_eq!(map.tick_to_position(384 + 288 + 50), position(2, 1, 2));
+    }
+
+    #[test]
+    fn time_signature_change_part_way_through_bar() {
+        let mut map = TempoMap::new(96);
+        map.set_time_signature(96, time_signature(3, 2)).unwrap();
+        // the partial first bar counts as a whole bar
+        assert_eq!(map.tick_to_position(95), position(0, 0, 95));
+        assert_eq!(map.tick_to_position(96), position(1, 0, 0));
+        assert_eq!(map.tick_to_position(96 + 288), position(2, 0, 0));
+    }
+
+    #[test]
+    fn time_signature_changes_follow_resolution() {
+        let mut map = TempoMap::new(96);
+        map.set_time_signature(384, time_signature(3, 2)).unwrap();
+        map.set_time_signature(768, time_signature(2, 2)).unwrap();
+        assert_eq!(map.tick_to_position(768), position(3, 0, 0));
+
+        map.push(&delta_clockstamp_tpq(192)).unwrap();
+        assert_eq!(map.tick_to_position(768), position(2, 0, 0));
+    }
+
+    #[test]
+    fn position_to_tick() {
+        let mut map = TempoMap::new(96);
+        map.set_time_signature(384, time_signature(6, 3)).unwrap();
+        map.set_time_signature(960, time_signature(3, 2)).unwrap();
+
+        assert_eq!(map.position_to_tick(position(0, 3, 5)), 293);
+        assert_eq!(map.position_to_tick(position(1, 0, 0)), 384);
+        assert_eq!(map.position_to_tick(position(2, 5, 0)), 384 + 288 + 240);
+        assert_eq!(map.position_to_tick(position(3, 1, 0)), 960 + 96);
+        for tick in [0, 1, 383, 384, 385, 700, 959, 960, 2000] {
+            assert_eq!(map.position_to_tick(map.tick_to_position(tick)), tick);
+        }
+    }
+
+    #[test]
+    fn position_with_fractional_beat_length() {
+        let mut map = TempoMap::new(1);
+        map.set_time_signature(0, time_signature(4, 3)).unwrap();
+        // an eighth note beat is half a tick
+        assert_eq!(map.tick_to_position(1), position(0, 2, 0));
+        assert_eq!(map.position_to_tick(position(0, 3, 0)), 1);
+        assert_eq!(map.position_to_tick(position(1, 0, 0)), 2);
+    }
+
+    #[test]
+    fn invalid_time_signature() {
+        let mut map = TempoMap::new(96);
+        assert_eq!(
+            map.push(&set_time_signature(0, 2)),
+            Err(InvalidData(ERR_INVALID_TIME_SIGNATURE)),
+        );
+        assert_eq!(
+            map.set_time_signature(0, time_signature(4, 32)),
+            Err(InvalidData(ERR_INVALID_TIME_SIGNATURE)),
+        );
+    }
+}
@@ -3,28 +3,42 @@ use crate::{
     detail::property,
 };
 
+mod assembler;
 mod device_identity;
+#[cfg(feature = "std")]
+mod discovery;
 mod end_of_clip;
+mod endpoint;
 mod endpoint_discovery;
 mod endpoint_info;
 mod endpoint_name;
 mod function_block_discovery;
 mod function_block_info;
+mod function_block_map;
 mod function_block_name;
+mod negotiator;
 mod packet;
 mod product_instance_id;
 mod start_of_clip;
 mod stream_configuration_notification;
 mod stream_configuration_request;
 
+pub use assembler::UmpStreamAssembler;
 pub use device_identity::*;
+#[cfg(feature = "std")]
+pub use discovery::{DiscoveryClient, DiscoveryStatus, RemoteEndpoint, RemoteFunctionBlock};
 pub use end_of_clip::*;
+pub use endpoint::{
+    Endpoint, EndpointReplies, EndpointResponder, FunctionBlock, Identity, REPLY_SIZE,
+};
 pub use endpoint_discovery::*;
 pub use endpoint_info::*;
 pub use endpoint_name::*;
 pub use function_block_discovery::*;
 pub use function_block_info::*;
+pub use function_block_map::{FunctionBlockMap, GroupFunctionBlocks, Route};
 pub use function_block_name::FunctionBlockName;
+pub use negotiator::{Protocol, StreamCapabilities, StreamConfiguration, StreamNegotiator};
 pub use packet::{Format, Packet};
 pub use product_instance_id::*;
 pub use start_of_clip::*;
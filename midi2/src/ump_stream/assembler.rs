@@ -0,0 +1,423 @@
+use crate::{
+    detail::{helpers::ERR_SYSEX_EXPECTED_BEGIN, BitOps},
+    error::InvalidData,
+    ump_stream::{
+        endpoint_name, function_block_name, product_instance_id, status_from_buffer, Format,
+        Packet, UmpStream,
+    },
+};
+
+/// Reassembles [UmpStream] messages from individual [Packet]s.
+///
+/// The multi packet text messages
+/// ([EndpointName](crate::ump_stream::EndpointName),
+/// [FunctionBlockName](crate::ump_stream::FunctionBlockName)
+/// and [ProductInstanceId](crate::ump_stream::ProductInstanceId))
+/// may be interleaved with other UMP Stream traffic during discovery.
+/// The assembler collects the packets of each text message by status,
+/// and by function block number for function block names,
+/// and produces a message once its final packet has been received.
+/// Single packet messages are produced as soon as they are pushed.
+///
+/// Text longer than the limits of the UMP specification is rejected:
+/// 98 bytes for endpoint names, 91 bytes for function block names
+/// and 42 bytes for product instance ids.
+/// Since the limits are fixed the assembler needs no external buffers.
+///
+/// The text of the assembled messages can be read with
+/// `name` / `id`, or without allocating, with `name_bytes` / `id_bytes`.
+///
+/// ```rust
+/// use midi2::ump_stream::{Packet, UmpStream, UmpStreamAssembler};
+///
+/// let mut assembler = UmpStreamAssembler::new();
+///
+/// let packets = [
+///     [0xF412_0953, 0x796E_7468, 0x5761_7665, 0xF09F_8C8A],
+///     // endpoint info notification
+///     [0xF001_0101, 0x0000_0000, 0x0000_0000, 0x0000_0000],
+///     [0xFC12_09C2, 0xB200_0000, 0x0000_0000, 0x0000_0000],
+/// ];
+///
+/// let mut messages = Vec::new();
+/// for packet in packets {
+///     let packet = Packet::try_from(&packet[..]).expect("Valid packet");
+///     match assembler.push(&packet).expect("Valid stream") {
+///         Some(UmpStream::FunctionBlockName(message)) => {
+///             messages.push(format!("{}: {}", message.function_block(), message.name()))
+///         }
+///         Some(UmpStream::EndpointInfo(_)) => messages.push("endpoint info".to_string()),
+///         _ => {}
+///     }
+/// }
+///
+/// assert_eq!(messages, ["endpoint info", "9: SynthWave🌊²"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UmpStreamAssembler {
+    endpoint_name: TextState,
+    product_instance_id: TextState,
+    function_block_names: [TextState; FUNCTION_BLOCKS],
+    complete: [u32; 4],
+}
+
+// the text limits of the specification are all
+// a whole number of packets
+const ENDPOINT_NAME_MAX_PACKETS: usize = 7;
+const FUNCTION_BLOCK_NAME_MAX_PACKETS: usize = 7;
+const PRODUCT_INSTANCE_ID_MAX_PACKETS: usize = 3;
+const MAX_PACKETS: usize = 7;
+const FUNCTION_BLOCKS: usize = 32;
+
+const ERR_TEXT_TOO_LONG: &str = "Text exceeds the maximum length";
+const ERR_NOT_MULTI_PACKET_STATUS: &str = "Status does not support multi packet messages";
+const ERR_FUNCTION_BLOCK_OUT_OF_RANGE: &str = "Function block number out of range";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct TextState {
+    buffer: [u32; 4 * MAX_PACKETS],
+    size: usize,
+    status: AssemblyStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum AssemblyStatus {
+    #[default]
+    Idle,
+    Assembling,
+    // the text exceeded the limit and the remaining
+    // packets of the message are discarded
+    Discarding,
+}
+
+impl UmpStreamAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop all partially assembled messages.
+    pub fn reset(&mut self) {
+        self.endpoint_name.status = AssemblyStatus::Idle;
+        self.product_instance_id.status = AssemblyStatus::Idle;
+        for state in self.function_block_names.iter_mut() {
+            state.status = AssemblyStatus::Idle;
+        }
+    }
+
+    /// Feed the next packet of the stream into the assembler.
+    ///
+    /// Returns the assembled message when the packet completes a message.
+    ///
+    /// A start packet received while a message with the same status
+    /// (and function block) is being assembled interrupts it
+    /// and the partial message is dropped.
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when
+    /// - a continue or end packet is received without a preceding start packet,
+    /// - a multi packet message is not a text message,
+    /// - the text of a message exceeds the limit of the specification.
+    ///   The error is reported once and the remaining packets
+    ///   of the message are discarded.
+    /// - the assembled message is not a valid [UmpStream] message.
+    pub fn push(&mut self, packet: &Packet) -> Result<Option<UmpStream<&[u32]>>, InvalidData> {
+        let format = packet.format();
+
+        if format == Format::Complete {
+            self.complete = packet.0;
+            return Ok(Some(UmpStream::try_from(&self.complete[..])?));
+        }
+
+        let (state, max_packets) = match status_from_buffer(packet) {
+            endpoint_name::STATUS => (&mut self.endpoint_name, ENDPOINT_NAME_MAX_PACKETS),
+            product_instance_id::STATUS => (
+                &mut self.product_instance_id,
+                PRODUCT_INSTANCE_ID_MAX_PACKETS,
+            ),
+            function_block_name::STATUS => {
+                let function_block = usize::from(packet[0].octet(2));
+                if function_block >= FUNCTION_BLOCKS {
+                    return Err(InvalidData(ERR_FUNCTION_BLOCK_OUT_OF_RANGE));
+                }
+                (
+                    &mut self.function_block_names[function_block],
+                    FUNCTION_BLOCK_NAME_MAX_PACKETS,
+                )
+            }
+            _ => return Err(InvalidData(ERR_NOT_MULTI_PACKET_STATUS)),
+        };
+
+        match (format, state.status) {
+            (Format::Start, _) => {
+                state.status = AssemblyStatus::Assembling;
+                state.size = 0;
+            }
+            (Format::Continue | Format::End, AssemblyStatus::Idle) => {
+                return Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN));
+            }
+            (Format::Continue, AssemblyStatus::Discarding) => return Ok(None),
+            (Format::End, AssemblyStatus::Discarding) => {
+                state.status = AssemblyStatus::Idle;
+                return Ok(None);
+            }
+            _ => {}
+        }
+
+        let is_end = format == Format::End;
+        let size = state.size + 4;
+        if size > 4 * max_packets {
+            state.status = if is_end {
+                AssemblyStatus::Idle
+            } else {
+                AssemblyStatus::Discarding
+            };
+            return Err(InvalidData(ERR_TEXT_TOO_LONG));
+        }
+        state.buffer[state.size..size].copy_from_slice(packet);
+        state.size = size;
+
+        if is_end {
+            state.status = AssemblyStatus::Idle;
+            Ok(Some(UmpStream::try_from(&state.buffer[..size])?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn packet(data: [u32; 4]) -> Packet {
+        Packet::try_from(&data[..]).unwrap()
+    }
+
+    const ENDPOINT_NAME_START: [u32; 4] = [0xF403_4769, 0x6D6D_6520, 0x736F_6D65, 0x2073_6967];
+    const ENDPOINT_NAME_CONTINUE: [u32; 4] = [0xF803_4769, 0x6D6D_6520, 0x736F_6D65, 0x2073_6967];
+    const ENDPOINT_NAME_END: [u32; 4] = [0xFC03_6E61, 0x6C20_F09F, 0x948A_20F0, 0x9F99_8C00];
+    const FUNCTION_BLOCK_NAME_START: [u32; 4] =
+        [0xF412_0953, 0x796E_7468, 0x5761_7665, 0xF09F_8C8A];
+    const FUNCTION_BLOCK_NAME_END: [u32; 4] = [0xFC12_09C2, 0xB200_0000, 0x0000_0000, 0x0000_0000];
+    const PRODUCT_INSTANCE_ID_START: [u32; 4] =
+        [0xF404_5069, 0x616E_6F50, 0x756C_7365, 0x2020_2020];
+    const PRODUCT_INSTANCE_ID_CONTINUE: [u32; 4] =
+        [0xF804_5069, 0x616E_6F50, 0x756C_7365, 0x2020_2020];
+    const PRODUCT_INSTANCE_ID_END: [u32; 4] = [0xFC04_5069, 0x616E_6F50, 0x756C_7365, 0x2020_2020];
+
+    #[test]
+    fn complete_packet() {
+        let mut assembler = UmpStreamAssembler::new();
+        let message = assembler
+            .push(&packet([
+                0xF004_5069,
+                0x616E_6F50,
+                0x756C_7365,
+                0x0000_0000,
+            ]))
+            .unwrap()
+            .unwrap();
+        assert!(matches!(message, UmpStream::ProductInstanceId(_)));
+    }
+
+    #[test]
+    fn single_packet_message() {
+        let mut assembler = UmpStreamAssembler::new();
+        let message = assembler
+            .push(&packet([0xF001_0101, 0x0, 0x0, 0x0]))
+            .unwrap()
+            .unwrap();
+        assert!(matches!(message, UmpStream::EndpointInfo(_)));
+    }
+
+    #[test]
+    fn endpoint_name() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        let message = assembler.push(&packet(ENDPOINT_NAME_END)).unwrap().unwrap();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF403_4769,
+                0x6D6D_6520,
+                0x736F_6D65,
+                0x2073_6967,
+                0xFC03_6E61,
+                0x6C20_F09F,
+                0x948A_20F0,
+                0x9F99_8C00,
+            ][..],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn endpoint_name_text() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        let Some(UmpStream::EndpointName(message)) =
+            assembler.push(&packet(ENDPOINT_NAME_END)).unwrap()
+        else {
+            panic!("Expected an endpoint name");
+        };
+        assert_eq!(message.name(), "Gimme some signal 🔊 🙌");
+    }
+
+    #[test]
+    fn interleaved_text_messages() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        assert_eq!(assembler.push(&packet(FUNCTION_BLOCK_NAME_START)), Ok(None));
+        assert_eq!(assembler.push(&packet(PRODUCT_INSTANCE_ID_START)), Ok(None));
+        assert!(matches!(
+            assembler.push(&packet(FUNCTION_BLOCK_NAME_END)),
+            Ok(Some(UmpStream::FunctionBlockName(_))),
+        ));
+        assert!(matches!(
+            assembler.push(&packet(PRODUCT_INSTANCE_ID_END)),
+            Ok(Some(UmpStream::ProductInstanceId(_))),
+        ));
+        assert!(matches!(
+            assembler.push(&packet(ENDPOINT_NAME_END)),
+            Ok(Some(UmpStream::EndpointName(_))),
+        ));
+    }
+
+    #[test]
+    fn interleaved_function_blocks() {
+        let mut assembler = UmpStreamAssembler::new();
+        let mut start = FUNCTION_BLOCK_NAME_START;
+        let mut end = FUNCTION_BLOCK_NAME_END;
+        start[0] = 0xF412_1F53;
+        end[0] = 0xFC12_1FC2;
+        assert_eq!(assembler.push(&packet(FUNCTION_BLOCK_NAME_START)), Ok(None));
+        assert_eq!(assembler.push(&packet(start)), Ok(None));
+        let Ok(Some(UmpStream::FunctionBlockName(message))) = assembler.push(&packet(end)) else {
+            panic!("Expected a function block name");
+        };
+        assert_eq!(message.function_block(), 0x1F);
+        let Ok(Some(UmpStream::FunctionBlockName(message))) =
+            assembler.push(&packet(FUNCTION_BLOCK_NAME_END))
+        else {
+            panic!("Expected a function block name");
+        };
+        assert_eq!(message.function_block(), 0x09);
+    }
+
+    #[test]
+    fn function_block_out_of_range() {
+        let mut assembler = UmpStreamAssembler::new();
+        let mut start = FUNCTION_BLOCK_NAME_START;
+        start[0] = 0xF412_2053;
+        assert_eq!(
+            assembler.push(&packet(start)),
+            Err(InvalidData(ERR_FUNCTION_BLOCK_OUT_OF_RANGE)),
+        );
+    }
+
+    #[test]
+    fn end_without_start() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(
+            assembler.push(&packet(ENDPOINT_NAME_END)),
+            Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN)),
+        );
+    }
+
+    #[test]
+    fn continue_without_start() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(
+            assembler.push(&packet(ENDPOINT_NAME_CONTINUE)),
+            Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN)),
+        );
+    }
+
+    #[test]
+    fn multi_packet_non_text_message() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(
+            assembler.push(&packet([0xF401_0101, 0x0, 0x0, 0x0])),
+            Err(InvalidData(ERR_NOT_MULTI_PACKET_STATUS)),
+        );
+    }
+
+    #[test]
+    fn start_interrupts_message() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_CONTINUE)), Ok(None));
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        let message = assembler.push(&packet(ENDPOINT_NAME_END)).unwrap().unwrap();
+        assert_eq!(message.data().len(), 8);
+    }
+
+    #[test]
+    fn endpoint_name_max_length() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        for _ in 0..5 {
+            assert_eq!(assembler.push(&packet(ENDPOINT_NAME_CONTINUE)), Ok(None));
+        }
+        let message = assembler.push(&packet(ENDPOINT_NAME_END)).unwrap().unwrap();
+        assert_eq!(message.data().len(), 28);
+    }
+
+    #[test]
+    fn endpoint_name_too_long() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        for _ in 0..6 {
+            assert_eq!(assembler.push(&packet(ENDPOINT_NAME_CONTINUE)), Ok(None));
+        }
+        assert_eq!(
+            assembler.push(&packet(ENDPOINT_NAME_CONTINUE)),
+            Err(InvalidData(ERR_TEXT_TOO_LONG)),
+        );
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_CONTINUE)), Ok(None));
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_END)), Ok(None));
+        // the next message is assembled as usual
+        assert_eq!(assembler.push(&packet(ENDPOINT_NAME_START)), Ok(None));
+        assert!(assembler
+            .push(&packet(ENDPOINT_NAME_END))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn product_instance_id_too_long() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(PRODUCT_INSTANCE_ID_START)), Ok(None));
+        assert_eq!(
+            assembler.push(&packet(PRODUCT_INSTANCE_ID_CONTINUE)),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.push(&packet(PRODUCT_INSTANCE_ID_CONTINUE)),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.push(&packet(PRODUCT_INSTANCE_ID_END)),
+            Err(InvalidData(ERR_TEXT_TOO_LONG)),
+        );
+        // the end packet finishes the discarded message
+        assert_eq!(
+            assembler.push(&packet(PRODUCT_INSTANCE_ID_END)),
+            Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN)),
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut assembler = UmpStreamAssembler::new();
+        assert_eq!(assembler.push(&packet(FUNCTION_BLOCK_NAME_START)), Ok(None));
+        assembler.reset();
+        assert_eq!(
+            assembler.push(&packet(FUNCTION_BLOCK_NAME_END)),
+            Err(InvalidData(ERR_SYSEX_EXPECTED_BEGIN)),
+        );
+    }
+}
@@ -0,0 +1,620 @@
+use crate::{
+    error::InvalidData,
+    ump_stream::{
+        Direction, EndpointDiscovery, FunctionBlockDiscovery, Identity, Midi1Port, Packet,
+        Protocol, StreamConfiguration, UiHint, UmpStream, UmpStreamAssembler,
+    },
+    ux::{u4, u7},
+};
+
+const UMP_VERSION_MAJOR: u8 = 1;
+const UMP_VERSION_MINOR: u8 = 1;
+const ALL_FUNCTION_BLOCKS: u8 = 0xFF;
+const GROUPS: u8 = 16;
+
+const ERR_UNKNOWN_PROTOCOL: &str = "Unknown stream configuration protocol";
+
+/// A function block of a [RemoteEndpoint] as reported by the endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFunctionBlock {
+    pub number: u7,
+    pub active: bool,
+    pub first_group: u4,
+    pub number_of_groups_spanned: u8,
+    pub direction: Direction,
+    pub ui_hint: UiHint,
+    pub midi1_port: Option<Midi1Port>,
+    pub midi_ci_version: u8,
+    pub max_number_of_midi_ci_streams: u8,
+    pub name: Option<std::string::String>,
+}
+
+impl RemoteFunctionBlock {
+    /// The groups spanned by the block.
+    ///
+    /// Spans reaching beyond the last group are cut short.
+    pub fn groups(&self) -> core::ops::Range<u8> {
+        let first = u8::from(self.first_group);
+        first
+            ..first
+                .saturating_add(self.number_of_groups_spanned)
+                .min(GROUPS)
+    }
+}
+
+/// The model of a UMP endpoint built by a [DiscoveryClient]
+/// from the replies of the endpoint.
+///
+/// Replies which have not been received are `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RemoteEndpoint {
+    pub ump_version_major: u8,
+    pub ump_version_minor: u8,
+    pub supports_midi1_protocol: bool,
+    pub supports_midi2_protocol: bool,
+    pub supports_receiving_jr_timestamps: bool,
+    pub supports_sending_jr_timestamps: bool,
+    pub name: Option<std::string::String>,
+    pub product_instance_id: Option<std::string::String>,
+    pub device_identity: Option<Identity>,
+    pub stream_configuration: Option<StreamConfiguration>,
+    pub static_function_blocks: bool,
+    pub number_of_function_blocks: u7,
+    /// The reported function blocks, ordered by number.
+    pub function_blocks: std::vec::Vec<RemoteFunctionBlock>,
+}
+
+impl RemoteEndpoint {
+    pub fn function_block(&self, number: u7) -> Option<&RemoteFunctionBlock> {
+        self.function_blocks
+            .binary_search_by_key(&number, |block| block.number)
+            .ok()
+            .map(|index| &self.function_blocks[index])
+    }
+}
+
+/// The progress of a discovery run by a [DiscoveryClient].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryStatus {
+    /// Replies are outstanding and the deadline has not passed.
+    InProgress,
+    /// Every requested reply has been received.
+    Complete,
+    /// The deadline passed with replies outstanding.
+    Incomplete,
+}
+
+/// Discovers a UMP endpoint from the host side of a UMP stream.
+///
+/// [start](DiscoveryClient::start) produces the
+/// [EndpointDiscovery] and [FunctionBlockDiscovery] requests
+/// to send to the endpoint, asking for every reply.
+/// The packets received from the endpoint are then pushed into the client,
+/// which reassembles the multi packet text messages and records the replies
+/// in a [RemoteEndpoint].
+///
+/// Discovery is complete once every requested reply has been received.
+/// Function block names are optional, so only the info of each
+/// function block is awaited.
+/// The deadline is given in the caller's clock and is only compared
+/// against the times passed to [status](DiscoveryClient::status).
+/// Endpoints need not implement every reply, so after the deadline
+/// the discovery is reported as incomplete, but whatever
+/// was received remains available.
+///
+/// Messages received after discovery, such as the function block info
+/// notifications of endpoints with dynamic function blocks,
+/// keep updating the model.
+///
+/// ```rust
+/// use core::time::Duration;
+/// use midi2::ump_stream::{DiscoveryClient, DiscoveryStatus, Packet};
+///
+/// let mut client = DiscoveryClient::new();
+/// let requests = client.start(Duration::from_millis(500));
+/// // send the requests to the endpoint ...
+/// # let _ = requests;
+///
+/// // endpoint info: UMP 1.1, MIDI 2.0 protocol, no function blocks
+/// let packet = Packet::try_from(&[0xF001_0101, 0x0000_0200, 0x0, 0x0][..]).unwrap();
+/// assert!(client.push(&packet).expect("Valid stream"));
+///
+/// assert!(client.remote().supports_midi2_protocol);
+/// assert_eq!(
+///     client.status(Duration::from_millis(100)),
+///     DiscoveryStatus::InProgress
+/// );
+/// assert_eq!(
+///     client.status(Duration::from_millis(500)),
+///     DiscoveryStatus::Incomplete
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiscoveryClient {
+    remote: RemoteEndpoint,
+    assembler: UmpStreamAssembler,
+    endpoint_info_received: bool,
+    // names received before the info of their function block
+    pending_function_block_names: std::vec::Vec<(u7, std::string::String)>,
+    deadline: Option<core::time::Duration>,
+}
+
+impl DiscoveryClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begin discovery, forgetting the previously discovered endpoint.
+    ///
+    /// Returns the requests to send to the endpoint.
+    pub fn start(&mut self, deadline: core::time::Duration) -> [UmpStream<[u32; 4]>; 2] {
+        *self = DiscoveryClient {
+            deadline: Some(deadline),
+            ..Default::default()
+        };
+
+        let mut endpoint_discovery = EndpointDiscovery::<[u32; 4]>::new();
+        endpoint_discovery.set_ump_version_major(UMP_VERSION_MAJOR);
+        endpoint_discovery.set_ump_version_minor(UMP_VERSION_MINOR);
+        endpoint_discovery.set_request_endpoint_info(true);
+        endpoint_discovery.set_request_device_identity(true);
+        endpoint_discovery.set_request_endpoint_name(true);
+        endpoint_discovery.set_request_product_instance_id(true);
+        endpoint_discovery.set_request_stream_configuration(true);
+
+        let mut function_block_discovery = FunctionBlockDiscovery::<[u32; 4]>::new();
+        function_block_discovery.set_function_block_number(ALL_FUNCTION_BLOCKS);
+        function_block_discovery.set_requesting_function_block_info(true);
+        function_block_discovery.set_requesting_function_block_name(true);
+
+        [endpoint_discovery.into(), function_block_discovery.into()]
+    }
+
+    pub fn remote(&self) -> &RemoteEndpoint {
+        &self.remote
+    }
+
+    /// The progress of the discovery at the given time.
+    ///
+    /// A client which has not been started is incomplete.
+    pub fn status(&self, now: core::time::Duration) -> DiscoveryStatus {
+        if self.is_complete() {
+            DiscoveryStatus::Complete
+        } else if self.deadline.is_some_and(|deadline| now < deadline) {
+            DiscoveryStatus::InProgress
+        } else {
+            DiscoveryStatus::Incomplete
+        }
+    }
+
+    /// Feed the next packet received from the endpoint into the client.
+    ///
+    /// Returns whether the packet changed the [RemoteEndpoint].
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when the packets do not assemble into valid
+    /// [UmpStream] messages (see [UmpStreamAssembler::push])
+    /// or a stream configuration notification reports an unknown protocol.
+    pub fn push(&mut self, packet: &Packet) -> Result<bool, InvalidData> {
+        let Some(message) = self.assembler.push(packet)? else {
+            return Ok(false);
+        };
+        let remote = &mut self.remote;
+        let changed = match message {
+            UmpStream::EndpointInfo(message) => {
+                self.endpoint_info_received = true;
+                let mut changed = false;
+                changed |= update(&mut remote.ump_version_major, message.ump_version_major());
+                changed |= update(&mut remote.ump_version_minor, message.ump_version_minor());
+                changed |= update(
+                    &mut remote.supports_midi1_protocol,
+                    message.supports_midi1_protocol(),
+                );
+                changed |= update(
+                    &mut remote.supports_midi2_protocol,
+                    message.supports_midi2_protocol(),
+                );
+                changed |= update(
+                    &mut remote.supports_receiving_jr_timestamps,
+                    message.supports_receiving_jr_timestamps(),
+                );
+                changed |= update(
+                    &mut remote.supports_sending_jr_timestamps,
+                    message.supports_sending_jr_timestamps(),
+                );
+                changed |= update(
+                    &mut remote.static_function_blocks,
+                    message.static_function_blocks(),
+                );
+                changed |= update(
+                    &mut remote.number_of_function_blocks,
+                    message.number_of_function_blocks(),
+                );
+                changed
+            }
+            UmpStream::DeviceIdentity(message) => update(
+                &mut remote.device_identity,
+                Some(Identity {
+                    manufacturer: message.device_manufacturer(),
+                    family: message.device_family(),
+                    family_model_number: message.device_family_model_number(),
+                    software_version: message.software_version(),
+                }),
+            ),
+            UmpStream::EndpointName(message) => update(&mut remote.name, Some(message.name())),
+            UmpStream::ProductInstanceId(message) => {
+                update(&mut remote.product_instance_id, Some(message.id()))
+            }
+            UmpStream::StreamConfigurationNotification(message) => update(
+                &mut remote.stream_configuration,
+                Some(StreamConfiguration {
+                    protocol: Protocol::from_value(message.protocol())
+                        .ok_or(InvalidData(ERR_UNKNOWN_PROTOCOL))?,
+                    receive_jr_timestamps: message.receive_jr_timestamps(),
+                    send_jr_timestamps: message.send_jr_timestamps(),
+                }),
+            ),
+            UmpStream::FunctionBlockInfo(message) => {
+                let number = message.function_block_number();
+                let mut block = RemoteFunctionBlock {
+                    number,
+                    active: message.active(),
+                    first_group: message.first_group(),
+                    number_of_groups_spanned: message.number_of_groups_spanned(),
+                    direction: message.direction(),
+                    ui_hint: message.ui_hint(),
+                    midi1_port: message.midi1_port(),
+                    midi_ci_version: message.midi_ci_version(),
+                    max_number_of_midi_ci_streams: message.max_number_of_midi_ci_streams(),
+                    name: None,
+                };
+                match remote
+                    .function_blocks
+                    .binary_search_by_key(&number, |block| block.number)
+                {
+                    Ok(index) => {
+                        let existing = &mut remote.function_blocks[index];
+                        block.name = existing.name.clone();
+                        update(existing, block)
+                    }
+                    Err(index) => {
+                        if let Some(pending) = self
+                            .pending_function_block_names
+                            .iter()
+                            .position(|(n, _)| *n == number)
+                        {
+                            block.name = Some(self.pending_function_block_names.remove(pending).1);
+                        }
+                        remote.function_blocks.insert(index, block);
+                        true
+                    }
+                }
+            }
+            UmpStream::FunctionBlockName(message) => {
+                let number = u7::new(message.function_block() & 0x7F);
+                match remote
+                    .function_blocks
+                    .binary_search_by_key(&number, |block| block.number)
+                {
+                    Ok(index) => update(
+                        &mut remote.function_blocks[index].name,
+                        Some(message.name()),
+                    ),
+                    Err(_) => {
+                        self.pending_function_block_names
+                            .retain(|(n, _)| *n != number);
+                        self.pending_function_block_names
+                            .push((number, message.name()));
+                        false
+                    }
+                }
+            }
+            _ => false,
+        };
+        Ok(changed)
+    }
+
+    fn is_complete(&self) -> bool {
+        let remote = &self.remote;
+        self.endpoint_info_received
+            && remote.device_identity.is_some()
+            && remote.name.is_some()
+            && remote.product_instance_id.is_some()
+            && remote.stream_configuration.is_some()
+            && (0..u8::from(remote.number_of_function_blocks))
+                .all(|number| remote.function_block(u7::new(number)).is_some())
+    }
+}
+
+fn update<T: PartialEq>(field: &mut T, value: T) -> bool {
+    if *field == value {
+        false
+    } else {
+        *field = value;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        traits::Data,
+        ump_stream::{Endpoint, EndpointResponder, FunctionBlock, FunctionBlockInfo},
+        ux::u14,
+    };
+    use core::time::Duration;
+    use pretty_assertions::assert_eq;
+
+    const DEADLINE: Duration = Duration::from_millis(500);
+
+    static FUNCTION_BLOCKS: [FunctionBlock; 2] = [
+        FunctionBlock {
+            active: true,
+            first_group: u4::new(0),
+            number_of_groups_spanned: 2,
+            direction: Direction::Output,
+            ui_hint: UiHint::Sender,
+            midi1_port: None,
+            midi_ci_version: 0x2,
+            max_number_of_midi_ci_streams: 0x1,
+            name: Some("SynthWave🌊²"),
+        },
+        FunctionBlock {
+            active: false,
+            first_group: u4::new(15),
+            number_of_groups_spanned: 1,
+            direction: Direction::Input,
+            ui_hint: UiHint::Receiver,
+            midi1_port: Some(Midi1Port::RestrictBandwidth),
+            midi_ci_version: 0x0,
+            max_number_of_midi_ci_streams: 0x0,
+            name: Some("Gimme some signal 🔊 🙌"),
+        },
+    ];
+
+    const IDENTITY: Identity = Identity {
+        manufacturer: [u7::new(0x0F), u7::new(0x33), u7::new(0x28)],
+        family: u14::new(0xF4A),
+        family_model_number: u14::new(0x3818),
+        software_version: [u7::new(0x43), u7::new(0x54), u7::new(0x32), u7::new(0x1)],
+    };
+
+    fn endpoint() -> Endpoint<'static> {
+        Endpoint {
+            supports_midi1_protocol: true,
+            supports_sending_jr_timestamps: true,
+            name: Some("GeoRes"),
+            product_instance_id: Some("PianoPulse"),
+            device_identity: Some(IDENTITY),
+            function_blocks: &FUNCTION_BLOCKS,
+            ..Default::default()
+        }
+    }
+
+    fn packets<B: crate::buffer::Ump>(message: &UmpStream<B>) -> std::vec::Vec<Packet> {
+        message
+            .data()
+            .chunks_exact(4)
+            .map(|packet| Packet::try_from(packet).unwrap())
+            .collect()
+    }
+
+    fn discover(client: &mut DiscoveryClient, endpoint: Endpoint) {
+        let mut responder = EndpointResponder::new(endpoint).unwrap();
+        for request in client.start(DEADLINE) {
+            let replies: std::vec::Vec<_> = responder.push(&request).collect();
+            for reply in replies {
+                for packet in packets(&reply) {
+                    client.push(&packet).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn requests() {
+        let mut client = DiscoveryClient::new();
+        assert_eq!(
+            client
+                .start(DEADLINE)
+                .map(|request| request.data().to_vec()),
+            [
+                std::vec![0xF000_0101, 0x0000_001F, 0x0, 0x0],
+                std::vec![0xF010_FF03],
+            ],
+        );
+    }
+
+    #[test]
+    fn discovers_endpoint() {
+        let mut client = DiscoveryClient::new();
+        discover(&mut client, endpoint());
+        assert_eq!(
+            client.remote(),
+            &RemoteEndpoint {
+                ump_version_major: 1,
+                ump_version_minor: 1,
+                supports_midi1_protocol: true,
+                supports_midi2_protocol: true,
+                supports_receiving_jr_timestamps: false,
+                supports_sending_jr_timestamps: true,
+                name: Some("GeoRes".into()),
+                product_instance_id: Some("PianoPulse".into()),
+                device_identity: Some(IDENTITY),
+                stream_configuration: Some(StreamConfiguration {
+                    protocol: Protocol::Midi2,
+                    receive_jr_timestamps: false,
+                    send_jr_timestamps: false,
+                }),
+                static_function_blocks: false,
+                number_of_function_blocks: u7::new(2),
+                function_blocks: std::vec![
+                    RemoteFunctionBlock {
+                        number: u7::new(0),
+                        active: true,
+                        first_group: u4::new(0),
+                        number_of_groups_spanned: 2,
+                        direction: Direction::Output,
+                        ui_hint: UiHint::Sender,
+                        midi1_port: None,
+                        midi_ci_version: 0x2,
+                        max_number_of_midi_ci_streams: 0x1,
+                        name: Some("SynthWave🌊²".into()),
+                    },
+                    RemoteFunctionBlock {
+                        number: u7::new(1),
+                        active: false,
+                        first_group: u4::new(15),
+                        number_of_groups_spanned: 1,
+                        direction: Direction::Input,
+                        ui_hint: UiHint::Receiver,
+                        midi1_port: Some(Midi1Port::RestrictBandwidth),
+                        midi_ci_version: 0x0,
+                        max_number_of_midi_ci_streams: 0x0,
+                        name: Some("Gimme some signal 🔊 🙌".into()),
+                    },
+                ],
+            },
+        );
+        assert_eq!(client.status(Duration::ZERO), DiscoveryStatus::Complete);
+        assert_eq!(client.status(DEADLINE), DiscoveryStatus::Complete);
+    }
+
+    #[test]
+    fn incomplete_after_deadline() {
+        let mut client = DiscoveryClient::new();
+        discover(
+            &mut client,
+            Endpoint {
+                name: None,
+                ..endpoint()
+            },
+        );
+        assert_eq!(client.remote().name, None);
+        assert_eq!(client.remote().function_blocks.len(), 2);
+        assert_eq!(
+            client.status(Duration::from_millis(499)),
+            DiscoveryStatus::InProgress
+        );
+        assert_eq!(client.status(DEADLINE), DiscoveryStatus::Incomplete);
+    }
+
+    #[test]
+    fn incomplete_without_function_blocks() {
+        let mut client = DiscoveryClient::new();
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        let [endpoint_discovery, _] = client.start(DEADLINE);
+        let replies: std::vec::Vec<_> = responder.push(&endpoint_discovery).collect();
+        for reply in replies {
+            for packet in packets(&reply) {
+                client.push(&packet).unwrap();
+            }
+        }
+        assert_eq!(client.status(DEADLINE), DiscoveryStatus::Incomplete);
+    }
+
+    #[test]
+    fn not_started() {
+        assert_eq!(
+            DiscoveryClient::new().status(Duration::ZERO),
+            DiscoveryStatus::Incomplete
+        );
+    }
+
+    #[test]
+    fn start_forgets_previous_endpoint() {
+        let mut client = DiscoveryClient::new();
+        discover(&mut client, endpoint());
+        client.start(DEADLINE);
+        assert_eq!(client.remote(), &RemoteEndpoint::default());
+        assert_eq!(client.status(Duration::ZERO), DiscoveryStatus::InProgress);
+    }
+
+    #[test]
+    fn follows_function_block_updates() {
+        let mut client = DiscoveryClient::new();
+        discover(&mut client, endpoint());
+
+        let mut update = FunctionBlockInfo::<[u32; 4]>::new();
+        update.set_active(true);
+        update.set_function_block_number(u7::new(1));
+        update.set_first_group(u4::new(14));
+        update.set_number_of_groups_spanned(2);
+        update.set_direction(Direction::Bidirectional);
+        let update = UmpStream::from(update);
+
+        assert!(client.push(&packets(&update)[0]).unwrap());
+        assert!(!client.push(&packets(&update)[0]).unwrap());
+
+        let block = client.remote().function_block(u7::new(1)).unwrap();
+        assert!(block.active);
+        assert_eq!(block.direction, Direction::Bidirectional);
+        assert_eq!(block.groups(), 14..16);
+        assert_eq!(block.name.as_deref(), Some("Gimme some signal 🔊 🙌"));
+        assert_eq!(client.status(DEADLINE), DiscoveryStatus::Complete);
+    }
+
+    #[test]
+    fn function_block_name_before_info() {
+        let mut client = DiscoveryClient::new();
+        client.start(DEADLINE);
+        for packet in [
+            [0xF412_0053, 0x796E_7468, 0x5761_7665, 0xF09F_8C8A],
+            [0xFC12_00C2, 0xB200_0000, 0x0000_0000, 0x0000_0000],
+        ] {
+            assert!(!client
+                .push(&Packet::try_from(&packet[..]).unwrap())
+                .unwrap());
+        }
+        let info = Packet::try_from(&[0xF011_8022, 0x0002_0201, 0x0, 0x0][..]).unwrap();
+        assert!(client.push(&info).unwrap());
+        assert_eq!(
+            client.remote().function_blocks[0].name.as_deref(),
+            Some("SynthWave🌊²")
+        );
+    }
+
+    #[test]
+    fn completes_with_unnamed_function_block() {
+        let mut client = DiscoveryClient::new();
+        discover(
+            &mut client,
+            Endpoint {
+                function_blocks: &[FunctionBlock {
+                    name: None,
+                    ..FUNCTION_BLOCKS[0]
+                }],
+                ..endpoint()
+            },
+        );
+        assert_eq!(client.remote().function_blocks.len(), 1);
+        assert_eq!(client.remote().function_blocks[0].name, None);
+        assert_eq!(client.status(Duration::ZERO), DiscoveryStatus::Complete);
+    }
+
+    #[test]
+    fn unknown_protocol() {
+        let mut client = DiscoveryClient::new();
+        let packet = Packet::try_from(&[0xF006_0700, 0x0, 0x0, 0x0][..]).unwrap();
+        assert_eq!(client.push(&packet), Err(InvalidData(ERR_UNKNOWN_PROTOCOL)));
+    }
+
+    #[test]
+    fn groups_beyond_last_group_are_cut_short() {
+        let block = RemoteFunctionBlock {
+            number: u7::new(0),
+            active: true,
+            first_group: u4::new(12),
+            number_of_groups_spanned: 8,
+            direction: Direction::Input,
+            ui_hint: UiHint::Undeclared,
+            midi1_port: None,
+            midi_ci_version: 0,
+            max_number_of_midi_ci_streams: 0,
+            name: None,
+        };
+        assert_eq!(block.groups(), 12..16);
+    }
+}
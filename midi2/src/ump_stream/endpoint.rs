@@ -0,0 +1,808 @@
+use crate::{
+    error::InvalidData,
+    ump_stream::{
+        DeviceIdentity, Direction, EndpointInfo, EndpointName, FunctionBlockInfo,
+        FunctionBlockName, Midi1Port, ProductInstanceId, StreamConfiguration,
+        StreamConfigurationNotification, StreamNegotiator, UiHint, UmpStream,
+    },
+    ux::{u14, u4, u7},
+};
+
+/// The size of the buffers of the replies produced by an [EndpointResponder].
+///
+/// Large enough for the longest text message allowed by the UMP specification.
+pub const REPLY_SIZE: usize = 28;
+
+const MAX_ENDPOINT_NAME_LENGTH: usize = 98;
+const MAX_PRODUCT_INSTANCE_ID_LENGTH: usize = 42;
+const MAX_FUNCTION_BLOCK_NAME_LENGTH: usize = 91;
+const MAX_FUNCTION_BLOCKS: usize = 32;
+const GROUPS: usize = 16;
+const ALL_FUNCTION_BLOCKS: u8 = 0xFF;
+
+const ERR_NO_PROTOCOL: &str = "Endpoint must support at least one protocol";
+const ERR_ENDPOINT_NAME_TOO_LONG: &str = "Endpoint name exceeds 98 bytes";
+const ERR_PRODUCT_INSTANCE_ID_TOO_LONG: &str = "Product instance id exceeds 42 bytes";
+const ERR_FUNCTION_BLOCK_NAME_TOO_LONG: &str = "Function block name exceeds 91 bytes";
+const ERR_TOO_MANY_FUNCTION_BLOCKS: &str = "Endpoint exceeds 32 function blocks";
+const ERR_INVALID_GROUP_SPAN: &str = "Function block must span between 1 and 16 groups";
+
+const REPLY_ENDPOINT_INFO: u8 = 0b0000_0001;
+const REPLY_DEVICE_IDENTITY: u8 = 0b0000_0010;
+const REPLY_ENDPOINT_NAME: u8 = 0b0000_0100;
+const REPLY_PRODUCT_INSTANCE_ID: u8 = 0b0000_1000;
+const REPLY_STREAM_CONFIGURATION: u8 = 0b0001_0000;
+
+const REPLY_FUNCTION_BLOCK_INFO: u8 = 0b0000_0001;
+const REPLY_FUNCTION_BLOCK_NAME: u8 = 0b0000_0010;
+
+/// The device identity reported by an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Identity {
+    pub manufacturer: [u7; 3],
+    pub family: u14,
+    pub family_model_number: u14,
+    pub software_version: [u7; 4],
+}
+
+/// The description of a function block of an [Endpoint].
+///
+/// The function block number is the index of the block
+/// in [Endpoint::function_blocks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FunctionBlock<'a> {
+    pub active: bool,
+    pub first_group: u4,
+    pub number_of_groups_spanned: u8,
+    pub direction: Direction,
+    pub ui_hint: UiHint,
+    pub midi1_port: Option<Midi1Port>,
+    pub midi_ci_version: u8,
+    pub max_number_of_midi_ci_streams: u8,
+    pub name: Option<&'a str>,
+}
+
+impl Default for FunctionBlock<'_> {
+    /// An active, unnamed, bidirectional block on group 0.
+    fn default() -> Self {
+        FunctionBlock {
+            active: true,
+            first_group: u4::new(0),
+            number_of_groups_spanned: 1,
+            direction: Direction::Bidirectional,
+            ui_hint: UiHint::Undeclared,
+            midi1_port: None,
+            midi_ci_version: 0,
+            max_number_of_midi_ci_streams: 0,
+            name: None,
+        }
+    }
+}
+
+/// The description of a UMP endpoint,
+/// as reported to hosts during endpoint discovery.
+///
+/// Optional fields which are `None` are not reported:
+/// discovery requests for them receive no reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Endpoint<'a> {
+    pub ump_version_major: u8,
+    pub ump_version_minor: u8,
+    pub supports_midi1_protocol: bool,
+    pub supports_midi2_protocol: bool,
+    pub supports_receiving_jr_timestamps: bool,
+    pub supports_sending_jr_timestamps: bool,
+    pub name: Option<&'a str>,
+    pub product_instance_id: Option<&'a str>,
+    pub device_identity: Option<Identity>,
+    pub static_function_blocks: bool,
+    pub function_blocks: &'a [FunctionBlock<'a>],
+}
+
+impl Default for Endpoint<'_> {
+    /// A UMP 1.1 endpoint supporting only the MIDI 2.0 protocol.
+    fn default() -> Self {
+        Endpoint {
+            ump_version_major: 1,
+            ump_version_minor: 1,
+            supports_midi1_protocol: false,
+            supports_midi2_protocol: true,
+            supports_receiving_jr_timestamps: false,
+            supports_sending_jr_timestamps: false,
+            name: None,
+            product_instance_id: None,
+            device_identity: None,
+            static_function_blocks: false,
+            function_blocks: &[],
+        }
+    }
+}
+
+impl Endpoint<'_> {
+    fn validate(&self) -> Result<(), InvalidData> {
+        if !self.supports_midi1_protocol && !self.supports_midi2_protocol {
+            return Err(InvalidData(ERR_NO_PROTOCOL));
+        }
+        if self
+            .name
+            .is_some_and(|n| n.len() > MAX_ENDPOINT_NAME_LENGTH)
+        {
+            return Err(InvalidData(ERR_ENDPOINT_NAME_TOO_LONG));
+        }
+        if self
+            .product_instance_id
+            .is_some_and(|id| id.len() > MAX_PRODUCT_INSTANCE_ID_LENGTH)
+        {
+            return Err(InvalidData(ERR_PRODUCT_INSTANCE_ID_TOO_LONG));
+        }
+        if self.function_blocks.len() > MAX_FUNCTION_BLOCKS {
+            return Err(InvalidData(ERR_TOO_MANY_FUNCTION_BLOCKS));
+        }
+        for block in self.function_blocks {
+            let span = usize::from(block.number_of_groups_spanned);
+            if span == 0 || usize::from(u8::from(block.first_group)) + span > GROUPS {
+                return Err(InvalidData(ERR_INVALID_GROUP_SPAN));
+            }
+            if block
+                .name
+                .is_some_and(|n| n.len() > MAX_FUNCTION_BLOCK_NAME_LENGTH)
+            {
+                return Err(InvalidData(ERR_FUNCTION_BLOCK_NAME_TOO_LONG));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Answers the discovery and stream configuration requests
+/// received by a UMP endpoint.
+///
+/// The responder is driven by a declarative [Endpoint] description.
+/// Each message pushed into the responder produces an iterator over
+/// the replies to send back to the host.
+/// Only the replies selected by the filter flags of the
+/// [EndpointDiscovery](crate::ump_stream::EndpointDiscovery) and
+/// [FunctionBlockDiscovery](crate::ump_stream::FunctionBlockDiscovery)
+/// requests are produced.
+///
+/// A [StreamConfigurationRequest](crate::ump_stream::StreamConfigurationRequest)
+/// switches to the requested protocol and jitter reduction settings where
+/// the endpoint supports them (see [StreamNegotiator])
+/// and the responder replies with the resulting configuration.
+///
+/// The replies are built in fixed size buffers,
+/// so the responder does not allocate.
+///
+/// ```rust
+/// use midi2::prelude::*;
+/// use midi2::ump_stream::{
+///     Endpoint, EndpointDiscovery, EndpointResponder, FunctionBlock, UmpStream,
+/// };
+///
+/// let function_blocks = [FunctionBlock {
+///     name: Some("Synth"),
+///     ..Default::default()
+/// }];
+/// let mut responder = EndpointResponder::new(Endpoint {
+///     name: Some("SynthWave"),
+///     function_blocks: &function_blocks,
+///     ..Default::default()
+/// })
+/// .expect("Valid endpoint");
+///
+/// let mut request = EndpointDiscovery::<[u32; 4]>::new();
+/// request.set_ump_version_major(1);
+/// request.set_ump_version_minor(1);
+/// request.set_request_endpoint_info(true);
+/// request.set_request_endpoint_name(true);
+///
+/// let mut replies = responder.push(&request.into());
+/// let Some(UmpStream::EndpointInfo(info)) = replies.next() else {
+///     panic!("Expected endpoint info");
+/// };
+/// assert_eq!(info.number_of_function_blocks(), u7::new(1));
+/// let Some(UmpStream::EndpointName(name)) = replies.next() else {
+///     panic!("Expected endpoint name");
+/// };
+/// assert_eq!(name.name(), "SynthWave");
+/// assert_eq!(replies.next(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointResponder<'a> {
+    endpoint: Endpoint<'a>,
+    negotiator: StreamNegotiator,
+}
+
+impl<'a> EndpointResponder<'a> {
+    /// Create a responder for the described endpoint.
+    ///
+    /// The initial configuration uses the MIDI 2.0 protocol when
+    /// supported and no jitter reduction timestamps.
+    ///
+    /// Fails when the description cannot be reported within the limits
+    /// of the UMP specification or does not support any protocol.
+    pub fn new(endpoint: Endpoint<'a>) -> Result<Self, InvalidData> {
+        endpoint.validate()?;
+        Ok(EndpointResponder {
+            negotiator: StreamNegotiator::new((&endpoint).into()),
+            endpoint,
+        })
+    }
+
+    pub fn endpoint(&self) -> &Endpoint<'a> {
+        &self.endpoint
+    }
+
+    /// The stream configuration currently in use.
+    pub fn configuration(&self) -> StreamConfiguration {
+        self.negotiator.active()
+    }
+
+    /// Handle a message received from the host.
+    ///
+    /// Messages which are not requests to the endpoint produce no replies.
+    pub fn push<B: crate::buffer::Ump>(
+        &mut self,
+        message: &UmpStream<B>,
+    ) -> EndpointReplies<'_, 'a> {
+        let mut replies = EndpointReplies {
+            endpoint: &self.endpoint,
+            configuration: self.negotiator.active(),
+            pending: 0,
+            function_blocks: 0..0,
+            function_block_filter: 0,
+            function_block_pending: 0,
+        };
+        match message {
+            UmpStream::EndpointDiscovery(request) => {
+                for (requested, reply) in [
+                    (request.request_endpoint_info(), REPLY_ENDPOINT_INFO),
+                    (request.request_device_identity(), REPLY_DEVICE_IDENTITY),
+                    (request.request_endpoint_name(), REPLY_ENDPOINT_NAME),
+                    (
+                        request.request_product_instance_id(),
+                        REPLY_PRODUCT_INSTANCE_ID,
+                    ),
+                    (
+                        request.request_stream_configuration(),
+                        REPLY_STREAM_CONFIGURATION,
+                    ),
+                ] {
+                    if requested {
+                        replies.pending |= reply;
+                    }
+                }
+            }
+            UmpStream::FunctionBlockDiscovery(request) => {
+                let number_of_blocks = self.endpoint.function_blocks.len();
+                replies.function_blocks = match request.function_block_number() {
+                    ALL_FUNCTION_BLOCKS => 0..number_of_blocks,
+                    n if usize::from(n) < number_of_blocks => usize::from(n)..usize::from(n) + 1,
+                    _ => 0..0,
+                };
+                if request.requesting_function_block_info() {
+                    replies.function_block_filter |= REPLY_FUNCTION_BLOCK_INFO;
+                }
+                if request.requesting_function_block_name() {
+                    replies.function_block_filter |= REPLY_FUNCTION_BLOCK_NAME;
+                }
+                replies.function_block_pending = replies.function_block_filter;
+            }
+            UmpStream::StreamConfigurationRequest(_) => {
+                self.negotiator.push(message);
+                replies.configuration = self.negotiator.active();
+                replies.pending = REPLY_STREAM_CONFIGURATION;
+            }
+            _ => {}
+        }
+        replies
+    }
+}
+
+/// The replies to a message pushed into an [EndpointResponder].
+///
+/// Endpoint level replies are produced in the order
+/// endpoint info, device identity, endpoint name, product instance id
+/// and stream configuration, followed by the function block info
+/// and name of each requested function block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointReplies<'r, 'a> {
+    endpoint: &'r Endpoint<'a>,
+    configuration: StreamConfiguration,
+    pending: u8,
+    function_blocks: core::ops::Range<usize>,
+    function_block_filter: u8,
+    function_block_pending: u8,
+}
+
+impl EndpointReplies<'_, '_> {
+    fn endpoint_reply(&self, reply: u8) -> Option<UmpStream<[u32; REPLY_SIZE]>> {
+        let endpoint = self.endpoint;
+        match reply {
+            REPLY_ENDPOINT_INFO => {
+                let mut message = EndpointInfo::<[u32; REPLY_SIZE]>::new();
+                message.set_ump_version_major(endpoint.ump_version_major);
+                message.set_ump_version_minor(endpoint.ump_version_minor);
+                message.set_static_function_blocks(endpoint.static_function_blocks);
+                message.set_supports_midi1_protocol(endpoint.supports_midi1_protocol);
+                message.set_supports_midi2_protocol(endpoint.supports_midi2_protocol);
+                message.set_supports_receiving_jr_timestamps(
+                    endpoint.supports_receiving_jr_timestamps,
+                );
+                message.set_supports_sending_jr_timestamps(endpoint.supports_sending_jr_timestamps);
+                message
+                    .set_number_of_function_blocks(u7::new(endpoint.function_blocks.len() as u8));
+                Some(message.into())
+            }
+            REPLY_DEVICE_IDENTITY => endpoint.device_identity.map(|identity| {
+                let mut message = DeviceIdentity::<[u32; REPLY_SIZE]>::new();
+                message.set_device_manufacturer(identity.manufacturer);
+                message.set_device_family(identity.family);
+                message.set_device_family_model_number(identity.family_model_number);
+                message.set_software_version(identity.software_version);
+                message.into()
+            }),
+            REPLY_ENDPOINT_NAME => endpoint.name.map(|name| {
+                let mut message = EndpointName::<[u32; REPLY_SIZE]>::new();
+                message
+                    .try_set_name(name)
+                    .expect("Name length is validated by the responder");
+                message.into()
+            }),
+            REPLY_PRODUCT_INSTANCE_ID => endpoint.product_instance_id.map(|id| {
+                let mut message = ProductInstanceId::<[u32; REPLY_SIZE]>::new();
+                message
+                    .try_set_id(id)
+                    .expect("Id length is validated by the responder");
+                message.into()
+            }),
+            REPLY_STREAM_CONFIGURATION => {
+                let mut message = StreamConfigurationNotification::<[u32; REPLY_SIZE]>::new();
+                message.set_protocol(self.configuration.protocol.value());
+                message.set_receive_jr_timestamps(self.configuration.receive_jr_timestamps);
+                message.set_send_jr_timestamps(self.configuration.send_jr_timestamps);
+                Some(message.into())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn function_block_reply(
+        &self,
+        index: usize,
+        reply: u8,
+    ) -> Option<UmpStream<[u32; REPLY_SIZE]>> {
+        let block = &self.endpoint.function_blocks[index];
+        match reply {
+            REPLY_FUNCTION_BLOCK_INFO => {
+                let mut message = FunctionBlockInfo::<[u32; REPLY_SIZE]>::new();
+                message.set_active(block.active);
+                message.set_function_block_number(u7::new(index as u8));
+                message.set_first_group(block.first_group);
+                message.set_number_of_groups_spanned(block.number_of_groups_spanned);
+                message.set_midi_ci_version(block.midi_ci_version);
+                message.set_max_number_of_midi_ci_streams(block.max_number_of_midi_ci_streams);
+                message.set_ui_hint(block.ui_hint);
+                message.set_midi1_port(block.midi1_port);
+                message.set_direction(block.direction);
+                Some(message.into())
+            }
+            REPLY_FUNCTION_BLOCK_NAME => block.name.map(|name| {
+                let mut message = FunctionBlockName::<[u32; REPLY_SIZE]>::new();
+                message.set_function_block(index as u8);
+                message
+                    .try_set_name(name)
+                    .expect("Name length is validated by the responder");
+                message.into()
+            }),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Iterator for EndpointReplies<'_, '_> {
+    type Item = UmpStream<[u32; REPLY_SIZE]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending != 0 {
+            let reply = self.pending & self.pending.wrapping_neg();
+            self.pending &= !reply;
+            if let Some(message) = self.endpoint_reply(reply) {
+                return Some(message);
+            }
+        }
+        while !self.function_blocks.is_empty() {
+            let index = self.function_blocks.start;
+            while self.function_block_pending != 0 {
+                let reply =
+                    self.function_block_pending & self.function_block_pending.wrapping_neg();
+                self.function_block_pending &= !reply;
+                if let Some(message) = self.function_block_reply(index, reply) {
+                    return Some(message);
+                }
+            }
+            self.function_blocks.start += 1;
+            self.function_block_pending = self.function_block_filter;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        traits::Data,
+        ump_stream::{
+            EndpointDiscovery, FunctionBlockDiscovery, Protocol, StreamConfigurationRequest,
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    static FUNCTION_BLOCKS: [FunctionBlock; 2] = [
+        FunctionBlock {
+            active: true,
+            first_group: u4::new(0),
+            number_of_groups_spanned: 2,
+            direction: Direction::Output,
+            ui_hint: UiHint::Sender,
+            midi1_port: None,
+            midi_ci_version: 0x2,
+            max_number_of_midi_ci_streams: 0x1,
+            name: Some("SynthWave🌊²"),
+        },
+        FunctionBlock {
+            active: false,
+            first_group: u4::new(2),
+            number_of_groups_spanned: 1,
+            direction: Direction::Input,
+            ui_hint: UiHint::Receiver,
+            midi1_port: Some(Midi1Port::DontRestrictBandwidth),
+            midi_ci_version: 0x0,
+            max_number_of_midi_ci_streams: 0x0,
+            name: None,
+        },
+    ];
+
+    fn endpoint() -> Endpoint<'static> {
+        Endpoint {
+            ump_version_major: 1,
+            ump_version_minor: 1,
+            supports_midi1_protocol: true,
+            supports_midi2_protocol: true,
+            supports_receiving_jr_timestamps: true,
+            supports_sending_jr_timestamps: false,
+            name: Some("GeoRes"),
+            product_instance_id: Some("PianoPulse"),
+            device_identity: Some(Identity {
+                manufacturer: [u7::new(0x0F), u7::new(0x33), u7::new(0x28)],
+                family: u14::new(0xF4A),
+                family_model_number: u14::new(0x3818),
+                software_version: [u7::new(0x43), u7::new(0x54), u7::new(0x32), u7::new(0x1)],
+            }),
+            static_function_blocks: true,
+            function_blocks: &FUNCTION_BLOCKS,
+        }
+    }
+
+    fn endpoint_discovery(filter: u8) -> UmpStream<[u32; 4]> {
+        let mut message = EndpointDiscovery::<[u32; 4]>::new();
+        message.set_ump_version_major(1);
+        message.set_ump_version_minor(1);
+        message.set_request_endpoint_info(filter & REPLY_ENDPOINT_INFO != 0);
+        message.set_request_device_identity(filter & REPLY_DEVICE_IDENTITY != 0);
+        message.set_request_endpoint_name(filter & REPLY_ENDPOINT_NAME != 0);
+        message.set_request_product_instance_id(filter & REPLY_PRODUCT_INSTANCE_ID != 0);
+        message.set_request_stream_configuration(filter & REPLY_STREAM_CONFIGURATION != 0);
+        message.into()
+    }
+
+    fn function_block_discovery(number: u8, info: bool, name: bool) -> UmpStream<[u32; 4]> {
+        let mut message = FunctionBlockDiscovery::<[u32; 4]>::new();
+        message.set_function_block_number(number);
+        message.set_requesting_function_block_info(info);
+        message.set_requesting_function_block_name(name);
+        message.into()
+    }
+
+    fn configuration_request(
+        protocol: u8,
+        receive_jr_timestamps: bool,
+        send_jr_timestamps: bool,
+    ) -> UmpStream<[u32; 4]> {
+        let mut message = StreamConfigurationRequest::<[u32; 4]>::new();
+        message.set_protocol(protocol);
+        message.set_receive_jr_timestamps(receive_jr_timestamps);
+        message.set_send_jr_timestamps(send_jr_timestamps);
+        message.into()
+    }
+
+    fn replies<B: crate::buffer::Ump>(
+        responder: &mut EndpointResponder,
+        message: &UmpStream<B>,
+    ) -> std::vec::Vec<std::vec::Vec<u32>> {
+        responder
+            .push(message)
+            .map(|reply| reply.data().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn endpoint_discovery_all() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &endpoint_discovery(0b1_1111)),
+            std::vec![
+                std::vec![0xF001_0101, 0x8200_0302, 0x0, 0x0],
+                std::vec![0xF002_0000, 0x000F_3328, 0x4A1E_1870, 0x4354_3201],
+                std::vec![0xF003_4765, 0x6F52_6573, 0x0, 0x0],
+                std::vec![0xF004_5069, 0x616E_6F50, 0x756C_7365, 0x0],
+                std::vec![0xF006_0200, 0x0, 0x0, 0x0],
+            ],
+        );
+    }
+
+    #[test]
+    fn endpoint_discovery_honors_filter() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(
+                &mut responder,
+                &endpoint_discovery(REPLY_ENDPOINT_NAME | REPLY_STREAM_CONFIGURATION)
+            ),
+            std::vec![
+                std::vec![0xF003_4765, 0x6F52_6573, 0x0, 0x0],
+                std::vec![0xF006_0200, 0x0, 0x0, 0x0],
+            ],
+        );
+    }
+
+    #[test]
+    fn endpoint_discovery_without_filter() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &endpoint_discovery(0)),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn endpoint_discovery_skips_undescribed_fields() {
+        let mut responder = EndpointResponder::new(Endpoint::default()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &endpoint_discovery(0b1_1111)),
+            std::vec![
+                std::vec![0xF001_0101, 0x0000_0200, 0x0, 0x0],
+                std::vec![0xF006_0200, 0x0, 0x0, 0x0],
+            ],
+        );
+    }
+
+    #[test]
+    fn endpoint_name_spanning_packets() {
+        let mut responder = EndpointResponder::new(Endpoint {
+            name: Some("Gimme some signal 🔊 🙌"),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            replies(&mut responder, &endpoint_discovery(REPLY_ENDPOINT_NAME)),
+            std::vec![std::vec![
+                0xF403_4769,
+                0x6D6D_6520,
+                0x736F_6D65,
+                0x2073_6967,
+                0xFC03_6E61,
+                0x6C20_F09F,
+                0x948A_20F0,
+                0x9F99_8C00,
+            ]],
+        );
+    }
+
+    #[test]
+    fn function_block_discovery_all() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &function_block_discovery(0xFF, true, true)),
+            std::vec![
+                std::vec![0xF011_8022, 0x0002_0201, 0x0, 0x0],
+                std::vec![
+                    0xF412_0053,
+                    0x796E_7468,
+                    0x5761_7665,
+                    0xF09F_8C8A,
+                    0xFC12_00C2,
+                    0xB200_0000,
+                    0x0000_0000,
+                    0x0000_0000,
+                ],
+                std::vec![0xF011_0115, 0x0201_0000, 0x0, 0x0],
+            ],
+        );
+    }
+
+    #[test]
+    fn function_block_discovery_single_block() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &function_block_discovery(0x1, true, true)),
+            std::vec![std::vec![0xF011_0115, 0x0201_0000, 0x0, 0x0]],
+        );
+    }
+
+    #[test]
+    fn function_block_discovery_honors_filter() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &function_block_discovery(0xFF, true, false)),
+            std::vec![
+                std::vec![0xF011_8022, 0x0002_0201, 0x0, 0x0],
+                std::vec![0xF011_0115, 0x0201_0000, 0x0, 0x0],
+            ],
+        );
+    }
+
+    #[test]
+    fn function_block_discovery_out_of_range() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &function_block_discovery(0x2, true, true)),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn stream_configuration_request() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &configuration_request(0x1, true, false)),
+            std::vec![std::vec![0xF006_0102, 0x0, 0x0, 0x0]],
+        );
+        assert_eq!(
+            responder.configuration(),
+            StreamConfiguration {
+                protocol: Protocol::Midi1,
+                receive_jr_timestamps: true,
+                send_jr_timestamps: false,
+            },
+        );
+    }
+
+    #[test]
+    fn stream_configuration_request_unsupported_settings() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        assert_eq!(
+            replies(&mut responder, &configuration_request(0x7, false, true)),
+            std::vec![std::vec![0xF006_0200, 0x0, 0x0, 0x0]],
+        );
+        assert_eq!(responder.configuration(), StreamConfiguration::default());
+    }
+
+    #[test]
+    fn configuration_defaults_to_midi1_without_midi2_support() {
+        let responder = EndpointResponder::new(Endpoint {
+            supports_midi1_protocol: true,
+            supports_midi2_protocol: false,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(responder.configuration().protocol, Protocol::Midi1);
+    }
+
+    #[test]
+    fn unrelated_messages_have_no_replies() {
+        let mut responder = EndpointResponder::new(endpoint()).unwrap();
+        let message: UmpStream<[u32; 4]> =
+            StreamConfigurationNotification::<[u32; 4]>::new().into();
+        assert_eq!(
+            replies(&mut responder, &message),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn invalid_endpoints() {
+        let function_blocks = [FunctionBlock::default(); 33];
+        let span_overflow = [FunctionBlock {
+            first_group: u4::new(15),
+            number_of_groups_spanned: 2,
+            ..Default::default()
+        }];
+        let empty_span = [FunctionBlock {
+            number_of_groups_spanned: 0,
+            ..Default::default()
+        }];
+        let long_name = [FunctionBlock {
+            name: Some(&"x".repeat(92)),
+            ..Default::default()
+        }];
+        for (endpoint, error) in [
+            (
+                Endpoint {
+                    supports_midi2_protocol: false,
+                    ..Default::default()
+                },
+                ERR_NO_PROTOCOL,
+            ),
+            (
+                Endpoint {
+                    name: Some(&"x".repeat(99)),
+                    ..Default::default()
+                },
+                ERR_ENDPOINT_NAME_TOO_LONG,
+            ),
+            (
+                Endpoint {
+                    product_instance_id: Some(&"x".repeat(43)),
+                    ..Default::default()
+                },
+                ERR_PRODUCT_INSTANCE_ID_TOO_LONG,
+            ),
+            (
+                Endpoint {
+                    function_blocks: &function_blocks,
+                    ..Default::default()
+                },
+                ERR_TOO_MANY_FUNCTION_BLOCKS,
+            ),
+            (
+                Endpoint {
+                    function_blocks: &span_overflow,
+                    ..Default::default()
+                },
+                ERR_INVALID_GROUP_SPAN,
+            ),
+            (
+                Endpoint {
+                    function_blocks: &empty_span,
+                    ..Default::default()
+                },
+                ERR_INVALID_GROUP_SPAN,
+            ),
+            (
+                Endpoint {
+                    function_blocks: &long_name,
+                    ..Default::default()
+                },
+                ERR_FUNCTION_BLOCK_NAME_TOO_LONG,
+            ),
+        ] {
+            assert_eq!(EndpointResponder::new(endpoint), Err(InvalidData(error)));
+        }
+    }
+
+    #[test]
+    fn longest_texts_fit_reply_buffers() {
+        let endpoint_name = "x".repeat(98);
+        let product_instance_id = "x".repeat(42);
+        let function_block_name = "x".repeat(91);
+        let function_blocks = [FunctionBlock {
+            name: Some(&function_block_name),
+            ..Default::default()
+        }];
+        let mut responder = EndpointResponder::new(Endpoint {
+            name: Some(&endpoint_name),
+            product_instance_id: Some(&product_instance_id),
+            function_blocks: &function_blocks,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            responder
+                .push(&endpoint_discovery(
+                    REPLY_ENDPOINT_NAME | REPLY_PRODUCT_INSTANCE_ID
+                ))
+                .map(|reply| reply.data().len())
+                .collect::<std::vec::Vec<_>>(),
+            std::vec![28, 12],
+        );
+        assert_eq!(
+            responder
+                .push(&function_block_discovery(0xFF, false, true))
+                .map(|reply| reply.data().len())
+                .collect::<std::vec::Vec<_>>(),
+            std::vec![28],
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{
+    error::InvalidData,
+    traits::ArrayRebufferInto,
+    ump_stream::{Direction, FunctionBlockInfo},
+    ux::u4,
+    UmpMessage,
+};
+
+const FUNCTION_BLOCKS: usize = 32;
+const GROUPS: usize = 16;
+
+const ERR_FUNCTION_BLOCK_OUT_OF_RANGE: &str = "Function block number out of range";
+const ERR_INVALID_GROUP_SPAN: &str = "Function block must span between 1 and 16 groups";
+const ERR_OVERLAPPING_FUNCTION_BLOCKS: &str =
+    "Function block overlaps the groups of another block in the same direction";
+
+/// The function blocks targeted by a [UmpMessage], as found by [FunctionBlockMap::route].
+#[derive(Debug, Clone)]
+pub enum Route<'a> {
+    /// The message has no group and belongs to the whole endpoint:
+    /// [UmpStream](crate::ump_stream::UmpStream) and
+    /// [Utility](crate::utility::Utility) messages.
+    Groupless,
+    /// The active function blocks spanning the group of the message.
+    FunctionBlocks(GroupFunctionBlocks<'a>),
+}
+
+/// Iterates over the active function blocks of a [FunctionBlockMap]
+/// spanning a group, in order of function block number.
+#[derive(Debug, Clone)]
+pub struct GroupFunctionBlocks<'a> {
+    blocks: core::slice::Iter<'a, Option<FunctionBlockInfo<[u32; 4]>>>,
+    group: u4,
+}
+
+impl<'a> Iterator for GroupFunctionBlocks<'a> {
+    type Item = &'a FunctionBlockInfo<[u32; 4]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let group = self.group;
+        self.blocks
+            .by_ref()
+            .flatten()
+            .find(|block| block.active() && groups(block).contains(&usize::from(u8::from(group))))
+    }
+}
+
+/// Routes the traffic of a UMP stream to the function blocks
+/// of an endpoint by group.
+///
+/// The map is built from the [FunctionBlockInfo] messages of the endpoint.
+/// Inserting a block with the number of a block already in the map
+/// replaces it, so the map can follow the updates of endpoints
+/// with dynamic function blocks.
+///
+/// Blocks are validated as they are inserted: they must span a valid range
+/// of groups and the groups of active blocks may only overlap when one
+/// of the blocks is an input and the other an output,
+/// as their traffic flows in opposite directions.
+///
+/// ```rust
+/// use midi2::prelude::*;
+/// use midi2::ump_stream::{Direction, FunctionBlockInfo, FunctionBlockMap, Route};
+///
+/// let mut info = FunctionBlockInfo::<[u32; 4]>::new();
+/// info.set_active(true);
+/// info.set_function_block_number(u7::new(0x3));
+/// info.set_first_group(u4::new(0x2));
+/// info.set_number_of_groups_spanned(2);
+/// info.set_direction(Direction::Bidirectional);
+///
+/// let mut map = FunctionBlockMap::new();
+/// map.insert(info).expect("Valid function block");
+///
+/// let mut message = channel_voice2::NoteOn::<[u32; 4]>::new();
+/// message.set_group(u4::new(0x3));
+/// let Route::FunctionBlocks(mut blocks) = map.route(&message.into()) else {
+///     panic!("Expected a grouped message");
+/// };
+/// assert_eq!(blocks.next().map(|b| b.function_block_number()), Some(u7::new(0x3)));
+/// assert_eq!(blocks.next(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FunctionBlockMap {
+    blocks: [Option<FunctionBlockInfo<[u32; 4]>>; FUNCTION_BLOCKS],
+}
+
+impl FunctionBlockMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a function block to the map, replacing any block with the same number.
+    ///
+    /// # Fails
+    ///
+    /// With [InvalidData] when
+    /// - the function block number is 32 or greater,
+    /// - the block spans no groups or spans beyond the last group,
+    /// - the block is active and overlaps the groups of another active block
+    ///   without one being an input and the other an output.
+    ///
+    /// The map is unchanged on failure.
+    pub fn insert<B: crate::buffer::Ump>(
+        &mut self,
+        info: FunctionBlockInfo<B>,
+    ) -> Result<(), InvalidData> {
+        let info: FunctionBlockInfo<[u32; 4]> = info.array_rebuffer_into();
+        let number = usize::from(u8::from(info.function_block_number()));
+        if number >= FUNCTION_BLOCKS {
+            return Err(InvalidData(ERR_FUNCTION_BLOCK_OUT_OF_RANGE));
+        }
+        let span = usize::from(info.number_of_groups_spanned());
+        if span == 0 || usize::from(u8::from(info.first_group())) + span > GROUPS {
+            return Err(InvalidData(ERR_INVALID_GROUP_SPAN));
+        }
+        if info.active() {
+            let overlapping = self
+                .blocks
+                .iter()
+                .enumerate()
+                .filter(|(other_number, _)| *other_number != number)
+                .filter_map(|(_, other)| other.as_ref())
+                .filter(|other| other.active())
+                .any(|other| {
+                    let (a, b) = (groups(&info), groups(other));
+                    a.start < b.end
+                        && b.start < a.end
+                        && !matches!(
+                            (info.direction(), other.direction()),
+                            (Direction::Input, Direction::Output)
+                                | (Direction::Output, Direction::Input)
+                        )
+                });
+            if overlapping {
+                return Err(InvalidData(ERR_OVERLAPPING_FUNCTION_BLOCKS));
+            }
+        }
+        self.blocks[number] = Some(info);
+        Ok(())
+    }
+
+    /// Remove the function block with the given number from the map.
+    pub fn remove(&mut self, number: u8) -> Option<FunctionBlockInfo<[u32; 4]>> {
+        self.blocks.get_mut(usize::from(number))?.take()
+    }
+
+    pub fn get(&self, number: u8) -> Option<&FunctionBlockInfo<[u32; 4]>> {
+        self.blocks.get(usize::from(number))?.as_ref()
+    }
+
+    /// Iterates over the blocks of the map in order of function block number.
+    pub fn iter(&self) -> impl Iterator<Item = &FunctionBlockInfo<[u32; 4]>> {
+        self.blocks.iter().flatten()
+    }
+
+    /// The active function blocks spanning the group.
+    pub fn group(&self, group: u4) -> GroupFunctionBlocks<'_> {
+        GroupFunctionBlocks {
+            blocks: self.blocks.iter(),
+            group,
+        }
+    }
+
+    /// The function blocks targeted by the message.
+    pub fn route<B: crate::buffer::Ump>(&self, message: &UmpMessage<B>) -> Route<'_> {
+        let group = match message {
+            #[cfg(feature = "flex-data")]
+            UmpMessage::FlexData(m) => Some(crate::Grouped::group(m)),
+            #[cfg(feature = "channel-voice1")]
+            UmpMessage::ChannelVoice1(m) => Some(crate::Grouped::group(m)),
+            #[cfg(feature = "channel-voice2")]
+            UmpMessage::ChannelVoice2(m) => Some(crate::Grouped::group(m)),
+            #[cfg(feature = "sysex7")]
+            UmpMessage::Sysex7(m) => Some(crate::Grouped::group(m)),
+            #[cfg(feature = "sysex8")]
+            UmpMessage::Sysex8(m) => Some(crate::Grouped::group(m)),
+            #[cfg(feature = "system-common")]
+            UmpMessage::SystemCommon(m) => Some(crate::Grouped::group(m)),
+            UmpMessage::UmpStream(_) => None,
+            #[cfg(feature = "utility")]
+            UmpMessage::Utility(_) => None,
+        };
+        match group {
+            Some(group) => Route::FunctionBlocks(self.group(group)),
+            None => Route::Groupless,
+        }
+    }
+}
+
+fn groups(info: &FunctionBlockInfo<[u32; 4]>) -> core::ops::Range<usize> {
+    let first = usize::from(u8::from(info.first_group()));
+    first..first + usize::from(info.number_of_groups_spanned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ump_stream::{EndpointDiscovery, UmpStream},
+        ux::u7,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn info(
+        number: u8,
+        first_group: u8,
+        number_of_groups_spanned: u8,
+        direction: Direction,
+    ) -> FunctionBlockInfo<[u32; 4]> {
+        let mut message = FunctionBlockInfo::<[u32; 4]>::new();
+        message.set_active(true);
+        message.set_function_block_number(u7::new(number));
+        message.set_first_group(u4::new(first_group));
+        message.set_number_of_groups_spanned(number_of_groups_spanned);
+        message.set_direction(direction);
+        message
+    }
+
+    fn map() -> FunctionBlockMap {
+        let mut map = FunctionBlockMap::new();
+        map.insert(info(0, 0, 4, Direction::Bidirectional)).unwrap();
+        map.insert(info(1, 4, 2, Direction::Input)).unwrap();
+        map.insert(info(2, 4, 1, Direction::Output)).unwrap();
+        map
+    }
+
+    fn numbers(blocks: GroupFunctionBlocks) -> std::vec::Vec<u8> {
+        blocks
+            .map(|block| u8::from(block.function_block_number()))
+            .collect()
+    }
+
+    #[test]
+    fn group() {
+        let map = map();
+        assert_eq!(numbers(map.group(u4::new(0))), [0]);
+        assert_eq!(numbers(map.group(u4::new(3))), [0]);
+        assert_eq!(numbers(map.group(u4::new(4))), [1, 2]);
+        assert_eq!(numbers(map.group(u4::new(5))), [1]);
+        assert_eq!(numbers(map.group(u4::new(6))), std::vec::Vec::<u8>::new());
+    }
+
+    #[test]
+    fn inactive_blocks_are_not_targeted() {
+        let mut map = map();
+        let mut inactive = info(1, 4, 2, Direction::Input);
+        inactive.set_active(false);
+        map.insert(inactive).unwrap();
+        assert_eq!(numbers(map.group(u4::new(4))), [2]);
+    }
+
+    #[test]
+    fn insert_replaces_block() {
+        let mut map = map();
+        map.insert(info(0, 8, 1, Direction::Output)).unwrap();
+        assert_eq!(numbers(map.group(u4::new(0))), std::vec::Vec::<u8>::new());
+        assert_eq!(numbers(map.group(u4::new(8))), [0]);
+        assert_eq!(map.iter().count(), 3);
+    }
+
+    #[test]
+    fn overlapping_blocks() {
+        let mut map = map();
+        assert_eq!(
+            map.insert(info(3, 3, 2, Direction::Output)),
+            Err(InvalidData(ERR_OVERLAPPING_FUNCTION_BLOCKS)),
+        );
+        assert_eq!(
+            map.insert(info(3, 5, 1, Direction::Input)),
+            Err(InvalidData(ERR_OVERLAPPING_FUNCTION_BLOCKS)),
+        );
+        assert_eq!(map.get(3), None);
+    }
+
+    #[test]
+    fn overlapping_inactive_block() {
+        let mut map = map();
+        let mut inactive = info(3, 0, 16, Direction::Bidirectional);
+        inactive.set_active(false);
+        assert_eq!(map.insert(inactive), Ok(()));
+    }
+
+    #[test]
+    fn invalid_group_span() {
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(
+            map.insert(info(0, 15, 2, Direction::Input)),
+            Err(InvalidData(ERR_INVALID_GROUP_SPAN)),
+        );
+        assert_eq!(
+            map.insert(info(0, 0, 0, Direction::Input)),
+            Err(InvalidData(ERR_INVALID_GROUP_SPAN)),
+        );
+    }
+
+    #[test]
+    fn function_block_out_of_range() {
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(
+            map.insert(info(32, 0, 1, Direction::Input)),
+            Err(InvalidData(ERR_FUNCTION_BLOCK_OUT_OF_RANGE)),
+        );
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = map();
+        assert_eq!(map.remove(1), Some(info(1, 4, 2, Direction::Input)));
+        assert_eq!(map.remove(1), None);
+        assert_eq!(map.remove(40), None);
+        assert_eq!(numbers(map.group(u4::new(5))), std::vec::Vec::<u8>::new());
+    }
+
+    #[test]
+    fn route_ump_stream_message() {
+        let message: UmpMessage<[u32; 4]> =
+            UmpStream::from(EndpointDiscovery::<[u32; 4]>::new()).into();
+        assert!(matches!(map().route(&message), Route::Groupless));
+    }
+
+    #[test]
+    #[cfg(feature = "utility")]
+    fn route_utility_message() {
+        let message: UmpMessage<[u32; 4]> = crate::utility::NoOp::<[u32; 4]>::new().into();
+        assert!(matches!(map().route(&message), Route::Groupless));
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn route_grouped_message() {
+        use crate::Grouped;
+
+        let mut message = crate::channel_voice2::NoteOn::<[u32; 4]>::new();
+        message.set_group(u4::new(0x4));
+        let map = map();
+        let Route::FunctionBlocks(blocks) = map.route(&message.into()) else {
+            panic!("Expected a grouped message");
+        };
+        assert_eq!(numbers(blocks), [1, 2]);
+    }
+}
@@ -0,0 +1,533 @@
+use crate::ump_stream::{
+    Endpoint, EndpointInfo, StreamConfigurationNotification, StreamConfigurationRequest, UmpStream,
+};
+
+/// The protocol of the channel voice messages exchanged over a UMP stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    Midi1,
+    #[default]
+    Midi2,
+}
+
+impl Protocol {
+    /// The value of the protocol field of stream configuration messages.
+    pub fn value(&self) -> u8 {
+        match self {
+            Protocol::Midi1 => 0x01,
+            Protocol::Midi2 => 0x02,
+        }
+    }
+
+    /// The protocol for the value of the protocol field
+    /// of stream configuration messages.
+    pub fn from_value(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(Protocol::Midi1),
+            0x02 => Some(Protocol::Midi2),
+            _ => None,
+        }
+    }
+}
+
+/// The protocol and jitter reduction timestamp settings in use on a UMP stream.
+///
+/// The jitter reduction flags are from the point of view of one side
+/// of the stream:
+/// `receive_jr_timestamps` means it expects to receive timestamps,
+/// `send_jr_timestamps` means it sends them.
+/// The stream configuration messages carry the settings
+/// from the point of view of the endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StreamConfiguration {
+    pub protocol: Protocol,
+    pub receive_jr_timestamps: bool,
+    pub send_jr_timestamps: bool,
+}
+
+impl StreamConfiguration {
+    // the same settings from the point of view of the other side
+    fn reversed(self) -> Self {
+        StreamConfiguration {
+            protocol: self.protocol,
+            receive_jr_timestamps: self.send_jr_timestamps,
+            send_jr_timestamps: self.receive_jr_timestamps,
+        }
+    }
+}
+
+/// The stream configuration capabilities of one side of a UMP stream,
+/// as declared in its [EndpointInfo].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StreamCapabilities {
+    pub supports_midi1_protocol: bool,
+    pub supports_midi2_protocol: bool,
+    pub supports_receiving_jr_timestamps: bool,
+    pub supports_sending_jr_timestamps: bool,
+}
+
+impl StreamCapabilities {
+    /// Whether the protocol can be used.
+    pub fn supports_protocol(&self, protocol: Protocol) -> bool {
+        match protocol {
+            Protocol::Midi1 => self.supports_midi1_protocol,
+            Protocol::Midi2 => self.supports_midi2_protocol,
+        }
+    }
+}
+
+impl<B: crate::buffer::Ump> From<&EndpointInfo<B>> for StreamCapabilities {
+    fn from(message: &EndpointInfo<B>) -> Self {
+        StreamCapabilities {
+            supports_midi1_protocol: message.supports_midi1_protocol(),
+            supports_midi2_protocol: message.supports_midi2_protocol(),
+            supports_receiving_jr_timestamps: message.supports_receiving_jr_timestamps(),
+            supports_sending_jr_timestamps: message.supports_sending_jr_timestamps(),
+        }
+    }
+}
+
+impl From<&Endpoint<'_>> for StreamCapabilities {
+    fn from(endpoint: &Endpoint<'_>) -> Self {
+        StreamCapabilities {
+            supports_midi1_protocol: endpoint.supports_midi1_protocol,
+            supports_midi2_protocol: endpoint.supports_midi2_protocol,
+            supports_receiving_jr_timestamps: endpoint.supports_receiving_jr_timestamps,
+            supports_sending_jr_timestamps: endpoint.supports_sending_jr_timestamps,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&crate::ump_stream::RemoteEndpoint> for StreamCapabilities {
+    fn from(endpoint: &crate::ump_stream::RemoteEndpoint) -> Self {
+        StreamCapabilities {
+            supports_midi1_protocol: endpoint.supports_midi1_protocol,
+            supports_midi2_protocol: endpoint.supports_midi2_protocol,
+            supports_receiving_jr_timestamps: endpoint.supports_receiving_jr_timestamps,
+            supports_sending_jr_timestamps: endpoint.supports_sending_jr_timestamps,
+        }
+    }
+}
+
+/// Negotiates the stream configuration of one side of a UMP stream.
+///
+/// The negotiator is created with the capabilities of the local side.
+/// The capabilities of the remote side are learnt from its [EndpointInfo]
+/// (or given with [set_remote](StreamNegotiator::set_remote)), after which
+/// the [agreed](StreamNegotiator::agreed) configuration is the most capable
+/// one supported by both sides: the MIDI 2.0 protocol is preferred over
+/// MIDI 1.0 and jitter reduction timestamps are enabled in each direction
+/// where the sender can send and the receiver can receive them.
+///
+/// On the host side the [request](StreamNegotiator::request) for the agreed
+/// configuration is sent to the endpoint and the
+/// [StreamConfigurationNotification] of the endpoint is pushed back
+/// into the negotiator to switch the active configuration.
+///
+/// On the endpoint side pushing a [StreamConfigurationRequest] switches to
+/// the requested settings the endpoint supports and produces the
+/// notification to reply with.
+///
+/// The [active](StreamNegotiator::active) configuration is always from the
+/// point of view of the local side, so it tells directly which protocol
+/// outgoing traffic should be translated to and whether to timestamp it.
+///
+/// ```rust
+/// use midi2::prelude::*;
+/// use midi2::ump_stream::{
+///     EndpointInfo, Protocol, StreamCapabilities, StreamConfiguration,
+///     StreamNegotiator, UmpStream,
+/// };
+///
+/// let host = StreamCapabilities {
+///     supports_midi1_protocol: true,
+///     supports_midi2_protocol: true,
+///     supports_receiving_jr_timestamps: true,
+///     supports_sending_jr_timestamps: true,
+/// };
+/// let mut endpoint = StreamNegotiator::new(StreamCapabilities {
+///     supports_midi1_protocol: true,
+///     supports_sending_jr_timestamps: true,
+///     ..Default::default()
+/// });
+/// let mut host = StreamNegotiator::new(host);
+///
+/// // the host learns the capabilities of the endpoint
+/// let mut info = EndpointInfo::<[u32; 4]>::new();
+/// info.set_supports_midi1_protocol(true);
+/// info.set_supports_sending_jr_timestamps(true);
+/// host.push(&UmpStream::from(info));
+///
+/// // and requests the agreed configuration
+/// let request = host.request().expect("Compatible capabilities");
+/// let notification = endpoint.push(&request.into()).expect("Reply to request");
+/// host.push(&notification.into());
+///
+/// assert_eq!(
+///     host.active(),
+///     StreamConfiguration {
+///         protocol: Protocol::Midi1,
+///         receive_jr_timestamps: true,
+///         send_jr_timestamps: false,
+///     }
+/// );
+/// assert_eq!(
+///     endpoint.active(),
+///     StreamConfiguration {
+///         protocol: Protocol::Midi1,
+///         receive_jr_timestamps: false,
+///         send_jr_timestamps: true,
+///     }
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamNegotiator {
+    local: StreamCapabilities,
+    remote: Option<StreamCapabilities>,
+    active: StreamConfiguration,
+}
+
+impl StreamNegotiator {
+    /// Create a negotiator for the local capabilities.
+    ///
+    /// The initial active configuration uses the MIDI 2.0 protocol when
+    /// supported, MIDI 1.0 otherwise, and no jitter reduction timestamps.
+    pub fn new(local: StreamCapabilities) -> Self {
+        let protocol = if local.supports_midi2_protocol || !local.supports_midi1_protocol {
+            Protocol::Midi2
+        } else {
+            Protocol::Midi1
+        };
+        StreamNegotiator {
+            local,
+            remote: None,
+            active: StreamConfiguration {
+                protocol,
+                receive_jr_timestamps: false,
+                send_jr_timestamps: false,
+            },
+        }
+    }
+
+    pub fn local(&self) -> StreamCapabilities {
+        self.local
+    }
+
+    pub fn remote(&self) -> Option<StreamCapabilities> {
+        self.remote
+    }
+
+    pub fn set_remote(&mut self, remote: StreamCapabilities) {
+        self.remote = Some(remote);
+    }
+
+    /// The configuration in use, from the point of view of the local side.
+    pub fn active(&self) -> StreamConfiguration {
+        self.active
+    }
+
+    /// The most capable configuration supported by both sides,
+    /// from the point of view of the local side.
+    ///
+    /// `None` when the remote capabilities are unknown
+    /// or the sides have no protocol in common.
+    pub fn agreed(&self) -> Option<StreamConfiguration> {
+        let local = self.local;
+        let remote = self.remote?;
+        let protocol = [Protocol::Midi2, Protocol::Midi1]
+            .into_iter()
+            .find(|&p| local.supports_protocol(p) && remote.supports_protocol(p))?;
+        Some(StreamConfiguration {
+            protocol,
+            receive_jr_timestamps: local.supports_receiving_jr_timestamps
+                && remote.supports_sending_jr_timestamps,
+            send_jr_timestamps: local.supports_sending_jr_timestamps
+                && remote.supports_receiving_jr_timestamps,
+        })
+    }
+
+    /// The request a host sends to switch the endpoint
+    /// to the [agreed](StreamNegotiator::agreed) configuration.
+    pub fn request(&self) -> Option<StreamConfigurationRequest<[u32; 4]>> {
+        let configuration = self.agreed()?.reversed();
+        let mut message = StreamConfigurationRequest::<[u32; 4]>::new();
+        message.set_protocol(configuration.protocol.value());
+        message.set_receive_jr_timestamps(configuration.receive_jr_timestamps);
+        message.set_send_jr_timestamps(configuration.send_jr_timestamps);
+        Some(message)
+    }
+
+    /// The notification an endpoint sends to report its active configuration.
+    pub fn notification(&self) -> StreamConfigurationNotification<[u32; 4]> {
+        let mut message = StreamConfigurationNotification::<[u32; 4]>::new();
+        message.set_protocol(self.active.protocol.value());
+        message.set_receive_jr_timestamps(self.active.receive_jr_timestamps);
+        message.set_send_jr_timestamps(self.active.send_jr_timestamps);
+        message
+    }
+
+    /// Handle a message received from the remote side.
+    ///
+    /// - An [EndpointInfo] sets the remote capabilities.
+    /// - A [StreamConfigurationRequest] switches to the requested settings
+    ///   supported locally. Unsupported protocols leave the protocol
+    ///   unchanged and unsupported timestamp settings are turned off.
+    ///   Returns the notification to reply with.
+    /// - A [StreamConfigurationNotification] with a known protocol
+    ///   sets the active configuration.
+    pub fn push<B: crate::buffer::Ump>(
+        &mut self,
+        message: &UmpStream<B>,
+    ) -> Option<StreamConfigurationNotification<[u32; 4]>> {
+        match message {
+            UmpStream::EndpointInfo(info) => {
+                self.remote = Some(info.into());
+                None
+            }
+            UmpStream::StreamConfigurationRequest(request) => {
+                if let Some(protocol) = Protocol::from_value(request.protocol()) {
+                    if self.local.supports_protocol(protocol) {
+                        self.active.protocol = protocol;
+                    }
+                }
+                self.active.receive_jr_timestamps =
+                    request.receive_jr_timestamps() && self.local.supports_receiving_jr_timestamps;
+                self.active.send_jr_timestamps =
+                    request.send_jr_timestamps() && self.local.supports_sending_jr_timestamps;
+                Some(self.notification())
+            }
+            UmpStream::StreamConfigurationNotification(notification) => {
+                if let Some(protocol) = Protocol::from_value(notification.protocol()) {
+                    self.active = StreamConfiguration {
+                        protocol,
+                        receive_jr_timestamps: notification.receive_jr_timestamps(),
+                        send_jr_timestamps: notification.send_jr_timestamps(),
+                    }
+                    .reversed();
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::Data;
+    use pretty_assertions::assert_eq;
+
+    const FULL: StreamCapabilities = StreamCapabilities {
+        supports_midi1_protocol: true,
+        supports_midi2_protocol: true,
+        supports_receiving_jr_timestamps: true,
+        supports_sending_jr_timestamps: true,
+    };
+
+    const MIDI1_ONLY: StreamCapabilities = StreamCapabilities {
+        supports_midi1_protocol: true,
+        supports_midi2_protocol: false,
+        supports_receiving_jr_timestamps: false,
+        supports_sending_jr_timestamps: false,
+    };
+
+    fn negotiator(local: StreamCapabilities, remote: StreamCapabilities) -> StreamNegotiator {
+        let mut negotiator = StreamNegotiator::new(local);
+        negotiator.set_remote(remote);
+        negotiator
+    }
+
+    fn request(
+        protocol: u8,
+        receive_jr_timestamps: bool,
+        send_jr_timestamps: bool,
+    ) -> UmpStream<[u32; 4]> {
+        let mut message = StreamConfigurationRequest::<[u32; 4]>::new();
+        message.set_protocol(protocol);
+        message.set_receive_jr_timestamps(receive_jr_timestamps);
+        message.set_send_jr_timestamps(send_jr_timestamps);
+        message.into()
+    }
+
+    #[test]
+    fn initial_configuration() {
+        assert_eq!(
+            StreamNegotiator::new(FULL).active(),
+            StreamConfiguration::default()
+        );
+        assert_eq!(
+            StreamNegotiator::new(MIDI1_ONLY).active().protocol,
+            Protocol::Midi1
+        );
+    }
+
+    #[test]
+    fn agreed_prefers_midi2() {
+        assert_eq!(
+            negotiator(FULL, FULL).agreed(),
+            Some(StreamConfiguration {
+                protocol: Protocol::Midi2,
+                receive_jr_timestamps: true,
+                send_jr_timestamps: true,
+            })
+        );
+    }
+
+    #[test]
+    fn agreed_falls_back_to_midi1() {
+        assert_eq!(
+            negotiator(FULL, MIDI1_ONLY).agreed(),
+            Some(StreamConfiguration {
+                protocol: Protocol::Midi1,
+                receive_jr_timestamps: false,
+                send_jr_timestamps: false,
+            })
+        );
+    }
+
+    #[test]
+    fn agreed_timestamps_need_sender_and_receiver() {
+        assert_eq!(
+            negotiator(
+                FULL,
+                StreamCapabilities {
+                    supports_sending_jr_timestamps: true,
+                    ..MIDI1_ONLY
+                }
+            )
+            .agreed(),
+            Some(StreamConfiguration {
+                protocol: Protocol::Midi1,
+                receive_jr_timestamps: true,
+                send_jr_timestamps: false,
+            })
+        );
+    }
+
+    #[test]
+    fn agreed_without_common_protocol() {
+        assert_eq!(
+            negotiator(
+                MIDI1_ONLY,
+                StreamCapabilities {
+                    supports_midi2_protocol: true,
+                    ..Default::default()
+                }
+            )
+            .agreed(),
+            None
+        );
+    }
+
+    #[test]
+    fn agreed_without_remote() {
+        assert_eq!(StreamNegotiator::new(FULL).agreed(), None);
+        assert_eq!(StreamNegotiator::new(FULL).request(), None);
+    }
+
+    #[test]
+    fn remote_from_endpoint_info() {
+        let mut info = EndpointInfo::<[u32; 4]>::new();
+        info.set_supports_midi1_protocol(true);
+        info.set_supports_receiving_jr_timestamps(true);
+        let mut negotiator = StreamNegotiator::new(FULL);
+        assert_eq!(negotiator.push(&UmpStream::from(info)), None);
+        assert_eq!(
+            negotiator.remote(),
+            Some(StreamCapabilities {
+                supports_receiving_jr_timestamps: true,
+                ..MIDI1_ONLY
+            })
+        );
+    }
+
+    #[test]
+    fn request_is_from_the_endpoint_point_of_view() {
+        let negotiator = negotiator(
+            StreamCapabilities {
+                supports_receiving_jr_timestamps: false,
+                ..FULL
+            },
+            FULL,
+        );
+        assert_eq!(
+            negotiator.request().unwrap().data(),
+            &[0xF005_0202, 0x0, 0x0, 0x0],
+        );
+    }
+
+    #[test]
+    fn accepts_supported_request() {
+        let mut negotiator = StreamNegotiator::new(FULL);
+        assert_eq!(
+            negotiator.push(&request(0x1, true, false)).unwrap().data(),
+            &[0xF006_0102, 0x0, 0x0, 0x0],
+        );
+        assert_eq!(
+            negotiator.active(),
+            StreamConfiguration {
+                protocol: Protocol::Midi1,
+                receive_jr_timestamps: true,
+                send_jr_timestamps: false,
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_unsupported_request() {
+        let mut negotiator = StreamNegotiator::new(MIDI1_ONLY);
+        assert_eq!(
+            negotiator.push(&request(0x2, true, true)).unwrap().data(),
+            &[0xF006_0100, 0x0, 0x0, 0x0],
+        );
+        assert_eq!(
+            negotiator.active(),
+            StreamNegotiator::new(MIDI1_ONLY).active()
+        );
+    }
+
+    #[test]
+    fn notification_sets_active_configuration() {
+        let mut notification = StreamConfigurationNotification::<[u32; 4]>::new();
+        notification.set_protocol(0x1);
+        notification.set_send_jr_timestamps(true);
+        let mut negotiator = StreamNegotiator::new(FULL);
+        assert_eq!(negotiator.push(&UmpStream::from(notification)), None);
+        assert_eq!(
+            negotiator.active(),
+            StreamConfiguration {
+                protocol: Protocol::Midi1,
+                receive_jr_timestamps: true,
+                send_jr_timestamps: false,
+            }
+        );
+    }
+
+    #[test]
+    fn notification_with_unknown_protocol_is_ignored() {
+        let mut notification = StreamConfigurationNotification::<[u32; 4]>::new();
+        notification.set_protocol(0x7);
+        let mut negotiator = StreamNegotiator::new(FULL);
+        negotiator.push(&UmpStream::from(notification));
+        assert_eq!(negotiator.active(), StreamConfiguration::default());
+    }
+
+    #[test]
+    fn host_and_endpoint_agree() {
+        let endpoint_capabilities = StreamCapabilities {
+            supports_receiving_jr_timestamps: true,
+            ..MIDI1_ONLY
+        };
+        let mut host = negotiator(FULL, endpoint_capabilities);
+        let mut endpoint = negotiator(endpoint_capabilities, FULL);
+
+        let notification = endpoint.push(&host.request().unwrap().into()).unwrap();
+        host.push(&notification.into());
+
+        assert_eq!(host.active(), host.agreed().unwrap());
+        assert_eq!(endpoint.active(), endpoint.agreed().unwrap());
+        assert_eq!(host.active(), endpoint.active().reversed());
+    }
+}
@@ -47,7 +47,7 @@ impl core::ops::Deref for Packet {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Format {
     Complete,
     Start,
@@ -0,0 +1,481 @@
+use crate::{
+    detail::{common_err_strings, helpers, BitOps},
+    error::InvalidData,
+    packet::size_from_ump_message_type,
+    UmpMessage,
+};
+
+/// Decodes the messages of a continuous stream of UMP words.
+///
+/// Each item is either a message borrowed from the stream along with
+/// its word offset, or an [UmpStreamDecodeError] describing the words which
+/// could not be interpreted.
+/// Packets with reserved (or disabled) UMP message types are reported
+/// as errors and skipped over according to the packet size given by the
+/// specification.
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let buffer = [
+///     0x2D9E_753D, // midi1 note on
+///     0x3016_0001, // sysex7 start
+///     0x0203_0405,
+///     0x3032_0607, // sysex7 end
+///     0x0000_0000,
+///     0x6000_0000, // reserved
+///     0x489A_5E00, // midi2 note on
+///     0x6A14_0000,
+///     0x4090_0000, // a partial trailing packet
+/// ];
+///
+/// let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+///
+/// let (offset, message) = decoder.next().unwrap().unwrap();
+/// assert_eq!(offset, 0);
+/// assert!(matches!(message, UmpMessage::ChannelVoice1(_)));
+///
+/// let (offset, message) = decoder.next().unwrap().unwrap();
+/// assert_eq!(offset, 1);
+/// assert!(matches!(message, UmpMessage::Sysex7(_)));
+///
+/// let error = decoder.next().unwrap().unwrap_err();
+/// assert_eq!(error.offset, 5);
+/// assert_eq!(error.size, 1);
+///
+/// let (offset, message) = decoder.next().unwrap().unwrap();
+/// assert_eq!(offset, 6);
+/// assert!(matches!(message, UmpMessage::ChannelVoice2(_)));
+///
+/// assert_eq!(decoder.next(), None);
+/// assert_eq!(decoder.remainder(), &[0x4090_0000]);
+/// ```
+///
+/// The decoder stops at the first incomplete packet, or at a multi-packet
+/// message whose end packet has not been received yet.
+/// The unconsumed words are available through [remainder](UmpStreamDecoder::remainder)
+/// so that they can be prepended to the next chunk of the stream.
+#[derive(Debug, Clone)]
+pub struct UmpStreamDecoder<'a> {
+    buffer: &'a [u32],
+    offset: usize,
+}
+
+/// Describes a region of a UMP stream which could not be decoded.
+///
+/// See [UmpStreamDecoder].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UmpStreamDecodeError {
+    /// The offset of the first word of the region.
+    pub offset: usize,
+    /// The number of words skipped over.
+    pub size: usize,
+    pub error: InvalidData,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UmpStreamDecodeError {}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for UmpStreamDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        <Self as std::fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<'a> UmpStreamDecoder<'a> {
+    pub fn new(buffer: &'a [u32]) -> Self {
+        UmpStreamDecoder { buffer, offset: 0 }
+    }
+
+    /// The offset of the next word to be decoded.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The words which have not been consumed by the decoder.
+    pub fn remainder(&self) -> &'a [u32] {
+        &self.buffer[self.offset..]
+    }
+}
+
+impl<'a> core::iter::Iterator for UmpStreamDecoder<'a> {
+    type Item = Result<(usize, UmpMessage<&'a [u32]>), UmpStreamDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        let data = self.remainder();
+        let size = match message_extent(data) {
+            Extent::Incomplete => return None,
+            Extent::Message(size) => size,
+            Extent::Interrupted(size) => {
+                self.offset += size;
+                return Some(Err(UmpStreamDecodeError {
+                    offset,
+                    size,
+                    error: InvalidData(helpers::ERR_SYSEX_EXPECTED_END),
+                }));
+            }
+        };
+
+        self.offset += size;
+
+        if !is_supported_ump_message_type(u8::from(data[0].nibble(0))) {
+            return Some(Err(UmpStreamDecodeError {
+                offset,
+                size,
+                error: InvalidData(common_err_strings::ERR_INCORRECT_UMP_MESSAGE_TYPE),
+            }));
+        }
+
+        Some(
+            UmpMessage::try_from(&data[..size])
+                .map(|message| (offset, message))
+                .map_err(|error| UmpStreamDecodeError {
+                    offset,
+                    size,
+                    error,
+                }),
+        )
+    }
+}
+
+impl core::iter::FusedIterator for UmpStreamDecoder<'_> {}
+
+enum Extent {
+    // not enough data in the buffer to read the whole message
+    Incomplete,
+    // the size of the message in words
+    Message(usize),
+    // the first n words begin a multi-packet message
+    // which is interrupted by an unrelated packet
+    Interrupted(usize),
+}
+
+fn message_extent(data: &[u32]) -> Extent {
+    if data.is_empty() {
+        return Extent::Incomplete;
+    }
+
+    let ump_type = u8::from(data[0].nibble(0));
+    let packet_size = size_from_ump_message_type(ump_type);
+    if data.len() < packet_size {
+        return Extent::Incomplete;
+    }
+
+    let Some((mut format, key)) = multi_packet_format(data[0]) else {
+        return Extent::Message(packet_size);
+    };
+    if format != Format::Start {
+        // complete packets and stray continue / end packets are
+        // reported as single packet messages
+        return Extent::Message(packet_size);
+    }
+
+    let mut size = packet_size;
+    while format != Format::End {
+        let Some(packet) = data.get(size..size + packet_size) else {
+            return Extent::Incomplete;
+        };
+        match multi_packet_format(packet[0]) {
+            Some((f @ (Format::Continue | Format::End), k)) if k == key => {
+                format = f;
+                size += packet_size;
+            }
+            _ => return Extent::Interrupted(size),
+        }
+    }
+
+    Extent::Message(size)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Complete,
+    Start,
+    Continue,
+    End,
+    Invalid,
+}
+
+impl From<u8> for Format {
+    fn from(v: u8) -> Self {
+        match v {
+            0x0 => Format::Complete,
+            0x1 => Format::Start,
+            0x2 => Format::Continue,
+            0x3 => Format::End,
+            _ => Format::Invalid,
+        }
+    }
+}
+
+// The packet format and the identifying fields which must remain
+// constant across all the packets of a multi-packet message.
+fn multi_packet_format(first_word: u32) -> Option<(Format, u32)> {
+    match u8::from(first_word.nibble(0)) {
+        #[cfg(feature = "sysex7")]
+        crate::sysex7::UMP_MESSAGE_TYPE => Some((
+            u8::from(first_word.nibble(2)).into(),
+            first_word & 0xFF00_0000,
+        )),
+        #[cfg(feature = "sysex8")]
+        crate::sysex8::UMP_MESSAGE_TYPE => Some((
+            u8::from(first_word.nibble(2)).into(),
+            first_word & 0xFF00_FF00,
+        )),
+        #[cfg(feature = "flex-data")]
+        crate::flex_data::UMP_MESSAGE_TYPE => Some((
+            u8::from(first_word.crumb(4)).into(),
+            first_word & 0xFF3F_FFFF,
+        )),
+        #[cfg(feature = "ump-stream")]
+        crate::ump_stream::UMP_MESSAGE_TYPE => Some((
+            u8::from(first_word.crumb(2)).into(),
+            first_word & 0xF3FF_0000,
+        )),
+        _ => None,
+    }
+}
+
+fn is_supported_ump_message_type(ump_type: u8) -> bool {
+    match ump_type {
+        #[cfg(feature = "channel-voice1")]
+        crate::channel_voice1::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "channel-voice2")]
+        crate::channel_voice2::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "flex-data")]
+        crate::flex_data::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "sysex7")]
+        crate::sysex7::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "sysex8")]
+        crate::sysex8::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "system-common")]
+        crate::system_common::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "ump-stream")]
+        crate::ump_stream::UMP_MESSAGE_TYPE => true,
+        #[cfg(feature = "utility")]
+        crate::utility::UMP_MESSAGE_TYPE => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(
+        feature = "channel-voice2",
+        feature = "flex-data",
+        feature = "sysex7",
+        feature = "ump-stream"
+    ))]
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty_buffer() {
+        let mut decoder = UmpStreamDecoder::new(&[]);
+        assert_eq!(decoder.next(), None);
+        assert!(decoder.remainder().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn offsets() {
+        let buffer = [0x4090_0000, 0x0, 0x4091_0000, 0x0, 0x4092_0000, 0x0];
+        assert_eq!(
+            UmpStreamDecoder::new(&buffer[..])
+                .map(|item| item.unwrap().0)
+                .collect::<std::vec::Vec<usize>>(),
+            std::vec![0, 2, 4],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn message_data() {
+        let buffer = [0x4090_0000, 0x1234_0000, 0x4091_0000, 0x5678_0000];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        assert_eq!(
+            decoder.next().unwrap().unwrap().1.data(),
+            &[0x4090_0000, 0x1234_0000]
+        );
+        assert_eq!(
+            decoder.next().unwrap().unwrap().1.data(),
+            &[0x4091_0000, 0x5678_0000]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn partial_trailing_packet() {
+        let buffer = [0x4090_0000, 0x0, 0x4091_0000];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        assert!(decoder.next().is_some());
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.offset(), 2);
+        assert_eq!(decoder.remainder(), &[0x4091_0000]);
+    }
+
+    #[test]
+    fn reserved_message_types() {
+        let buffer = [
+            0x6000_0000,
+            0x7000_0000,
+            0x8000_0000,
+            0x0,
+            0x9000_0000,
+            0x0,
+            0xA000_0000,
+            0x0,
+            0xB000_0000,
+            0x0,
+            0x0,
+            0xC000_0000,
+            0x0,
+            0x0,
+            0xE000_0000,
+            0x0,
+            0x0,
+            0x0,
+        ];
+        assert_eq!(
+            UmpStreamDecoder::new(&buffer[..])
+                .map(|item| {
+                    let error = item.unwrap_err();
+                    (error.offset, error.size)
+                })
+                .collect::<std::vec::Vec<(usize, usize)>>(),
+            std::vec![
+                (0, 1),
+                (1, 1),
+                (2, 2),
+                (4, 2),
+                (6, 2),
+                (8, 3),
+                (11, 3),
+                (14, 4)
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn consecutive_sysex7_messages() {
+        let buffer = [
+            0x3016_0001,
+            0x0203_0405,
+            0x3032_0607,
+            0x0000_0000,
+            0x3003_0809,
+            0x0A00_0000,
+        ];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        let (offset, message) = decoder.next().unwrap().unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(message.data(), &buffer[..4]);
+        let (offset, message) = decoder.next().unwrap().unwrap();
+        assert_eq!(offset, 4);
+        assert_eq!(message.data(), &buffer[4..]);
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex7_awaiting_end_packet() {
+        let buffer = [0x3016_0001, 0x0203_0405, 0x3026_0607, 0x0809_0A0B];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.remainder(), &buffer[..]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn interrupted_sysex7() {
+        let buffer = [
+            0x3016_0001,
+            0x0203_0405,
+            0x2D9E_753D,
+            0x3032_0607,
+            0x0000_0000,
+        ];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        assert_eq!(
+            decoder.next(),
+            Some(Err(UmpStreamDecodeError {
+                offset: 0,
+                size: 2,
+                error: InvalidData(helpers::ERR_SYSEX_EXPECTED_END),
+            }))
+        );
+        assert_eq!(decoder.next().unwrap().unwrap().0, 2);
+        assert!(decoder.next().unwrap().is_err());
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex7_interrupted_by_other_group() {
+        let buffer = [0x3016_0001, 0x0203_0405, 0x3116_0001, 0x0203_0405];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        assert_eq!(decoder.next().unwrap().unwrap_err().size, 2);
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.remainder(), &buffer[2..]);
+    }
+
+    #[test]
+    #[cfg(feature = "flex-data")]
+    fn flex_data_text() {
+        let buffer = [
+            0xD050_0101,
+            0x5368_6164,
+            0x6F77_7320,
+            0x6F66_2074,
+            0xD090_0101,
+            0x6865_2046,
+            0x6F72_676F,
+            0x7474_656E,
+            0xD0D0_0101,
+            0x2043_6174,
+            0x6865_6472,
+            0x616C_0000,
+        ];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        let (offset, message) = decoder.next().unwrap().unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(message.data(), &buffer[..]);
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "ump-stream")]
+    fn ump_stream_text() {
+        let buffer = [
+            0xF403_4769,
+            0x6D6D_6520,
+            0x736F_6D65,
+            0x2073_6967,
+            0xFC03_6E61,
+            0x6C20_F09F,
+            0x9A80_0000,
+            0x0000_0000,
+        ];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        let (offset, message) = decoder.next().unwrap().unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(message.data(), &buffer[..]);
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "utility")]
+    fn invalid_message_is_skipped() {
+        // utility message with an unknown status
+        let buffer = [0x00F0_0000, 0x0000_0000];
+        let mut decoder = UmpStreamDecoder::new(&buffer[..]);
+        let error = decoder.next().unwrap().unwrap_err();
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.size, 1);
+        assert_eq!(decoder.next().unwrap().unwrap().0, 1);
+    }
+}
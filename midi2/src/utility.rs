@@ -153,12 +153,14 @@ mod delta_clockstamp_tpq {
         time_data: u16,
     }
 }
+mod jitter_reduction;
 
 pub(crate) const UMP_MESSAGE_TYPE: u8 = 0x0;
 
 pub use clock::Clock;
 pub use delta_clockstamp::DeltaClockstamp;
 pub use delta_clockstamp_tpq::DeltaClockstampTpq;
+pub use jitter_reduction::{JrSender, JrTracker, JR_CLOCK_INTERVAL, JR_TICK};
 pub use no_op::NoOp;
 pub use packet::Packet;
 pub use timestamp::Timestamp;
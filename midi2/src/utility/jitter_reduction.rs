@@ -0,0 +1,416 @@
+use crate::{
+    buffer::Ump,
+    traits::Data,
+    utility::{Clock, Timestamp, Utility},
+    UmpMessage,
+};
+
+/// The duration of one unit of JR Clock and JR Timestamp time data: 1/31250 seconds.
+pub const JR_TICK: core::time::Duration = core::time::Duration::from_micros(32);
+
+/// The longest time a [JrSender] waits between [Clock] messages.
+pub const JR_CLOCK_INTERVAL: core::time::Duration = core::time::Duration::from_millis(250);
+
+const JR_TICK_NANOS: i128 = 32_000;
+
+/// Tracks the clock of a sender of Jitter Reduction [Clock] and [Timestamp]
+/// messages against the local clock of the receiver.
+///
+/// Every [Clock] message gives a sample of the offset between the sender's clock
+/// and the local clock. Transmission delays only ever make the offset appear larger,
+/// so the tracker follows any sample below its estimate straight away
+/// and drifts slowly towards samples above it.
+///
+/// Messages preceded by a [Timestamp] are mapped to the local time at which
+/// they should be presented: the time at which they were sent according to the
+/// sender's clock, plus the estimated offset and a configurable latency.
+/// The latency should cover the largest expected transmission jitter.
+///
+/// The 16 bit time data of the messages wraps around every ~2.1 seconds.
+/// The tracker unwraps the time data relative to the previous [Clock] message,
+/// so the sender must send [Clock] messages at least once a second.
+///
+/// Local times are given as a [Duration](core::time::Duration)
+/// since an arbitrary epoch of a monotonic clock.
+///
+/// ```rust
+/// use midi2::{
+///     prelude::*,
+///     utility::{Clock, JrTracker, Timestamp, Utility},
+/// };
+/// use core::time::Duration;
+///
+/// let mut tracker = JrTracker::new();
+/// tracker.set_latency(Duration::from_millis(5));
+///
+/// let mut clock = Clock::<[u32; 4]>::new();
+/// clock.set_time_data(1000);
+/// tracker.push(Duration::from_secs(10), &UmpMessage::from(Utility::from(clock)));
+///
+/// // a message sent 100 ticks (3.2 ms) after the clock
+/// let mut timestamp = Timestamp::<[u32; 4]>::new();
+/// timestamp.set_time_data(1100);
+/// tracker.push(Duration::from_secs(10), &UmpMessage::from(Utility::from(timestamp)));
+///
+/// let note_on = UmpMessage::try_from(&[0x4090_3C00, 0xFFFF_0000][..]).unwrap();
+/// assert_eq!(
+///     tracker.push(Duration::from_millis(10_001), &note_on),
+///     Some(Duration::from_micros(10_008_200)),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JrTracker {
+    latency: core::time::Duration,
+    // the unwrapped time data of the last clock message
+    sender_time: i64,
+    // the estimated local time minus the sender's time in nanoseconds
+    offset: Option<i128>,
+    // the unwrapped time data of the pending timestamp
+    timestamp: Option<i64>,
+}
+
+// samples above the estimated offset move the estimate
+// by this fraction of the difference
+const OFFSET_DRIFT_DIVISOR: i128 = 16;
+
+impl JrTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the sender's clock and any pending timestamp.
+    pub fn reset(&mut self) {
+        *self = JrTracker {
+            latency: self.latency,
+            ..Default::default()
+        };
+    }
+
+    pub fn latency(&self) -> core::time::Duration {
+        self.latency
+    }
+
+    pub fn set_latency(&mut self, latency: core::time::Duration) {
+        self.latency = latency;
+    }
+
+    /// Whether a [Clock] message has been received since construction or reset.
+    pub fn is_synchronized(&self) -> bool {
+        self.offset.is_some()
+    }
+
+    /// Process a message received at the local time `now`.
+    ///
+    /// [Clock] messages update the estimate of the sender's clock
+    /// and [Timestamp] messages apply to the following message.
+    ///
+    /// Returns the local presentation time of a message preceded by a [Timestamp].
+    /// Returns `None` for utility messages, for messages without a timestamp,
+    /// or if no [Clock] message has been received yet.
+    /// These should be presented straight away.
+    pub fn push<B: Ump>(
+        &mut self,
+        now: core::time::Duration,
+        message: &UmpMessage<B>,
+    ) -> Option<core::time::Duration> {
+        match Utility::try_from(message.data()) {
+            Ok(Utility::Clock(clock)) => {
+                self.push_clock(now, &clock);
+                None
+            }
+            Ok(Utility::Timestamp(timestamp)) => {
+                self.timestamp = Some(self.unwrap(timestamp.time_data()));
+                None
+            }
+            Ok(_) => None,
+            Err(_) => {
+                let timestamp = self.timestamp.take()?;
+                self.local_time(timestamp)
+            }
+        }
+    }
+
+    /// The local presentation time of the given sender time data.
+    ///
+    /// Returns `None` if no [Clock] message has been received yet.
+    pub fn presentation_time(&self, time_data: u16) -> Option<core::time::Duration> {
+        self.local_time(self.unwrap(time_data))
+    }
+
+    fn push_clock<B: Ump>(&mut self, now: core::time::Duration, clock: &Clock<B>) {
+        let sender_time = match self.offset {
+            Some(_) => self.unwrap(clock.time_data()),
+            None => i64::from(clock.time_data()),
+        };
+        self.sender_time = sender_time;
+
+        let sample = now.as_nanos() as i128 - i128::from(sender_time) * JR_TICK_NANOS;
+        self.offset = Some(match self.offset {
+            Some(offset) if sample > offset => offset + (sample - offset) / OFFSET_DRIFT_DIVISOR,
+            _ => sample,
+        });
+    }
+
+    // the closest time to the last clock with the given time data
+    fn unwrap(&self, time_data: u16) -> i64 {
+        let delta = time_data.wrapping_sub(self.sender_time as u16) as i16;
+        self.sender_time + i64::from(delta)
+    }
+
+    fn local_time(&self, sender_time: i64) -> Option<core::time::Duration> {
+        let nanos = i128::from(sender_time) * JR_TICK_NANOS
+            + self.offset?
+            + self.latency.as_nanos() as i128;
+        let nanos = nanos.max(0) as u128;
+        Some(core::time::Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        ))
+    }
+}
+
+/// Produces Jitter Reduction [Clock] and [Timestamp] messages
+/// from the local clock of a sender.
+///
+/// Local times are given as a [Duration](core::time::Duration)
+/// since an arbitrary epoch of a monotonic clock.
+/// The time data of the messages counts units of [JR_TICK] since the epoch,
+/// wrapping around at 16 bits.
+///
+/// ```rust
+/// use midi2::{prelude::*, utility::JrSender};
+/// use core::time::Duration;
+///
+/// let mut sender = JrSender::new();
+///
+/// // the first poll always produces a clock message
+/// let clock = sender.clock(Duration::from_millis(100)).unwrap();
+/// assert_eq!(clock.time_data(), 3125);
+/// assert_eq!(sender.clock(Duration::from_millis(200)), None);
+/// assert!(sender.clock(Duration::from_millis(350)).is_some());
+///
+/// let timestamp = sender.timestamp(Duration::from_millis(400));
+/// assert_eq!(timestamp.time_data(), 12500);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JrSender {
+    last_clock: Option<core::time::Duration>,
+}
+
+impl JrSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The time data of the given local time.
+    pub fn time_data(now: core::time::Duration) -> u16 {
+        (now.as_nanos() / JR_TICK_NANOS as u128) as u16
+    }
+
+    /// A [Clock] message if one is due at the local time `now`.
+    ///
+    /// A clock message is due if none has been produced yet
+    /// or if [JR_CLOCK_INTERVAL] has passed since the last one.
+    /// This should be polled regularly, including while no other messages are sent.
+    pub fn clock(&mut self, now: core::time::Duration) -> Option<Clock<[u32; 4]>> {
+        if matches!(self.last_clock, Some(last) if now.saturating_sub(last) < JR_CLOCK_INTERVAL) {
+            return None;
+        }
+        self.last_clock = Some(now);
+        let mut clock = Clock::<[u32; 4]>::new();
+        clock.set_time_data(Self::time_data(now));
+        Some(clock)
+    }
+
+    /// A [Timestamp] message for a message sent at the local time `now`.
+    pub fn timestamp(&self, now: core::time::Duration) -> Timestamp<[u32; 4]> {
+        let mut timestamp = Timestamp::<[u32; 4]>::new();
+        timestamp.set_time_data(Self::time_data(now));
+        timestamp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+    use pretty_assertions::assert_eq;
+
+    fn clock(time_data: u16) -> UmpMessage<[u32; 4]> {
+        let mut message = Clock::<[u32; 4]>::new();
+        message.set_time_data(time_data);
+        Utility::from(message).into()
+    }
+
+    #[cfg(feature = "system-common")]
+    fn timestamp(time_data: u16) -> UmpMessage<[u32; 4]> {
+        let mut message = Timestamp::<[u32; 4]>::new();
+        message.set_time_data(time_data);
+        Utility::from(message).into()
+    }
+
+    #[cfg(feature = "system-common")]
+    fn no_op() -> UmpMessage<[u32; 4]> {
+        Utility::from(crate::utility::NoOp::<[u32; 4]>::new()).into()
+    }
+
+    #[cfg(feature = "system-common")]
+    fn timing_clock() -> UmpMessage<[u32; 4]> {
+        crate::system_common::SystemCommon::from(
+            crate::system_common::TimingClock::<[u32; 4]>::new(),
+        )
+        .into()
+    }
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn micros(micros: u64) -> Duration {
+        Duration::from_micros(micros)
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn not_synchronized() {
+        let mut tracker = JrTracker::new();
+        assert!(!tracker.is_synchronized());
+        tracker.push(millis(10), &timestamp(100));
+        assert_eq!(tracker.push(millis(10), &timing_clock()), None);
+        assert_eq!(tracker.presentation_time(100), None);
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn message_without_timestamp() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(10), &clock(0));
+        assert!(tracker.is_synchronized());
+        assert_eq!(tracker.push(millis(10), &timing_clock()), None);
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn timestamp_applies_to_next_message_only() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(10), &clock(0));
+        tracker.push(millis(10), &timestamp(125));
+        assert_eq!(tracker.push(millis(10), &no_op()), None);
+        assert_eq!(tracker.push(millis(10), &timing_clock()), Some(millis(14)));
+        assert_eq!(tracker.push(millis(10), &timing_clock()), None);
+    }
+
+    #[test]
+    fn latency() {
+        let mut tracker = JrTracker::new();
+        tracker.set_latency(millis(3));
+        tracker.push(millis(10), &clock(0));
+        assert_eq!(tracker.presentation_time(125), Some(millis(17)));
+    }
+
+    #[test]
+    fn reset_keeps_latency() {
+        let mut tracker = JrTracker::new();
+        tracker.set_latency(millis(3));
+        tracker.push(millis(10), &clock(0));
+        tracker.reset();
+        assert!(!tracker.is_synchronized());
+        assert_eq!(tracker.latency(), millis(3));
+    }
+
+    #[test]
+    fn timestamp_before_clock() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(10), &clock(0));
+        // 100 ticks before the clock, wrapped around
+        assert_eq!(tracker.presentation_time(0xFFFF - 99), Some(micros(6_800)));
+    }
+
+    #[test]
+    fn presentation_time_before_epoch_saturates() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(1), &clock(1000));
+        assert_eq!(tracker.presentation_time(0), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn clock_wraparound() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(0), &clock(0xFF00));
+        // 0x100 ticks later the time data wraps
+        tracker.push(micros(0x100 * 32), &clock(0x0000));
+        assert_eq!(tracker.presentation_time(0x0010), Some(micros(0x110 * 32)));
+        // over many wraps
+        let mut now = micros(0x100 * 32);
+        let mut time_data = 0_u16;
+        for _ in 0..100 {
+            now += millis(200);
+            time_data = time_data.wrapping_add(6250);
+            tracker.push(now, &clock(time_data));
+        }
+        assert_eq!(tracker.presentation_time(time_data), Some(now));
+    }
+
+    #[test]
+    fn delayed_clock_drifts_offset() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(0), &clock(0));
+        // a clock delayed by 1.6 ms moves the estimate by a sixteenth
+        tracker.push(micros(3_200 + 1_600), &clock(100));
+        assert_eq!(tracker.presentation_time(100), Some(micros(3_300)));
+    }
+
+    #[test]
+    fn early_clock_resets_offset() {
+        let mut tracker = JrTracker::new();
+        tracker.push(millis(10), &clock(0));
+        // a clock with less transmission delay than the first
+        tracker.push(micros(10_000 + 3_200 - 1_000), &clock(100));
+        assert_eq!(tracker.presentation_time(100), Some(micros(12_200)));
+    }
+
+    #[test]
+    fn sender_time_data() {
+        assert_eq!(JrSender::time_data(Duration::ZERO), 0);
+        assert_eq!(JrSender::time_data(micros(31)), 0);
+        assert_eq!(JrSender::time_data(micros(32)), 1);
+        assert_eq!(JrSender::time_data(Duration::from_secs(1)), 31250);
+        // wraps around at 16 bits
+        assert_eq!(JrSender::time_data(micros(0x1_0001 * 32)), 1);
+    }
+
+    #[test]
+    fn sender_clock_interval() {
+        let mut sender = JrSender::new();
+        assert!(sender.clock(millis(0)).is_some());
+        assert_eq!(sender.clock(millis(249)), None);
+        assert_eq!(sender.clock(millis(250)).map(|c| c.time_data()), Some(7812));
+        assert_eq!(sender.clock(millis(300)), None);
+    }
+
+    #[test]
+    fn sender_timestamp() {
+        let sender = JrSender::new();
+        assert_eq!(sender.timestamp(millis(4)).data(), &[0x0020_007D]);
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn sender_to_tracker() {
+        let mut sender = JrSender::new();
+        let mut tracker = JrTracker::new();
+        let delay = millis(2);
+
+        let sent = millis(5_000);
+        let clock = sender.clock(sent).unwrap();
+        tracker.push(sent + delay, &Utility::from(clock).into());
+
+        let sent = millis(5_100);
+        let timestamp = sender.timestamp(sent);
+        tracker.push(sent + delay, &Utility::from(timestamp).into());
+        assert_eq!(
+            tracker.push(sent + delay, &timing_clock()),
+            Some(sent + delay)
+        );
+    }
+}
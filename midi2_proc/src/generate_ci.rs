@@ -410,12 +410,9 @@ fn ci_version_impls(root_ident: &syn::Ident, args: &GenerateCiArgs) -> TokenStre
     let mut ret = TokenStream::new();
 
     for version in args.supported_versions.iter().map(|v| v.version) {
-        for compatible_version in args
-            .supported_versions
-            .iter()
-            .filter(|v| v.version <= version)
-            .map(|v| v.version)
-        {
+        // messages introduced in later versions still carry the
+        // properties defined by earlier versions (e.g. the common header)
+        for compatible_version in 0x1..=version {
             ret.extend(quote!{
                 impl<B: crate::buffer::Bytes> crate::ci::version::CiVersion<#compatible_version> for #root_ident<#version, B> {}
             });
@@ -556,6 +553,33 @@ fn try_from_slice_impl(root_ident: &syn::Ident, properties: &[Property]) -> Toke
     }
 }
 
+fn try_from_sysex7_impl(root_ident: &syn::Ident) -> TokenStream {
+    let std_only_attribute = common::std_only_attribute(true);
+    quote! {
+        impl<'a, const VERSION: u8> core::convert::TryFrom<crate::sysex7::Sysex7<&'a [u8]>> for #root_ident<VERSION, &'a [u8]> {
+            type Error = crate::error::InvalidData;
+            fn try_from(sysex7: crate::sysex7::Sysex7<&'a [u8]>) -> core::result::Result<Self, Self::Error> {
+                let buffer: &'a [u8] = *sysex7.buffer_access();
+                Self::try_from(buffer)
+            }
+        }
+
+        #std_only_attribute
+        impl<const VERSION: u8, B: crate::buffer::Buffer> core::convert::TryFrom<&crate::sysex7::Sysex7<B>> for #root_ident<VERSION, std::vec::Vec<u8>> {
+            type Error = crate::error::InvalidData;
+            fn try_from(sysex7: &crate::sysex7::Sysex7<B>) -> core::result::Result<Self, Self::Error> {
+                use crate::traits::Sysex;
+                let mut buffer = std::vec::Vec::with_capacity(sysex7.payload_size() + 2);
+                buffer.push(0xF0);
+                buffer.extend(sysex7.payload().map(u8::from));
+                buffer.push(0xF7);
+                let borrowed = #root_ident::<VERSION, &[u8]>::try_from(&buffer[..])?;
+                Ok(<Self as crate::traits::RebufferFrom<#root_ident<VERSION, &[u8]>>>::rebuffer_from(borrowed))
+            }
+        }
+    }
+}
+
 fn rebuffer_from_impl(root_ident: &syn::Ident) -> TokenStream {
     quote! {
         impl<
@@ -603,6 +627,7 @@ pub fn generate_ci(attrs: TokenStream1, item: TokenStream1) -> TokenStream1 {
     let message_impl = message_impl(root_ident, &properties);
     let ci_impl = ci_impl(root_ident);
     let try_from_slice_impl = try_from_slice_impl(root_ident, &properties);
+    let try_from_sysex7_impl = try_from_sysex7_impl(root_ident);
     let rebuffer_from_impl = rebuffer_from_impl(root_ident);
     let try_rebuffer_from_impl = try_rebuffer_from_impl(root_ident);
 
@@ -619,6 +644,7 @@ pub fn generate_ci(attrs: TokenStream1, item: TokenStream1) -> TokenStream1 {
         #message_impl
         #ci_impl
         #try_from_slice_impl
+        #try_from_sysex7_impl
         #ci_version_impls
         #rebuffer_from_impl
         #try_rebuffer_from_impl